@@ -1,4 +1,5 @@
 use anyhow::Context;
+use moq_transport::data::ObjectStatus;
 use moq_transport::serve::{
     DatagramsReader, StreamReader, Subgroup, SubgroupWriter, SubgroupsReader, SubgroupsWriter,
     TrackReader, TrackReaderMode,
@@ -88,6 +89,9 @@ impl Publisher {
             // Get the current time again to check if we overslept
             let next = Utc::now();
             if next.minute() != now.minute() {
+                subgroup_writer
+                    .write_status(ObjectStatus::EndOfGroup)
+                    .context("failed to end group")?;
                 return Ok(());
             }
 
@@ -1,6 +1,7 @@
 use moq_native_ietf::quic;
 
 use anyhow::Context;
+use url::Url;
 
 mod cli;
 mod clock;
@@ -11,7 +12,7 @@ use cli::Cli;
 use moq_transport::{
     coding::TrackNamespace,
     serve,
-    session::{Publisher, Subscriber},
+    session::{Publisher, Session, SessionConfig, Subscriber},
 };
 
 /// The main entry point for the MoQ Clock IETF example.
@@ -48,26 +49,24 @@ async fn main() -> anyhow::Result<()> {
     // Depending on whether we are publishing or subscribing, create the appropriate session
     if config.publish {
         // Create the publisher session
-        let (session, mut publisher) = Publisher::connect(session)
+        let (session, mut publisher, subscriber) = Session::connect(session, None)
             .await
             .context("failed to create MoQ Transport session")?;
 
-        let (mut tracks_writer, _, tracks_reader) = serve::Tracks {
-            namespace: TrackNamespace::from_utf8_path(&config.namespace),
-        }
-        .produce();
+        let (mut tracks_writer, _, tracks_reader) =
+            serve::Tracks::new(TrackNamespace::from_utf8_path(&config.namespace)).produce();
 
         let track_writer = tracks_writer.create(&config.track).unwrap();
         let clock_publisher = clock::Publisher::new(track_writer.subgroups()?);
 
         tokio::select! {
-            res = session.run() => res.context("session error")?,
+            res = run_session(&quic, &config.url, session, publisher.clone(), subscriber) => res?,
             res = clock_publisher.run() => res.context("clock error")?,
             res = publisher.announce(tracks_reader) => res.context("failed to serve tracks")?,
         }
     } else {
         // Create the subscriber session
-        let (session, mut subscriber) = Subscriber::connect(session)
+        let (session, publisher, mut subscriber) = Session::connect(session, None)
             .await
             .context("failed to create MoQ Transport session")?;
 
@@ -75,7 +74,15 @@ async fn main() -> anyhow::Result<()> {
 
         if config.track_status {
             // Request a track_status for the clock track (testing purposes only)
-            subscriber.track_status(&track_namespace, &config.track);
+            let mut subscriber = subscriber.clone();
+            let track_namespace = track_namespace.clone();
+            let track_name = config.track.clone();
+            tokio::spawn(async move {
+                match subscriber.track_status(&track_namespace, &track_name).await {
+                    Ok(status) => println!("track status: {:?}", status),
+                    Err(err) => println!("track status error: {}", err),
+                }
+            });
         }
 
         let (track_writer, track_reader) =
@@ -84,7 +91,7 @@ async fn main() -> anyhow::Result<()> {
         let clock_subscriber = clock::Subscriber::new(track_reader);
 
         tokio::select! {
-            res = session.run() => res.context("session error")?,
+            res = run_session(&quic, &config.url, session, publisher, subscriber.clone()) => res?,
             res = clock_subscriber.run() => res.context("clock error")?,
             res = subscriber.subscribe(track_writer) => res.context("failed to subscribe to track")?,
         }
@@ -92,3 +99,59 @@ async fn main() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Run the session, reconnecting whenever the server sends a GOAWAY.
+/// Subscriptions and announces move to the new session, while the old one keeps running until it drains.
+async fn run_session(
+    quic: &quic::Endpoint,
+    url: &Url,
+    session: Session,
+    mut publisher: Publisher,
+    mut subscriber: Subscriber,
+) -> anyhow::Result<()> {
+    // Each session is tagged with a count, so we know when the current one ends.
+    let mut sessions = tokio::task::JoinSet::new();
+    let mut current = 0;
+
+    let mut handle = session.handle();
+    sessions.spawn(async move { (0, session.run().await) });
+
+    loop {
+        tokio::select! {
+            Some(uri) = handle.go_away_received() => {
+                // An empty URI means reconnecting to the same one.
+                let url = match uri.as_str() {
+                    "" => url.clone(),
+                    uri => Url::parse(uri).context("invalid GOAWAY uri")?,
+                };
+
+                log::info!("received GOAWAY, migrating to: url={}", url);
+
+                let (session, _) = quic.client.connect(&url).await?;
+                let (session, new_publisher, new_subscriber) =
+                    Session::migrate(session, &mut publisher, &mut subscriber, SessionConfig::default())
+                        .await
+                        .context("failed to migrate MoQ Transport session")?;
+
+                publisher = new_publisher;
+                subscriber = new_subscriber;
+                handle = session.handle();
+                current += 1;
+
+                let id = current;
+                sessions.spawn(async move { (id, session.run().await) });
+            },
+            Some(res) = sessions.join_next() => {
+                let (id, res) = res?;
+                if id == current {
+                    return res.context("session error");
+                }
+
+                // A drained session ended, which is expected once migrated.
+                if let Err(err) = res {
+                    log::debug!("old session closed: {}", err);
+                }
+            },
+        }
+    }
+}
@@ -1,12 +1,19 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
 use anyhow::Context;
 use futures::{stream::FuturesUnordered, FutureExt, StreamExt};
 use moq_transport::{
-    serve::Tracks,
-    session::{Announced, SessionError, Subscriber},
+    coding::TrackNamespace,
+    serve::{ServeError, Tracks, TracksWriter},
+    session::{Announced, Published, SessionError, Subscriber},
 };
 
 use crate::{Api, Locals, Producer};
 
+/// The number of recent groups kept for each local track, so FETCH can be served from the relay.
+const CACHED_GROUPS: usize = 8;
+
 /// Consumer of tracks from a remote Publisher
 #[derive(Clone)]
 pub struct Consumer {
@@ -14,6 +21,9 @@ pub struct Consumer {
     locals: Locals,
     api: Option<Api>,
     forward: Option<Producer>, // Forward all announcements to this subscriber
+
+    // Writers for the namespaces announced by the remote, so pushed tracks can be added to them
+    tracks: Arc<Mutex<HashMap<TrackNamespace, TracksWriter>>>,
 }
 
 impl Consumer {
@@ -28,17 +38,22 @@ impl Consumer {
             locals,
             api,
             forward,
+            tracks: Default::default(),
         }
     }
 
     /// Run the consumer to serve announce requests.
-    pub async fn run(mut self) -> Result<(), SessionError> {
-        let mut tasks = FuturesUnordered::new();
+    pub async fn run(self) -> Result<(), SessionError> {
+        let mut tasks: FuturesUnordered<futures::future::BoxFuture<'static, ()>> =
+            FuturesUnordered::new();
 
         loop {
+            let mut remote_announced = self.remote.clone();
+            let mut remote_published = self.remote.clone();
+
             tokio::select! {
                 // Handle a new announce request
-                Some(announce) = self.remote.announced() => {
+                Some(announce) = remote_announced.announced() => {
                     let this = self.clone();
 
                     tasks.push(async move {
@@ -49,7 +64,21 @@ impl Consumer {
                         if let Err(err) = this.serve(announce).await {
                             log::warn!("failed serving announce: {:?}, error: {}", info, err)
                         }
-                    });
+                    }.boxed());
+                },
+                // Handle a new publish request
+                Some(published) = remote_published.published() => {
+                    let this = self.clone();
+
+                    tasks.push(async move {
+                        let info = published.info.clone();
+                        log::info!("serving publish: {:?}", info);
+
+                        // Serve the publish request
+                        if let Err(err) = this.serve_publish(published).await {
+                            log::warn!("failed serving publish: {:?}, error: {}", info, err)
+                        }
+                    }.boxed());
                 },
                 _ = tasks.next(), if !tasks.is_empty() => {},
                 else => return Ok(()),
@@ -57,12 +86,33 @@ impl Consumer {
         }
     }
 
+    /// Serve a publish request, adding the pushed track to its announced namespace.
+    async fn serve_publish(self, published: Published) -> Result<(), anyhow::Error> {
+        let track = self
+            .tracks
+            .lock()
+            .unwrap()
+            .get_mut(&published.track_namespace)
+            .and_then(|tracks| tracks.create(&published.track_name));
+
+        match track {
+            Some(track) => Ok(published.accept(track).await?),
+            None => {
+                // Only tracks in a namespace announced by the remote can be pushed
+                published.close(ServeError::NotFound)?;
+                Err(ServeError::NotFound.into())
+            }
+        }
+    }
+
     /// Serve an announce request.
     async fn serve(mut self, mut announce: Announced) -> Result<(), anyhow::Error> {
         let mut tasks = FuturesUnordered::new();
 
         // Produce the tracks for this announce and return the reader
-        let (_, mut request, reader) = Tracks::new(announce.namespace.clone()).produce();
+        let (writer, mut request, reader) = Tracks::new(announce.namespace.clone())
+            .with_cached_groups(CACHED_GROUPS)
+            .produce();
 
         // Start refreshing the API origin, if any
         if let Some(api) = self.api.as_ref() {
@@ -75,6 +125,16 @@ impl Consumer {
         // Register the local tracks, unregister on drop
         let _register = self.locals.register(reader.clone()).await?;
 
+        // Keep the writer around so published tracks can be added, remove it when done
+        self.tracks
+            .lock()
+            .unwrap()
+            .insert(announce.namespace.clone(), writer);
+        let _tracks = TracksRegistration {
+            tracks: self.tracks.clone(),
+            namespace: announce.namespace.clone(),
+        };
+
         // Accept the announce with an OK response
         announce.ok()?;
 
@@ -121,3 +181,15 @@ impl Consumer {
         }
     }
 }
+
+struct TracksRegistration {
+    tracks: Arc<Mutex<HashMap<TrackNamespace, TracksWriter>>>,
+    namespace: TrackNamespace,
+}
+
+/// Remove the announced tracks writer on drop.
+impl Drop for TracksRegistration {
+    fn drop(&mut self) {
+        self.tracks.lock().unwrap().remove(&self.namespace);
+    }
+}
@@ -1,17 +1,22 @@
 use std::collections::hash_map;
 use std::collections::HashMap;
+use std::future::Future;
 
 use std::sync::{Arc, Mutex};
 
 use moq_transport::{
     coding::TrackNamespace,
     serve::{ServeError, TracksReader},
+    watch::State,
 };
 
 /// Registry of local tracks
 #[derive(Clone)]
 pub struct Locals {
     lookup: Arc<Mutex<HashMap<TrackNamespace, TracksReader>>>,
+
+    /// Bumped on every registration, so namespace subscriptions can pick up new local tracks.
+    registrations: State<u64>,
 }
 
 impl Default for Locals {
@@ -25,6 +30,7 @@ impl Locals {
     pub fn new() -> Self {
         Self {
             lookup: Default::default(),
+            registrations: Default::default(),
         }
     }
 
@@ -38,6 +44,10 @@ impl Locals {
             hash_map::Entry::Occupied(_) => return Err(ServeError::Duplicate.into()),
         };
 
+        if let Some(mut registrations) = self.registrations.lock_mut() {
+            *registrations += 1;
+        }
+
         let registration = Registration {
             locals: self.clone(),
             namespace,
@@ -50,6 +60,27 @@ impl Locals {
     pub fn route(&self, namespace: &TrackNamespace) -> Option<TracksReader> {
         self.lookup.lock().unwrap().get(namespace).cloned()
     }
+
+    /// Lookup all local tracks with a namespace under the prefix.
+    pub fn matching(&self, prefix: &TrackNamespace) -> Vec<TracksReader> {
+        self.lookup
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(namespace, _)| namespace.has_prefix(prefix))
+            .map(|(_, tracks)| tracks.clone())
+            .collect()
+    }
+
+    /// Returns a future that resolves the next time local tracks are registered.
+    pub fn registered(&self) -> impl Future<Output = ()> {
+        let notify = self.registrations.lock().modified();
+        async move {
+            if let Some(notify) = notify {
+                notify.await
+            }
+        }
+    }
 }
 
 pub struct Registration {
@@ -39,6 +39,10 @@ pub struct Cli {
     #[arg(long)]
     pub mlog_dir: Option<PathBuf>,
 
+    /// The MAX_REQUEST_ID advertised to each session, which is also how many request IDs are granted at a time.
+    #[arg(long, default_value_t = moq_transport::session::DEFAULT_MAX_REQUEST_ID)]
+    pub max_request_id: u64,
+
     /// Forward all announces to the provided server for authentication/routing.
     /// If not provided, the relay accepts every unique announce.
     #[arg(long)]
@@ -109,6 +113,7 @@ async fn main() -> anyhow::Result<()> {
         bind: cli.bind,
         qlog_dir: qlog_dir_for_relay,
         mlog_dir: mlog_dir_for_relay,
+        max_request_id: cli.max_request_id,
         node: cli.node,
         api: cli.api,
         announce: cli.announce,
@@ -1,7 +1,10 @@
+use std::collections::HashSet;
+
 use futures::{stream::FuturesUnordered, FutureExt, StreamExt};
 use moq_transport::{
+    coding::TrackNamespace,
     serve::{ServeError, TracksReader},
-    session::{Publisher, SessionError, Subscribed, TrackStatusRequested},
+    session::{Fetched, Publisher, SessionError, Subscribed, TrackStatusRequested},
 };
 
 use crate::{Locals, RemotesConsumer};
@@ -36,7 +39,9 @@ impl Producer {
 
         loop {
             let mut remote_publisher_subscribed = self.remote_publisher.clone();
+            let mut remote_publisher_fetched = self.remote_publisher.clone();
             let mut remote_publisher_track_status = self.remote_publisher.clone();
+            let mut remote_publisher_namespaces = self.remote_publisher.clone();
 
             tokio::select! {
                 // Handle a new subscribe request
@@ -54,6 +59,21 @@ impl Producer {
                         }
                     }.boxed())
                 },
+                // Handle a new fetch request
+                Some(fetched) = remote_publisher_fetched.fetched() => {
+                    let this = self.clone();
+
+                    // Spawn a new task to handle the fetch
+                    tasks.push(async move {
+                        let info = fetched.info.clone();
+                        log::info!("serving fetch: {:?}", info);
+
+                        // Serve the fetch request
+                        if let Err(err) = this.serve_fetch(fetched).await {
+                            log::warn!("failed serving fetch: {:?}, error: {}", info, err)
+                        }
+                    }.boxed())
+                },
                 // Handle a new track_status request
                 Some(track_status_requested) = remote_publisher_track_status.track_status_requested() => {
                     let this = self.clone();
@@ -69,6 +89,19 @@ impl Producer {
                         }
                     }.boxed())
                 },
+                // Handle a new namespace subscription
+                Some(prefix) = remote_publisher_namespaces.subscribed_namespace() => {
+                    let this = self.clone();
+
+                    // Spawn a new task to announce the matching local namespaces
+                    tasks.push(async move {
+                        log::info!("serving subscribe_namespace: {}", prefix);
+
+                        if let Err(err) = this.serve_subscribe_namespace(prefix.clone()).await {
+                            log::warn!("failed serving subscribe_namespace: {}, error: {}", prefix, err)
+                        }
+                    }.boxed())
+                },
                 _= tasks.next(), if !tasks.is_empty() => {},
                 else => return Ok(()),
             };
@@ -99,19 +132,70 @@ impl Producer {
                         track.info
                     );
 
+                    // Answer with the upstream expires, so downstream subscriptions last no longer than ours.
+                    let reader = track.accepted().await?;
+
                     // NOTE: Depends on drop(track) being called afterwards
-                    return Ok(subscribed.serve(track.reader).await?);
+                    return Ok(subscribed.serve(reader).await?);
+                }
+            }
+        }
+
+        Err(ServeError::NotFound.into())
+    }
+
+    /// Announce the local namespaces under a prefix the remote subscribed to, including ones registered later.
+    async fn serve_subscribe_namespace(self, prefix: TrackNamespace) -> Result<(), anyhow::Error> {
+        let mut announced = HashSet::new();
+        let mut announces = FuturesUnordered::new();
+
+        loop {
+            let registered = self.locals.registered();
+
+            if self.remote_publisher.is_namespace_subscribed(&prefix) {
+                for tracks in self.locals.matching(&prefix) {
+                    if announced.insert(tracks.namespace.clone()) {
+                        log::info!("announcing local namespace: {}", tracks.namespace);
+                        let mut publisher = self.remote_publisher.clone();
+                        announces.push(async move { publisher.announce(tracks).await });
+                    }
                 }
+            } else if announces.is_empty() {
+                // Unsubscribed, and the namespaces we already announced are done.
+                return Ok(());
+            }
+
+            tokio::select! {
+                _ = registered => {},
+                Some(res) = announces.next() => {
+                    if let Err(err) = res {
+                        log::debug!("announce for subscribe_namespace {} ended: {}", prefix, err);
+                    }
+                },
+            }
+        }
+    }
+
+    /// Serve a fetch request.
+    async fn serve_fetch(self, fetched: Fetched) -> Result<(), anyhow::Error> {
+        // Only local tracks keep a cache we can fetch from
+        if let Some(mut local) = self.locals.route(&fetched.track_namespace) {
+            if let Some(track) = local.get_track_reader(&fetched.track_name) {
+                log::info!("serving fetch from local: {:?}", track.info);
+                return Ok(fetched.serve(track).await?);
             }
         }
 
+        // TODO - forward fetches to remotes?
+        fetched.close(ServeError::NotFound)?;
+
         Err(ServeError::NotFound.into())
     }
 
     /// Serve a track_status request.
     async fn serve_track_status(
         self,
-        mut track_status_requested: TrackStatusRequested,
+        track_status_requested: TrackStatusRequested,
     ) -> Result<(), anyhow::Error> {
         // Check local tracks first, and serve from local if possible
         if let Some(mut local_tracks) = self
@@ -143,7 +227,7 @@ impl Producer {
             }
         }*/
 
-        track_status_requested.respond_error(4, "Track not found")?;
+        track_status_requested.respond_error(ServeError::NotFound)?;
 
         Err(ServeError::NotFound.into())
     }
@@ -1,13 +1,17 @@
-use std::{net, path::PathBuf};
+use std::{net, path::PathBuf, time::Duration};
 
 use anyhow::Context;
 
 use futures::{stream::FuturesUnordered, FutureExt, StreamExt};
 use moq_native_ietf::quic;
+use moq_transport::session::{SessionConfig, TerminationCode};
 use url::Url;
 
 use crate::{Api, Consumer, Locals, Producer, Remotes, RemotesConsumer, RemotesProducer, Session};
 
+/// How long sessions can keep running after a GOAWAY, while clients migrate to a new relay.
+const GOAWAY_DRAIN: Duration = Duration::from_secs(10);
+
 /// Configuration for the relay.
 pub struct RelayConfig {
     /// Listen on this address
@@ -22,6 +26,9 @@ pub struct RelayConfig {
     /// Directory to write mlog files (one per connection)
     pub mlog_dir: Option<PathBuf>,
 
+    /// The MAX_REQUEST_ID advertised to each session.
+    pub max_request_id: u64,
+
     /// Forward all announcements to the (optional) URL.
     pub announce: Option<Url>,
 
@@ -38,6 +45,7 @@ pub struct Relay {
     quic: quic::Endpoint,
     announce_url: Option<Url>,
     mlog_dir: Option<PathBuf>,
+    session_config: SessionConfig,
     locals: Locals,
     api: Option<Api>,
     remotes: Option<(RemotesProducer, RemotesConsumer)>,
@@ -86,16 +94,22 @@ impl Relay {
             quic,
             announce_url: config.announce,
             mlog_dir: config.mlog_dir,
+            session_config: SessionConfig::default()
+                .with_max_request_id(config.max_request_id)
+                .with_implementation(concat!("moq-relay-ietf/", env!("CARGO_PKG_VERSION"))),
             api,
             locals,
             remotes,
         })
     }
 
-    /// Run the relay server.
+    /// Run the relay server.  On ctrl-c, every session is sent a GOAWAY and given time to drain.
     pub async fn run(self) -> anyhow::Result<()> {
         let mut tasks = FuturesUnordered::new();
 
+        // Set on shutdown, so each session sends a GOAWAY.
+        let (shutdown, _) = tokio::sync::watch::channel(false);
+
         // Start the remotes producer task, if any
         let remotes = self.remotes.map(|(producer, consumer)| {
             tasks.push(producer.run().boxed());
@@ -116,9 +130,12 @@ impl Relay {
 
             // Create the MoQ session over the connection
             let (session, publisher, subscriber) =
-                moq_transport::session::Session::connect(session, None)
-                    .await
-                    .context("failed to establish forward session")?;
+                moq_transport::session::Session::connect_with_config(
+                    session,
+                    self.session_config.clone(),
+                )
+                .await
+                .context("failed to establish forward session")?;
 
             // Create a normal looking session, except we never forward or register announces.
             let session = Session {
@@ -153,17 +170,19 @@ impl Relay {
                     // Construct mlog path from connection ID if mlog directory is configured
                     let mlog_path = self.mlog_dir.as_ref()
                         .map(|dir| dir.join(format!("{}_server.mlog", connection_id)));
+                    let config = self.session_config.clone().with_mlog_path(mlog_path);
 
                     let locals = self.locals.clone();
                     let remotes = remotes.clone();
                     let forward = forward_producer.clone();
                     let api = self.api.clone();
+                    let mut shutdown = shutdown.subscribe();
 
                     // Spawn a new task to handle the connection
                     tasks.push(async move {
 
                         // Create the MoQ session over the connection (setup handshake etc)
-                        let (session, publisher, subscriber) = match moq_transport::session::Session::accept(conn, mlog_path).await {
+                        let (session, publisher, subscriber) = match moq_transport::session::Session::accept_with_config(conn, config).await {
                             Ok(session) => session,
                             Err(err) => {
                                 log::warn!("failed to accept MoQ session: {}", err);
@@ -171,6 +190,10 @@ impl Relay {
                             }
                         };
 
+                        log::debug!("accepted MoQ session: version={} peer_params={:?}", session.version(), session.peer_params());
+
+                        let mut handle = session.handle();
+
                         // Create our MoQ relay session
                         let session = Session {
                             session,
@@ -178,7 +201,26 @@ impl Relay {
                             consumer: subscriber.map(|subscriber| Consumer::new(subscriber, locals, api, forward)),
                         };
 
-                        if let Err(err) = session.run().await {
+                        let run = session.run();
+                        tokio::pin!(run);
+
+                        let res = tokio::select! {
+                            res = &mut run => res,
+                            Ok(()) = async { shutdown.wait_for(|shutdown| *shutdown).await.map(|_| ()) } => {
+                                // Ask the client to reconnect, and keep serving it until it does.
+                                handle.go_away("");
+
+                                match tokio::time::timeout(GOAWAY_DRAIN, run).await {
+                                    Ok(res) => res,
+                                    Err(_) => {
+                                        handle.close(TerminationCode::GoawayTimeout, "goaway timeout");
+                                        Ok(())
+                                    }
+                                }
+                            }
+                        };
+
+                        if let Err(err) = res {
                             log::warn!("failed to run MoQ session: {}", err);
                         }
 
@@ -186,7 +228,24 @@ impl Relay {
                     }.boxed());
                 },
                 res = tasks.next(), if !tasks.is_empty() => res.unwrap()?,
+                _ = tokio::signal::ctrl_c() => break,
             }
         }
+
+        log::info!("shutting down, sending GOAWAY to all sessions");
+        shutdown.send_replace(true);
+
+        // Keep running the sessions until the clients migrate, or the drain period is over.
+        // Sessions close themselves with GOAWAY_TIMEOUT first, so give them a moment longer.
+        let drain = async {
+            while let Some(res) = tasks.next().await {
+                if let Err(err) = res {
+                    log::warn!("failed while draining: {}", err);
+                }
+            }
+        };
+        let _ = tokio::time::timeout(GOAWAY_DRAIN + Duration::from_secs(1), drain).await;
+
+        Ok(())
     }
 }
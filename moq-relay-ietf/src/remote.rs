@@ -5,13 +5,15 @@ use std::fmt;
 use std::ops;
 use std::sync::Arc;
 use std::sync::Weak;
+use std::time::Duration;
 
 use futures::stream::FuturesUnordered;
 use futures::FutureExt;
 use futures::StreamExt;
 use moq_native_ietf::quic;
 use moq_transport::coding::TrackNamespace;
-use moq_transport::serve::{Track, TrackReader, TrackWriter};
+use moq_transport::serve::{ServeError, Track, TrackReader, TrackWriter};
+use moq_transport::session::{SubscribeOk, Subscriber};
 use moq_transport::watch::State;
 use url::Url;
 
@@ -209,10 +211,15 @@ impl Remote {
     }
 }
 
+/// How long an upstream subscription is kept on standby, without forwarding, after the last reader is dropped.
+const STANDBY_TIMEOUT: Duration = Duration::from_secs(30);
+
 #[derive(Default)]
 struct RemoteState {
     tracks: HashMap<(TrackNamespace, String), RemoteTrackWeak>,
-    requested: VecDeque<TrackWriter>,
+
+    /// Requested tracks, along with whether objects should be forwarded and where to report the SUBSCRIBE_OK.
+    requested: VecDeque<(TrackWriter, State<bool>, State<Option<SubscribeOk>>)>,
 }
 
 pub struct RemoteProducer {
@@ -240,17 +247,18 @@ impl RemoteProducer {
         loop {
             tokio::select! {
                 track = self.next(), if done.is_none() => {
-                    let track = match track {
+                    let (track, forward, ok) = match track {
                         Ok(Some(track)) => track,
                         Ok(None) => { done = Some(Ok(())); continue },
                         Err(err) => { done = Some(Err(err)); continue },
                     };
 
                     let info = track.info.clone();
-                    let mut subscriber = subscriber.clone();
+                    let subscriber = subscriber.clone();
+                    let parent = self.state.clone();
 
                     tasks.push(async move {
-                        if let Err(err) = subscriber.subscribe(track).await {
+                        if let Err(err) = Self::serve_track(subscriber, track, forward, ok, parent).await {
                             log::warn!("failed serving track: {:?}, error: {}", info, err);
                         }
                     });
@@ -265,8 +273,73 @@ impl RemoteProducer {
         }
     }
 
+    /// Subscribe to the track upstream, pausing forwarding while nobody is reading it.
+    /// The subscription is kept on standby for [STANDBY_TIMEOUT] so it can be resumed without a new SUBSCRIBE.
+    /// After that our reader is dropped, and the session unsubscribes once no other reader is left either.
+    async fn serve_track(
+        mut subscriber: Subscriber,
+        track: TrackWriter,
+        forward: State<bool>,
+        ok: State<Option<SubscribeOk>>,
+        parent: State<RemoteState>,
+    ) -> anyhow::Result<()> {
+        let key = (track.namespace.clone(), track.name.clone());
+        let mut subscribe = subscriber.subscribe_handle(track);
+        let mut released = false;
+        let mut accepted = false;
+
+        let res = loop {
+            let (forwarding, changed) = {
+                let state = forward.lock();
+                (*state, state.modified())
+            };
+
+            if forwarding != subscribe.forward && !released {
+                log::debug!("setting forward={} for remote track: {:?}", forwarding, key);
+                if let Err(err) = subscribe.set_forward(forwarding) {
+                    break Err(err);
+                }
+            }
+
+            tokio::select! {
+                res = subscribe.refresh_until_closed() => break res,
+                Ok(info) = subscribe.ok(), if !accepted => {
+                    accepted = true;
+                    if let Some(mut ok) = ok.lock_mut() {
+                        *ok = Some(info);
+                    }
+                },
+                Some(()) = async move {
+                    changed?.await;
+                    Some(())
+                } => {},
+                _ = tokio::time::sleep(STANDBY_TIMEOUT), if !forwarding && !released => {
+                    // Only give up the subscription if nobody resumed it in the meantime.
+                    if let Some(mut parent) = parent.lock_mut() {
+                        if !*forward.lock() {
+                            // Dropping our reader lets the session unsubscribe, which closes the subscription.
+                            parent.tracks.remove(&key);
+                            released = true;
+                        }
+                    }
+                },
+            }
+        };
+
+        // Once released, the key may already belong to a new subscription.
+        if !released {
+            if let Some(mut parent) = parent.lock_mut() {
+                parent.tracks.remove(&key);
+            }
+        }
+
+        Ok(res?)
+    }
+
     /// Block until the next track requested by a consumer.
-    async fn next(&self) -> anyhow::Result<Option<TrackWriter>> {
+    async fn next(
+        &self,
+    ) -> anyhow::Result<Option<(TrackWriter, State<bool>, State<Option<SubscribeOk>>)>> {
         loop {
             let notify = {
                 let state = self.state.lock();
@@ -327,12 +400,19 @@ impl RemoteConsumer {
             None => return Ok(None),
         };
 
+        // Resume forwarding on a standby subscription instead of subscribing again.
+        if let Some(track) = state.tracks.get_mut(&key) {
+            return Ok(Some(track.resume(self.state.clone())));
+        }
+
+        let forward = State::new(true);
+        let (ok_writer, ok) = State::default().split();
         let (writer, reader) = Track::new(namespace, name).produce();
-        let reader = RemoteTrackReader::new(reader, self.state.clone());
+        let reader = RemoteTrackReader::new(reader, self.state.clone(), forward.clone(), ok);
 
         // Insert the track into our Map so we deduplicate future requests.
         state.tracks.insert(key, reader.downgrade());
-        state.requested.push_back(writer);
+        state.requested.push_back((writer, forward, ok_writer));
 
         Ok(Some(reader))
     }
@@ -349,25 +429,63 @@ impl ops::Deref for RemoteConsumer {
 #[derive(Clone)]
 pub struct RemoteTrackReader {
     pub reader: TrackReader,
+    ok: State<Option<SubscribeOk>>,
     drop: Arc<RemoteTrackDrop>,
 }
 
 impl RemoteTrackReader {
-    fn new(reader: TrackReader, parent: State<RemoteState>) -> Self {
+    fn new(
+        reader: TrackReader,
+        parent: State<RemoteState>,
+        forward: State<bool>,
+        ok: State<Option<SubscribeOk>>,
+    ) -> Self {
         let drop = Arc::new(RemoteTrackDrop {
             parent,
             key: (reader.namespace.clone(), reader.name.clone()),
+            forward,
         });
 
-        Self { reader, drop }
+        Self { reader, ok, drop }
     }
 
     fn downgrade(&self) -> RemoteTrackWeak {
         RemoteTrackWeak {
             reader: self.reader.clone(),
+            ok: self.ok.clone(),
             drop: Arc::downgrade(&self.drop),
+            forward: self.drop.forward.clone(),
         }
     }
+
+    /// Wait for the upstream SUBSCRIBE_OK, returning a reader that carries its expires.
+    /// Serving this reader ends downstream subscriptions that aren't refreshed within the upstream lifetime.
+    pub async fn accepted(&self) -> Result<TrackReader, ServeError> {
+        let ok = loop {
+            let notify = {
+                let state = self.ok.lock();
+                if let Some(ok) = state.clone() {
+                    break ok;
+                }
+
+                state.modified()
+            };
+
+            match notify {
+                Some(notify) => notify.await,
+                // The upstream subscription ended before it was accepted.
+                None => return Err(self.reader.closed().await.err().unwrap_or(ServeError::Done)),
+            }
+        };
+
+        let mut reader = self.reader.clone();
+        reader.info = Arc::new(Track {
+            expires: (ok.expires > 0).then(|| Duration::from_millis(ok.expires)),
+            ..(*self.reader.info).clone()
+        });
+
+        Ok(reader)
+    }
 }
 
 impl ops::Deref for RemoteTrackReader {
@@ -386,27 +504,58 @@ impl ops::DerefMut for RemoteTrackReader {
 
 struct RemoteTrackWeak {
     reader: TrackReader,
+    ok: State<Option<SubscribeOk>>,
     drop: Weak<RemoteTrackDrop>,
+    forward: State<bool>,
 }
 
 impl RemoteTrackWeak {
     fn upgrade(&self) -> Option<RemoteTrackReader> {
         Some(RemoteTrackReader {
             reader: self.reader.clone(),
+            ok: self.ok.clone(),
             drop: self.drop.upgrade()?,
         })
     }
+
+    /// Returns a reader for the track, turning forwarding back on if it was on standby.
+    fn resume(&mut self, parent: State<RemoteState>) -> RemoteTrackReader {
+        if let Some(track) = self.upgrade() {
+            return track;
+        }
+
+        let track = RemoteTrackReader::new(
+            self.reader.clone(),
+            parent,
+            self.forward.clone(),
+            self.ok.clone(),
+        );
+        self.drop = Arc::downgrade(&track.drop);
+
+        if let Some(mut forward) = self.forward.lock_mut() {
+            *forward = true;
+        }
+
+        track
+    }
 }
 
 struct RemoteTrackDrop {
     parent: State<RemoteState>,
     key: (TrackNamespace, String),
+    forward: State<bool>,
 }
 
 impl Drop for RemoteTrackDrop {
     fn drop(&mut self) {
-        if let Some(mut parent) = self.parent.lock_mut() {
-            parent.tracks.remove(&self.key);
+        // Keep the upstream subscription on standby, unless the track was resumed while we were dropping.
+        let parent = self.parent.lock();
+        if let Some(track) = parent.tracks.get(&self.key) {
+            if track.drop.strong_count() == 0 {
+                if let Some(mut forward) = self.forward.lock_mut() {
+                    *forward = false;
+                }
+            }
         }
     }
 }
@@ -0,0 +1,174 @@
+use crate::coding::{Decode, DecodeError, Encode, EncodeError};
+
+/// An authorization token, as registered with or sent to the peer.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AuthToken {
+    /// The token type, from the IANA registry, or 0 if it's negotiated out of band.
+    pub token_type: u64,
+    pub value: Vec<u8>,
+}
+
+impl AuthToken {
+    pub fn new(token_type: u64, value: Vec<u8>) -> Self {
+        Self { token_type, value }
+    }
+
+    /// The size counted against the peer's MAX_AUTH_TOKEN_CACHE_SIZE: the token type and value as encoded.
+    pub fn size(&self) -> usize {
+        let mut buf = Vec::new();
+        // A u64 always fits in a VarInt, so this can't fail.
+        let _ = self.token_type.encode(&mut buf);
+        buf.len() + self.value.len()
+    }
+}
+
+/// The value of an AUTHORIZATION_TOKEN parameter, which may register, use or delete an alias for a token.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AuthTokenParam {
+    /// Delete the token registered with this alias.
+    Delete(u64),
+
+    /// Register the token with this alias, and use it for the current message.
+    Register(u64, AuthToken),
+
+    /// Use the token previously registered with this alias.
+    UseAlias(u64),
+
+    /// Use the token without registering it.
+    UseValue(AuthToken),
+}
+
+impl AuthTokenParam {
+    const DELETE: u64 = 0x0;
+    const REGISTER: u64 = 0x1;
+    const USE_ALIAS: u64 = 0x2;
+    const USE_VALUE: u64 = 0x3;
+}
+
+impl Decode for AuthTokenParam {
+    fn decode<R: bytes::Buf>(r: &mut R) -> Result<Self, DecodeError> {
+        let param = match u64::decode(r)? {
+            Self::DELETE => Self::Delete(u64::decode(r)?),
+            Self::REGISTER => {
+                let alias = u64::decode(r)?;
+                let token_type = u64::decode(r)?;
+                Self::Register(
+                    alias,
+                    AuthToken::new(token_type, r.copy_to_bytes(r.remaining()).to_vec()),
+                )
+            }
+            Self::USE_ALIAS => Self::UseAlias(u64::decode(r)?),
+            Self::USE_VALUE => {
+                let token_type = u64::decode(r)?;
+                Self::UseValue(AuthToken::new(
+                    token_type,
+                    r.copy_to_bytes(r.remaining()).to_vec(),
+                ))
+            }
+            _ => return Err(DecodeError::InvalidValue),
+        };
+
+        // The token value runs to the end of the parameter, so anything left over after an alias is malformed.
+        if r.has_remaining() {
+            return Err(DecodeError::InvalidValue);
+        }
+
+        Ok(param)
+    }
+}
+
+impl Encode for AuthTokenParam {
+    fn encode<W: bytes::BufMut>(&self, w: &mut W) -> Result<(), EncodeError> {
+        match self {
+            Self::Delete(alias) => {
+                Self::DELETE.encode(w)?;
+                alias.encode(w)?;
+            }
+            Self::Register(alias, token) => {
+                Self::REGISTER.encode(w)?;
+                alias.encode(w)?;
+                token.token_type.encode(w)?;
+                Self::encode_remaining(w, token.value.len())?;
+                w.put_slice(&token.value);
+            }
+            Self::UseAlias(alias) => {
+                Self::USE_ALIAS.encode(w)?;
+                alias.encode(w)?;
+            }
+            Self::UseValue(token) => {
+                Self::USE_VALUE.encode(w)?;
+                token.token_type.encode(w)?;
+                Self::encode_remaining(w, token.value.len())?;
+                w.put_slice(&token.value);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::BytesMut;
+
+    #[test]
+    fn encode_decode() {
+        let mut buf = BytesMut::new();
+
+        let param = AuthTokenParam::Register(5, AuthToken::new(1, b"secret".to_vec()));
+        param.encode(&mut buf).unwrap();
+        assert_eq!(
+            buf.to_vec(),
+            vec![
+                0x01, // Register
+                0x05, // Alias
+                0x01, // Token type
+                0x73, 0x65, 0x63, 0x72, 0x65, 0x74, // "secret"
+            ]
+        );
+        let decoded = AuthTokenParam::decode(&mut buf).unwrap();
+        assert_eq!(decoded, param);
+
+        for param in [
+            AuthTokenParam::Delete(5),
+            AuthTokenParam::UseAlias(5),
+            AuthTokenParam::UseValue(AuthToken::new(0, vec![])),
+            AuthTokenParam::UseValue(AuthToken::new(2, b"token".to_vec())),
+        ] {
+            param.encode(&mut buf).unwrap();
+            let decoded = AuthTokenParam::decode(&mut buf).unwrap();
+            assert_eq!(decoded, param);
+        }
+    }
+
+    #[test]
+    fn decode_malformed() {
+        // Unknown alias type
+        let mut buf: &[u8] = &[0x04, 0x01];
+        assert!(matches!(
+            AuthTokenParam::decode(&mut buf).unwrap_err(),
+            DecodeError::InvalidValue
+        ));
+
+        // Trailing bytes after an alias
+        let mut buf: &[u8] = &[0x02, 0x01, 0x01];
+        assert!(matches!(
+            AuthTokenParam::decode(&mut buf).unwrap_err(),
+            DecodeError::InvalidValue
+        ));
+
+        // Missing alias
+        let mut buf: &[u8] = &[0x00];
+        assert!(matches!(
+            AuthTokenParam::decode(&mut buf).unwrap_err(),
+            DecodeError::More(_)
+        ));
+    }
+
+    #[test]
+    fn size() {
+        assert_eq!(AuthToken::new(1, b"secret".to_vec()).size(), 7);
+        assert_eq!(AuthToken::new(100, vec![]).size(), 2);
+    }
+}
@@ -1,4 +1,5 @@
 use super::BoundsExceeded;
+use crate::setup;
 use std::{io, string::FromUtf8Error, sync};
 use thiserror::Error;
 
@@ -76,6 +77,9 @@ pub enum DecodeError {
 
     #[error("invalid datagram type")]
     InvalidDatagramType,
+
+    #[error("unsupported version: {0}")]
+    UnsupportedVersion(setup::Version),
 }
 
 impl From<io::Error> for DecodeError {
@@ -1,6 +1,7 @@
 use std::{io, sync};
 
 use super::BoundsExceeded;
+use crate::setup;
 
 pub trait Encode: Sized {
     fn encode<W: bytes::BufMut>(&self, w: &mut W) -> Result<(), EncodeError>;
@@ -39,6 +40,9 @@ pub enum EncodeError {
 
     #[error("field '{0}' too large")]
     FieldBoundsExceeded(String),
+
+    #[error("unsupported version: {0}")]
+    UnsupportedVersion(setup::Version),
 }
 
 impl From<io::Error> for EncodeError {
@@ -134,6 +134,22 @@ impl KeyValuePairs {
     pub fn get(&mut self, key: u64) -> Option<&KeyValuePair> {
         self.0.get(&key)
     }
+
+    /// Returns the value for the key, if present and an integer.
+    pub fn get_intvalue(&self, key: u64) -> Option<u64> {
+        match self.0.get(&key)?.value {
+            Value::IntValue(value) => Some(value),
+            Value::BytesValue(_) => None,
+        }
+    }
+
+    /// Returns the value for the key, if present and bytes.
+    pub fn get_bytesvalue(&self, key: u64) -> Option<&[u8]> {
+        match &self.0.get(&key)?.value {
+            Value::IntValue(_) => None,
+            Value::BytesValue(value) => Some(value),
+        }
+    }
 }
 
 impl Decode for KeyValuePairs {
@@ -221,6 +237,21 @@ mod tests {
         assert_eq!(decoded, kvp);
     }
 
+    #[test]
+    fn get_values() {
+        let mut kvps = KeyValuePairs::new();
+        kvps.set_intvalue(2, 100);
+        kvps.set_bytesvalue(7, b"moq-rs".to_vec());
+
+        assert_eq!(kvps.get_intvalue(2), Some(100));
+        assert_eq!(kvps.get_bytesvalue(7), Some(&b"moq-rs"[..]));
+
+        // Wrong type or missing
+        assert_eq!(kvps.get_intvalue(7), None);
+        assert_eq!(kvps.get_bytesvalue(2), None);
+        assert_eq!(kvps.get_bytesvalue(1), None);
+    }
+
     #[test]
     fn decode_badtype() {
         // Simulate a VarInt value of 5, but with an odd key/type
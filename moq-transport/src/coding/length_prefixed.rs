@@ -0,0 +1,122 @@
+use bytes::{Buf, BytesMut};
+
+use crate::coding::{Decode, DecodeError, Encode, EncodeError};
+
+/// Decode a body prefixed with its u16 length, as used by control messages.
+/// The body is bounded to the declared length, so a body that's shorter or longer than what the decoder consumes
+/// is an [DecodeError::InvalidLength] rather than bleeding into the next message.
+pub fn decode_length_prefixed<R: Buf, T>(
+    r: &mut R,
+    f: impl FnOnce(&mut bytes::buf::Take<&mut R>) -> Result<T, DecodeError>,
+) -> Result<T, DecodeError> {
+    let len = u16::decode(r)? as usize;
+
+    // Wait for the whole body, so running out of bytes below means the declared length was too short.
+    if r.remaining() < len {
+        return Err(DecodeError::More(len - r.remaining()));
+    }
+
+    let mut body = r.take(len);
+    let res = f(&mut body);
+    let remaining = body.remaining();
+
+    // Skip anything left over, so the next message starts at the right place.
+    body.advance(remaining);
+
+    match res {
+        Ok(_) if remaining > 0 => Err(DecodeError::InvalidLength(len, len - remaining)),
+        Err(DecodeError::More(needed)) => Err(DecodeError::InvalidLength(len, len + needed)),
+        res => res,
+    }
+}
+
+/// Encode a body prefixed with its u16 length, as used by control messages.
+/// The length is reserved and filled in once the body is written, so the body isn't copied.
+pub fn encode_length_prefixed(
+    buf: &mut BytesMut,
+    f: impl FnOnce(&mut BytesMut) -> Result<(), EncodeError>,
+) -> Result<(), EncodeError> {
+    let start = buf.len();
+    0u16.encode(buf)?;
+
+    let res = f(buf).and_then(|_| {
+        u16::try_from(buf.len() - start - 2).map_err(|_| EncodeError::MsgBoundsExceeded)
+    });
+
+    match res {
+        Ok(len) => {
+            buf[start..start + 2].copy_from_slice(&len.to_be_bytes());
+            Ok(())
+        }
+        Err(err) => {
+            // Don't leave a partial message behind.
+            buf.truncate(start);
+            Err(err)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::BufMut;
+
+    #[test]
+    fn encode_decode() {
+        let mut buf = BytesMut::new();
+        encode_length_prefixed(&mut buf, |buf| {
+            7u64.encode(buf)?;
+            1000u64.encode(buf)
+        })
+        .unwrap();
+        assert_eq!(buf.to_vec(), vec![0x00, 0x03, 0x07, 0x43, 0xe8]);
+
+        let decoded =
+            decode_length_prefixed(&mut buf, |r| Ok((u64::decode(r)?, u64::decode(r)?))).unwrap();
+        assert_eq!(decoded, (7, 1000));
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn encode_too_large() {
+        let mut buf = BytesMut::new();
+        buf.put_u8(0xff);
+
+        let err = encode_length_prefixed(&mut buf, |buf| {
+            buf.put_bytes(0, u16::MAX as usize + 1);
+            Ok(())
+        })
+        .unwrap_err();
+        assert!(matches!(err, EncodeError::MsgBoundsExceeded));
+        assert_eq!(buf.to_vec(), vec![0xff]);
+    }
+
+    #[test]
+    fn decode_incomplete() {
+        // The body hasn't fully arrived yet
+        let mut buf: &[u8] = &[0x00, 0x03, 0x07];
+        assert!(matches!(
+            decode_length_prefixed(&mut buf, |r| u64::decode(r)),
+            Err(DecodeError::More(2))
+        ));
+    }
+
+    #[test]
+    fn decode_wrong_length() {
+        // Trailing bytes after the body, which are skipped
+        let mut buf: &[u8] = &[0x00, 0x02, 0x07, 0x08, 0x09];
+        assert!(matches!(
+            decode_length_prefixed(&mut buf, |r| u64::decode(r)),
+            Err(DecodeError::InvalidLength(2, 1))
+        ));
+        assert_eq!(buf, &[0x09]);
+
+        // The declared length is shorter than the body
+        let mut buf: &[u8] = &[0x00, 0x01, 0x43, 0xe8];
+        assert!(matches!(
+            decode_length_prefixed(&mut buf, |r| u64::decode(r)),
+            Err(DecodeError::InvalidLength(1, 2))
+        ));
+        assert_eq!(buf, &[0xe8]);
+    }
+}
@@ -1,21 +1,27 @@
+mod auth_token;
 mod bounded_string;
 mod decode;
 mod encode;
 mod hex_dump;
 mod integer;
 mod kvp;
+mod length_prefixed;
 mod location;
 mod string;
 mod track_namespace;
 mod tuple;
 mod varint;
+mod versioned;
 
+pub use auth_token::*;
 pub use bounded_string::*;
 pub use decode::*;
 pub use encode::*;
 pub use hex_dump::*;
 pub use kvp::*;
+pub use length_prefixed::*;
 pub use location::*;
 pub use track_namespace::*;
 pub use tuple::*;
 pub use varint::*;
+pub use versioned::*;
@@ -39,6 +39,11 @@ impl TrackNamespace {
         }
         path
     }
+
+    /// Returns true if the leading tuple fields of this namespace match the prefix.
+    pub fn has_prefix(&self, prefix: &TrackNamespace) -> bool {
+        self.fields.starts_with(&prefix.fields)
+    }
 }
 
 impl Hash for TrackNamespace {
@@ -138,6 +143,18 @@ mod tests {
         assert_eq!(decoded, t);
     }
 
+    #[test]
+    fn has_prefix() {
+        let t = TrackNamespace::from_utf8_path("live/sports/soccer");
+
+        assert!(t.has_prefix(&TrackNamespace::new()));
+        assert!(t.has_prefix(&TrackNamespace::from_utf8_path("live")));
+        assert!(t.has_prefix(&TrackNamespace::from_utf8_path("live/sports")));
+        assert!(t.has_prefix(&t));
+        assert!(!t.has_prefix(&TrackNamespace::from_utf8_path("live/spo")));
+        assert!(!t.has_prefix(&TrackNamespace::from_utf8_path("live/sports/soccer/extra")));
+    }
+
     #[test]
     fn encode_too_large() {
         let mut buf = BytesMut::new();
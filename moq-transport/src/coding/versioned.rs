@@ -0,0 +1,37 @@
+use bytes::BytesMut;
+
+use crate::coding::{Decode, DecodeError, Encode, EncodeError};
+use crate::setup::Version;
+
+/// Decode a type whose wire format depends on the version negotiated in SETUP.
+/// The [Decode] impl is the draft-14 format, which the default uses for every supported version; a type that
+/// changes in a later draft overrides this to pick the format per version.
+pub trait DecodeVersioned: Decode {
+    fn decode_versioned<R: bytes::Buf>(r: &mut R, version: Version) -> Result<Self, DecodeError> {
+        match version {
+            Version::DRAFT_14 | Version::DRAFT_15 => Self::decode(r),
+            _ => Err(DecodeError::UnsupportedVersion(version)),
+        }
+    }
+}
+
+/// Encode a type whose wire format depends on the version negotiated in SETUP.
+/// The [Encode] impl is the draft-14 format, which the default uses for every supported version.
+pub trait EncodeVersioned: Encode {
+    fn encode_versioned<W: bytes::BufMut>(
+        &self,
+        w: &mut W,
+        version: Version,
+    ) -> Result<(), EncodeError> {
+        match version {
+            Version::DRAFT_14 | Version::DRAFT_15 => self.encode(w),
+            _ => Err(EncodeError::UnsupportedVersion(version)),
+        }
+    }
+
+    /// Encode into a [BytesMut], which unlike a [bytes::BufMut] can be patched after writing.
+    /// A length-prefixed type overrides this to fill in its length in place instead of buffering its body.
+    fn encode_bytes(&self, buf: &mut BytesMut, version: Version) -> Result<(), EncodeError> {
+        self.encode_versioned(buf, version)
+    }
+}
@@ -1,5 +1,12 @@
-use crate::coding::{Decode, DecodeError, Encode, EncodeError, KeyValuePairs};
-use crate::data::ObjectStatus;
+use crate::coding::{
+    Decode, DecodeError, DecodeVersioned, Encode, EncodeError, EncodeVersioned, KeyValuePairs,
+};
+use crate::data::{ObjectStatus, DEFAULT_PUBLISHER_PRIORITY};
+use crate::setup::Version;
+
+/// Set in a draft-15 datagram type when the Publisher Priority is left out,
+/// which means the object has [DEFAULT_PUBLISHER_PRIORITY].
+const DATAGRAM_DEFAULT_PRIORITY: u64 = 0x08;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum DatagramType {
@@ -17,7 +24,15 @@ pub enum DatagramType {
 
 impl Decode for DatagramType {
     fn decode<B: bytes::Buf>(r: &mut B) -> Result<Self, DecodeError> {
-        match u64::decode(r)? {
+        Self::try_from(u64::decode(r)?)
+    }
+}
+
+impl TryFrom<u64> for DatagramType {
+    type Error = DecodeError;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        match value {
             0x00 => Ok(Self::ObjectIdPayload),
             0x01 => Ok(Self::ObjectIdPayloadExt),
             0x02 => Ok(Self::ObjectIdPayloadEndOfGroup),
@@ -71,6 +86,17 @@ pub struct Datagram {
 impl Decode for Datagram {
     fn decode<R: bytes::Buf>(r: &mut R) -> Result<Self, DecodeError> {
         let datagram_type = DatagramType::decode(r)?;
+        Self::decode_body(datagram_type, true, r)
+    }
+}
+
+impl Datagram {
+    /// Decode the rest of the datagram once the type is known.
+    fn decode_body<R: bytes::Buf>(
+        datagram_type: DatagramType,
+        has_priority: bool,
+        r: &mut R,
+    ) -> Result<Self, DecodeError> {
         let track_alias = u64::decode(r)?;
         let group_id = u64::decode(r)?;
 
@@ -85,7 +111,10 @@ impl Decode for Datagram {
             _ => None,
         };
 
-        let publisher_priority = u8::decode(r)?;
+        let publisher_priority = match has_priority {
+            true => u8::decode(r)?,
+            false => DEFAULT_PUBLISHER_PRIORITY,
+        };
 
         // Decode Extension Headers if required
         let extension_headers = match datagram_type {
@@ -133,7 +162,20 @@ impl Decode for Datagram {
 
 impl Encode for Datagram {
     fn encode<W: bytes::BufMut>(&self, w: &mut W) -> Result<(), EncodeError> {
-        self.datagram_type.encode(w)?;
+        self.encode_body(w, true)
+    }
+}
+
+impl Datagram {
+    fn encode_body<W: bytes::BufMut>(
+        &self,
+        w: &mut W,
+        has_priority: bool,
+    ) -> Result<(), EncodeError> {
+        match has_priority {
+            true => self.datagram_type.encode(w)?,
+            false => (self.datagram_type as u64 | DATAGRAM_DEFAULT_PRIORITY).encode(w)?,
+        }
         self.track_alias.encode(w)?;
         self.group_id.encode(w)?;
 
@@ -154,7 +196,9 @@ impl Encode for Datagram {
             _ => {}
         };
 
-        self.publisher_priority.encode(w)?;
+        if has_priority {
+            self.publisher_priority.encode(w)?;
+        }
 
         // Encode Extension Headers if required
         match self.datagram_type {
@@ -208,6 +252,38 @@ impl Encode for Datagram {
     }
 }
 
+// Draft-15 adds a datagram type without the Publisher Priority for each draft-14 one.
+impl DecodeVersioned for Datagram {
+    fn decode_versioned<R: bytes::Buf>(r: &mut R, version: Version) -> Result<Self, DecodeError> {
+        match version {
+            Version::DRAFT_14 => Self::decode(r),
+            Version::DRAFT_15 => {
+                let value = u64::decode(r)?;
+                let has_priority = value & DATAGRAM_DEFAULT_PRIORITY == 0;
+                let datagram_type = DatagramType::try_from(value & !DATAGRAM_DEFAULT_PRIORITY)?;
+                Self::decode_body(datagram_type, has_priority, r)
+            }
+            _ => Err(DecodeError::UnsupportedVersion(version)),
+        }
+    }
+}
+
+impl EncodeVersioned for Datagram {
+    fn encode_versioned<W: bytes::BufMut>(
+        &self,
+        w: &mut W,
+        version: Version,
+    ) -> Result<(), EncodeError> {
+        match version {
+            Version::DRAFT_14 => self.encode(w),
+            Version::DRAFT_15 => {
+                self.encode_body(w, self.publisher_priority != DEFAULT_PUBLISHER_PRIORITY)
+            }
+            _ => Err(EncodeError::UnsupportedVersion(version)),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -458,6 +534,89 @@ mod tests {
         assert_eq!(decoded, msg);
     }
 
+    #[test]
+    fn encode_decode_versioned() {
+        let mut buf = BytesMut::new();
+
+        let msg = Datagram {
+            datagram_type: DatagramType::ObjectIdPayload,
+            track_alias: 12,
+            group_id: 10,
+            object_id: Some(1234),
+            publisher_priority: 127,
+            extension_headers: None,
+            status: None,
+            payload: Some(Bytes::from("payload")),
+        };
+        for version in Version::SUPPORTED {
+            msg.encode_versioned(&mut buf, *version).unwrap();
+            let decoded = Datagram::decode_versioned(&mut buf, *version).unwrap();
+            assert_eq!(decoded, msg, "{version}");
+        }
+
+        // There's no wire format for older drafts.
+        assert!(matches!(
+            msg.encode_versioned(&mut buf, Version::DRAFT_13),
+            Err(EncodeError::UnsupportedVersion(Version::DRAFT_13))
+        ));
+        let mut data: &[u8] = &[0x00];
+        assert!(matches!(
+            Datagram::decode_versioned(&mut data, Version::DRAFT_13),
+            Err(DecodeError::UnsupportedVersion(Version::DRAFT_13))
+        ));
+    }
+
+    #[test]
+    fn encode_decode_versioned_default_priority() {
+        let mut kvps = KeyValuePairs::new();
+        kvps.set_bytesvalue(123, vec![0x00, 0x01, 0x02, 0x03]);
+
+        let payload = Datagram {
+            datagram_type: DatagramType::PayloadExtEndOfGroup,
+            track_alias: 12,
+            group_id: 10,
+            object_id: None,
+            publisher_priority: DEFAULT_PUBLISHER_PRIORITY,
+            extension_headers: Some(kvps),
+            status: None,
+            payload: Some(Bytes::from("payload")),
+        };
+        let status = Datagram {
+            datagram_type: DatagramType::ObjectIdStatus,
+            track_alias: 12,
+            group_id: 10,
+            object_id: Some(3),
+            publisher_priority: DEFAULT_PUBLISHER_PRIORITY,
+            extension_headers: None,
+            status: Some(ObjectStatus::EndOfGroup),
+            payload: None,
+        };
+
+        for msg in [payload, status] {
+            // Draft-14 always sends the priority.
+            let mut buf = BytesMut::new();
+            msg.encode_versioned(&mut buf, Version::DRAFT_14).unwrap();
+            assert_eq!(buf[0], msg.datagram_type as u8);
+            let draft_14_len = buf.len();
+            let decoded = Datagram::decode_versioned(&mut buf, Version::DRAFT_14).unwrap();
+            assert_eq!(decoded, msg);
+
+            // Draft-15 leaves the default out, and says so in the type.
+            msg.encode_versioned(&mut buf, Version::DRAFT_15).unwrap();
+            assert_eq!(buf[0], msg.datagram_type as u8 | 0x08);
+            assert_eq!(buf.len(), draft_14_len - 1);
+            let decoded = Datagram::decode_versioned(&mut buf, Version::DRAFT_15).unwrap();
+            assert_eq!(decoded, msg);
+        }
+
+        // Draft-14 has no such type.
+        let mut data: &[u8] = &[0x08, 0x0c, 0x0a, 0x03];
+        assert!(matches!(
+            Datagram::decode_versioned(&mut data, Version::DRAFT_14),
+            Err(DecodeError::InvalidDatagramType)
+        ));
+    }
+
     #[test]
     fn encode_datagram_missing_fields() {
         let mut buf = BytesMut::new();
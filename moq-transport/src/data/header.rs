@@ -1,7 +1,12 @@
-use crate::coding::{Decode, DecodeError, Encode, EncodeError};
+use crate::coding::{Decode, DecodeError, DecodeVersioned, Encode, EncodeError, EncodeVersioned};
 use crate::data::{FetchHeader, SubgroupHeader};
+use crate::setup::Version;
 use std::fmt;
 
+/// Set in a draft-15 subgroup header type when the Publisher Priority is left out of the header,
+/// which means the object has [crate::data::DEFAULT_PUBLISHER_PRIORITY].
+pub(crate) const SUBGROUP_DEFAULT_PRIORITY: u64 = 0x20;
+
 /// Stream Header Types
 #[repr(u64)]
 #[derive(Copy, Debug, Clone, Eq, PartialEq)]
@@ -82,7 +87,26 @@ impl Decode for StreamHeaderType {
             type_value
         );
 
-        let header_type = match type_value {
+        let header_type = Self::try_from(type_value);
+
+        if let Ok(header_type_inner) = &header_type {
+            log::debug!(
+                "[DECODE] StreamHeaderType: {}, has_subgroup_id={}, has_extension_headers={}",
+                header_type_inner,
+                header_type_inner.has_subgroup_id(),
+                header_type_inner.has_extension_headers()
+            );
+        }
+
+        header_type
+    }
+}
+
+impl TryFrom<u64> for StreamHeaderType {
+    type Error = DecodeError;
+
+    fn try_from(type_value: u64) -> Result<Self, Self::Error> {
+        match type_value {
             0x10_u64 => Ok(Self::SubgroupZeroId),
             0x11_u64 => Ok(Self::SubgroupZeroIdExt),
             0x12_u64 => Ok(Self::SubgroupFirstObjectId),
@@ -103,18 +127,7 @@ impl Decode for StreamHeaderType {
                 );
                 Err(DecodeError::InvalidHeaderType)
             }
-        };
-
-        if let Ok(header_type_inner) = &header_type {
-            log::debug!(
-                "[DECODE] StreamHeaderType: {}, has_subgroup_id={}, has_extension_headers={}",
-                header_type_inner,
-                header_type_inner.has_subgroup_id(),
-                header_type_inner.has_extension_headers()
-            );
         }
-
-        header_type
     }
 }
 
@@ -149,10 +162,21 @@ impl Decode for StreamHeader {
             header_type
         );
 
+        Self::decode_body(header_type, true, r)
+    }
+}
+
+impl StreamHeader {
+    /// Decode the rest of the header once the type is known.
+    fn decode_body<R: bytes::Buf>(
+        header_type: StreamHeaderType,
+        has_priority: bool,
+        r: &mut R,
+    ) -> Result<Self, DecodeError> {
         let subgroup_header = match header_type.is_subgroup() {
             true => {
                 log::trace!("[DECODE] StreamHeader: decoding subgroup header");
-                Some(SubgroupHeader::decode(header_type, r)?)
+                Some(SubgroupHeader::decode_body(header_type, has_priority, r)?)
             }
             false => {
                 log::trace!("[DECODE] StreamHeader: no subgroup header (not a subgroup type)");
@@ -187,6 +211,49 @@ impl Decode for StreamHeader {
     }
 }
 
+// Draft-15 adds a subgroup header type without the Publisher Priority for each draft-14 one.
+impl DecodeVersioned for StreamHeader {
+    fn decode_versioned<R: bytes::Buf>(r: &mut R, version: Version) -> Result<Self, DecodeError> {
+        match version {
+            Version::DRAFT_14 => Self::decode(r),
+            Version::DRAFT_15 => {
+                let type_value = u64::decode(r)?;
+                let has_priority = !(0x30..=0x3d).contains(&type_value);
+                let header_type = match has_priority {
+                    true => StreamHeaderType::try_from(type_value)?,
+                    false => StreamHeaderType::try_from(type_value & !SUBGROUP_DEFAULT_PRIORITY)?,
+                };
+                log::trace!(
+                    "[DECODE] StreamHeader: decoded header_type={:?}, has_priority={}",
+                    header_type,
+                    has_priority
+                );
+
+                Self::decode_body(header_type, has_priority, r)
+            }
+            _ => Err(DecodeError::UnsupportedVersion(version)),
+        }
+    }
+}
+
+impl EncodeVersioned for StreamHeader {
+    fn encode_versioned<W: bytes::BufMut>(
+        &self,
+        w: &mut W,
+        version: Version,
+    ) -> Result<(), EncodeError> {
+        match &self.subgroup_header {
+            Some(subgroup_header) if self.header_type.is_subgroup() => {
+                subgroup_header.encode_versioned(w, version)
+            }
+            _ => match version {
+                Version::DRAFT_14 | Version::DRAFT_15 => self.encode(w),
+                _ => Err(EncodeError::UnsupportedVersion(version)),
+            },
+        }
+    }
+}
+
 impl Encode for StreamHeader {
     fn encode<W: bytes::BufMut>(&self, w: &mut W) -> Result<(), EncodeError> {
         log::trace!(
@@ -230,6 +297,7 @@ impl Encode for StreamHeader {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::data::DEFAULT_PUBLISHER_PRIORITY;
     use bytes::Bytes;
     use bytes::BytesMut;
 
@@ -301,4 +369,72 @@ mod tests {
         assert!(!sh.header_type.is_fetch());
         assert!(sh.header_type.has_subgroup_id());
     }
+
+    #[test]
+    fn encode_decode_versioned_stream_header() {
+        for publisher_priority in [100, DEFAULT_PUBLISHER_PRIORITY] {
+            let sh = StreamHeader {
+                header_type: StreamHeaderType::SubgroupIdExtEndOfGroup,
+                subgroup_header: Some(SubgroupHeader {
+                    header_type: StreamHeaderType::SubgroupIdExtEndOfGroup,
+                    track_alias: 10,
+                    group_id: 0,
+                    subgroup_id: Some(1),
+                    publisher_priority,
+                }),
+                fetch_header: None,
+            };
+
+            for version in Version::SUPPORTED {
+                let mut buf = BytesMut::new();
+                sh.encode_versioned(&mut buf, *version).unwrap();
+                let decoded = StreamHeader::decode_versioned(&mut buf, *version).unwrap();
+                assert_eq!(decoded, sh, "{version}");
+            }
+        }
+    }
+
+    #[test]
+    fn encode_versioned_subgroup_header_type() {
+        let mut header = SubgroupHeader {
+            header_type: StreamHeaderType::SubgroupZeroId,
+            track_alias: 10,
+            group_id: 1,
+            subgroup_id: None,
+            publisher_priority: DEFAULT_PUBLISHER_PRIORITY,
+        };
+
+        // Draft-14 always sends the priority.
+        let mut buf = BytesMut::new();
+        header
+            .encode_versioned(&mut buf, Version::DRAFT_14)
+            .unwrap();
+        assert_eq!(buf.to_vec(), vec![0x10, 0x0a, 0x01, 0x80]);
+
+        // Draft-15 leaves the default out, and says so in the type.
+        let mut buf = BytesMut::new();
+        header
+            .encode_versioned(&mut buf, Version::DRAFT_15)
+            .unwrap();
+        assert_eq!(buf.to_vec(), vec![0x30, 0x0a, 0x01]);
+
+        header.publisher_priority = 1;
+        let mut buf = BytesMut::new();
+        header
+            .encode_versioned(&mut buf, Version::DRAFT_15)
+            .unwrap();
+        assert_eq!(buf.to_vec(), vec![0x10, 0x0a, 0x01, 0x01]);
+
+        // Draft-14 has no such type, and draft-15 only adds the subgroup ones.
+        let mut data: &[u8] = &[0x30, 0x0a, 0x01];
+        assert!(matches!(
+            StreamHeader::decode_versioned(&mut data, Version::DRAFT_14),
+            Err(DecodeError::InvalidHeaderType)
+        ));
+        let mut data: &[u8] = &[0x25, 0x0a];
+        assert!(matches!(
+            StreamHeader::decode_versioned(&mut data, Version::DRAFT_15),
+            Err(DecodeError::InvalidHeaderType)
+        ));
+    }
 }
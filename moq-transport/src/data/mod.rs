@@ -9,3 +9,18 @@ pub use fetch::*;
 pub use header::*;
 pub use object_status::*;
 pub use subgroup::*;
+
+use crate::coding::{DecodeVersioned, EncodeVersioned};
+
+/// The Publisher Priority of an object whose header leaves it out, which draft-15 allows.
+pub const DEFAULT_PUBLISHER_PRIORITY: u8 = 128;
+
+// The wire formats haven't changed since draft-14, so these use the default versioned codecs.
+// A type that changes in a later draft drops its impl here and picks the format per version itself.
+impl DecodeVersioned for SubgroupObject {}
+impl EncodeVersioned for SubgroupObject {}
+impl DecodeVersioned for SubgroupObjectExt {}
+impl EncodeVersioned for SubgroupObjectExt {}
+impl EncodeVersioned for FetchHeader {}
+impl DecodeVersioned for FetchObject {}
+impl EncodeVersioned for FetchObject {}
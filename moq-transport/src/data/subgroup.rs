@@ -1,5 +1,8 @@
-use crate::coding::{Decode, DecodeError, Encode, EncodeError, KeyValuePairs};
-use crate::data::{ObjectStatus, StreamHeaderType};
+use crate::coding::{Decode, DecodeError, Encode, EncodeError, EncodeVersioned, KeyValuePairs};
+use crate::data::{
+    ObjectStatus, StreamHeaderType, DEFAULT_PUBLISHER_PRIORITY, SUBGROUP_DEFAULT_PRIORITY,
+};
+use crate::setup::Version;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct SubgroupHeader {
@@ -25,6 +28,15 @@ impl SubgroupHeader {
     pub fn decode<R: bytes::Buf>(
         header_type: StreamHeaderType,
         r: &mut R,
+    ) -> Result<Self, DecodeError> {
+        Self::decode_body(header_type, true, r)
+    }
+
+    /// Decode a header whose Publisher Priority may have been left out, which draft-15 allows.
+    pub(crate) fn decode_body<R: bytes::Buf>(
+        header_type: StreamHeaderType,
+        has_priority: bool,
+        r: &mut R,
     ) -> Result<Self, DecodeError> {
         log::trace!(
             "[DECODE] SubgroupHeader: starting decode with header_type={:?}, buffer_remaining={} bytes",
@@ -52,7 +64,10 @@ impl SubgroupHeader {
             }
         };
 
-        let publisher_priority = u8::decode(r)?;
+        let publisher_priority = match has_priority {
+            true => u8::decode(r)?,
+            false => DEFAULT_PUBLISHER_PRIORITY,
+        };
         log::trace!(
             "[DECODE] SubgroupHeader: publisher_priority={}, buffer_remaining={} bytes",
             publisher_priority,
@@ -81,6 +96,33 @@ impl SubgroupHeader {
 
 impl Encode for SubgroupHeader {
     fn encode<W: bytes::BufMut>(&self, w: &mut W) -> Result<(), EncodeError> {
+        self.encode_body(w, true)
+    }
+}
+
+// Draft-15 leaves the Publisher Priority out of the header when it's the default.
+impl EncodeVersioned for SubgroupHeader {
+    fn encode_versioned<W: bytes::BufMut>(
+        &self,
+        w: &mut W,
+        version: Version,
+    ) -> Result<(), EncodeError> {
+        match version {
+            Version::DRAFT_14 => self.encode(w),
+            Version::DRAFT_15 => {
+                self.encode_body(w, self.publisher_priority != DEFAULT_PUBLISHER_PRIORITY)
+            }
+            _ => Err(EncodeError::UnsupportedVersion(version)),
+        }
+    }
+}
+
+impl SubgroupHeader {
+    fn encode_body<W: bytes::BufMut>(
+        &self,
+        w: &mut W,
+        has_priority: bool,
+    ) -> Result<(), EncodeError> {
         log::trace!(
             "[ENCODE] SubgroupHeader: starting encode - track_alias={}, group_id={}, subgroup_id={:?}, priority={}, header_type={:?}",
             self.track_alias,
@@ -92,8 +134,14 @@ impl Encode for SubgroupHeader {
 
         let start_pos = w.remaining_mut();
 
-        self.header_type.encode(w)?;
-        log::trace!("[ENCODE] SubgroupHeader: encoded header_type");
+        match has_priority {
+            true => self.header_type.encode(w)?,
+            false => (self.header_type as u64 | SUBGROUP_DEFAULT_PRIORITY).encode(w)?,
+        }
+        log::trace!(
+            "[ENCODE] SubgroupHeader: encoded header_type, has_priority={}",
+            has_priority
+        );
 
         self.track_alias.encode(w)?;
         log::trace!(
@@ -125,11 +173,13 @@ impl Encode for SubgroupHeader {
             log::trace!("[ENCODE] SubgroupHeader: subgroup_id not encoded (not required for this header type)");
         }
 
-        self.publisher_priority.encode(w)?;
-        log::trace!(
-            "[ENCODE] SubgroupHeader: encoded publisher_priority={}",
-            self.publisher_priority
-        );
+        if has_priority {
+            self.publisher_priority.encode(w)?;
+            log::trace!(
+                "[ENCODE] SubgroupHeader: encoded publisher_priority={}",
+                self.publisher_priority
+            );
+        }
 
         let bytes_written = start_pos - w.remaining_mut();
         log::debug!(
@@ -141,6 +191,34 @@ impl Encode for SubgroupHeader {
     }
 }
 
+/// Converts between object ids and the object_id_delta sent on a subgroup stream.
+/// The first object's delta is its id, after that it's relative to the previous object id plus one.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ObjectIdDelta {
+    next_object_id: u64,
+}
+
+impl ObjectIdDelta {
+    /// Returns the delta to send for object_id, which must be larger than the previous one.
+    pub fn encode(&mut self, object_id: u64) -> Result<u64, EncodeError> {
+        let delta = object_id
+            .checked_sub(self.next_object_id)
+            .ok_or(EncodeError::InvalidValue)?;
+        self.next_object_id = object_id + 1;
+        Ok(delta)
+    }
+
+    /// Returns the object id for a received delta.
+    pub fn decode(&mut self, delta: u64) -> Result<u64, DecodeError> {
+        let object_id = self
+            .next_object_id
+            .checked_add(delta)
+            .ok_or(DecodeError::InvalidValue)?;
+        self.next_object_id = object_id.checked_add(1).ok_or(DecodeError::InvalidValue)?;
+        Ok(object_id)
+    }
+}
+
 // Subgroup Object without Extension headers (version with ExtensionHeaders is below)
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct SubgroupObject {
@@ -352,6 +430,7 @@ impl Encode for SubgroupObjectExt {
 }
 
 // TODO SLG - add more unit tests
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -389,4 +468,46 @@ mod tests {
         let decoded = SubgroupObjectExt::decode(&mut buf).unwrap();
         assert_eq!(decoded, msg);
     }
+
+    #[test]
+    fn object_id_delta_round_trip() {
+        let object_ids = [3, 4, 5, 9, 10, 20];
+
+        let mut buf = BytesMut::new();
+        let mut deltas = ObjectIdDelta::default();
+        for object_id in object_ids {
+            SubgroupObject {
+                object_id_delta: deltas.encode(object_id).unwrap(),
+                payload_length: 1,
+                status: None,
+            }
+            .encode(&mut buf)
+            .unwrap();
+        }
+
+        let mut deltas = ObjectIdDelta::default();
+        let mut decoded = Vec::new();
+        while !buf.is_empty() {
+            let object = SubgroupObject::decode(&mut buf).unwrap();
+            decoded.push(deltas.decode(object.object_id_delta).unwrap());
+        }
+
+        assert_eq!(decoded, object_ids);
+    }
+
+    #[test]
+    fn object_id_delta_values() {
+        let mut deltas = ObjectIdDelta::default();
+        assert_eq!(deltas.encode(0).unwrap(), 0);
+        assert_eq!(deltas.encode(1).unwrap(), 0);
+        assert_eq!(deltas.encode(5).unwrap(), 3);
+
+        // Object ids must increase.
+        assert!(deltas.encode(5).is_err());
+
+        let mut deltas = ObjectIdDelta::default();
+        assert_eq!(deltas.decode(2).unwrap(), 2);
+        assert_eq!(deltas.decode(0).unwrap(), 3);
+        assert!(deltas.decode(u64::MAX).is_err());
+    }
 }
@@ -0,0 +1,166 @@
+use std::fmt;
+
+// Use a macro to generate the error code enums rather than copy-paste.
+// Each one converts to and from the code sent on the wire, keeping any unknown code as is.
+macro_rules! error_codes {
+    {$(
+        $(#[$doc:meta])*
+        $enum:ident {
+            $($name:ident = $val:expr => $display:expr,)*
+        }
+    )*} => {
+        $(
+            $(#[$doc])*
+            #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+            pub enum $enum {
+                $($name,)*
+                Unknown(u64),
+            }
+
+            impl $enum {
+                /// An integer code that is sent over the wire.
+                pub fn code(&self) -> u64 {
+                    match self {
+                        $(Self::$name => $val,)*
+                        Self::Unknown(code) => *code,
+                    }
+                }
+            }
+
+            impl From<u64> for $enum {
+                fn from(code: u64) -> Self {
+                    match code {
+                        $($val => Self::$name,)*
+                        _ => Self::Unknown(code),
+                    }
+                }
+            }
+
+            impl fmt::Display for $enum {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    match self {
+                        $(Self::$name => write!(f, $display),)*
+                        Self::Unknown(code) => write!(f, "unknown code {:#x}", code),
+                    }
+                }
+            }
+        )*
+    }
+}
+
+error_codes! {
+    /// The error codes sent in SUBSCRIBE_ERROR, and in TRACK_STATUS_ERROR which shares them.
+    SubscribeErrorCode {
+        InternalError = 0x0 => "internal error",
+        Unauthorized = 0x1 => "unauthorized",
+        Timeout = 0x2 => "timeout",
+        NotSupported = 0x3 => "not supported",
+        TrackDoesNotExist = 0x4 => "track does not exist",
+        InvalidRange = 0x5 => "invalid range",
+        MalformedAuthToken = 0x10 => "malformed auth token",
+        ExpiredAuthToken = 0x12 => "expired auth token",
+    }
+
+    /// The error codes sent in FETCH_ERROR.
+    FetchErrorCode {
+        InternalError = 0x0 => "internal error",
+        Unauthorized = 0x1 => "unauthorized",
+        Timeout = 0x2 => "timeout",
+        NotSupported = 0x3 => "not supported",
+        TrackDoesNotExist = 0x4 => "track does not exist",
+        InvalidRange = 0x5 => "invalid range",
+        NoObjects = 0x6 => "no objects",
+        InvalidJoiningRequestId = 0x7 => "invalid joining request id",
+        UnknownStatusInRange = 0x8 => "unknown status in range",
+        MalformedTrack = 0x9 => "malformed track",
+        MalformedAuthToken = 0x10 => "malformed auth token",
+        ExpiredAuthToken = 0x12 => "expired auth token",
+    }
+
+    /// The error codes sent in PUBLISH_NAMESPACE_ERROR and PUBLISH_NAMESPACE_CANCEL.
+    PublishNamespaceErrorCode {
+        InternalError = 0x0 => "internal error",
+        Unauthorized = 0x1 => "unauthorized",
+        Timeout = 0x2 => "timeout",
+        NotSupported = 0x3 => "not supported",
+        Uninterested = 0x4 => "uninterested",
+        MalformedAuthToken = 0x10 => "malformed auth token",
+        ExpiredAuthToken = 0x12 => "expired auth token",
+    }
+
+    /// The error codes sent in PUBLISH_ERROR.
+    PublishErrorCode {
+        InternalError = 0x0 => "internal error",
+        Unauthorized = 0x1 => "unauthorized",
+        Timeout = 0x2 => "timeout",
+        NotSupported = 0x3 => "not supported",
+        Uninterested = 0x4 => "uninterested",
+        MalformedAuthToken = 0x10 => "malformed auth token",
+        ExpiredAuthToken = 0x12 => "expired auth token",
+    }
+
+    /// The error codes sent in SUBSCRIBE_NAMESPACE_ERROR.
+    SubscribeNamespaceErrorCode {
+        InternalError = 0x0 => "internal error",
+        Unauthorized = 0x1 => "unauthorized",
+        Timeout = 0x2 => "timeout",
+        NotSupported = 0x3 => "not supported",
+        NamespacePrefixUnknown = 0x4 => "namespace prefix unknown",
+        NamespacePrefixOverlap = 0x5 => "namespace prefix overlap",
+        MalformedAuthToken = 0x10 => "malformed auth token",
+        ExpiredAuthToken = 0x12 => "expired auth token",
+    }
+
+    /// The status codes sent in PUBLISH_DONE.
+    PublishDoneStatus {
+        InternalError = 0x0 => "internal error",
+        Unauthorized = 0x1 => "unauthorized",
+        TrackEnded = 0x2 => "track ended",
+        SubscriptionEnded = 0x3 => "subscription ended",
+        GoingAway = 0x4 => "going away",
+        Expired = 0x5 => "expired",
+        TooFarBehind = 0x6 => "too far behind",
+        MalformedTrack = 0x7 => "malformed track",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for code in 0x0..0x20 {
+            assert_eq!(SubscribeErrorCode::from(code).code(), code);
+            assert_eq!(FetchErrorCode::from(code).code(), code);
+            assert_eq!(PublishNamespaceErrorCode::from(code).code(), code);
+            assert_eq!(PublishErrorCode::from(code).code(), code);
+            assert_eq!(SubscribeNamespaceErrorCode::from(code).code(), code);
+            assert_eq!(PublishDoneStatus::from(code).code(), code);
+        }
+    }
+
+    #[test]
+    fn known_codes() {
+        assert_eq!(
+            SubscribeErrorCode::from(0x4),
+            SubscribeErrorCode::TrackDoesNotExist
+        );
+        assert_eq!(
+            SubscribeErrorCode::from(0x6),
+            SubscribeErrorCode::Unknown(0x6)
+        );
+        assert_eq!(FetchErrorCode::from(0x6), FetchErrorCode::NoObjects);
+        assert_eq!(
+            PublishNamespaceErrorCode::from(0x4),
+            PublishNamespaceErrorCode::Uninterested
+        );
+        assert_eq!(PublishErrorCode::from(0x4), PublishErrorCode::Uninterested);
+        assert_eq!(
+            SubscribeNamespaceErrorCode::from(0x5),
+            SubscribeNamespaceErrorCode::NamespacePrefixOverlap
+        );
+        assert_eq!(PublishDoneStatus::from(0x5), PublishDoneStatus::Expired);
+        assert_eq!(PublishDoneStatus::TrackEnded.to_string(), "track ended");
+    }
+}
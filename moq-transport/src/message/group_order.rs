@@ -8,6 +8,18 @@ pub enum GroupOrder {
     Descending = 0x2,
 }
 
+impl GroupOrder {
+    /// Resolve the subscriber's preference, deferring to the publisher's when asked.
+    /// The publisher falls back to ascending if it has no preference either.
+    pub fn resolve(self, publisher: GroupOrder) -> GroupOrder {
+        match (self, publisher) {
+            (Self::Publisher, Self::Publisher) => Self::Ascending,
+            (Self::Publisher, publisher) => publisher,
+            (subscriber, _) => subscriber,
+        }
+    }
+}
+
 impl Encode for GroupOrder {
     fn encode<W: bytes::BufMut>(&self, w: &mut W) -> Result<(), EncodeError> {
         let val = *self as u8;
@@ -56,6 +68,26 @@ mod tests {
         assert_eq!(decoded, go);
     }
 
+    #[test]
+    fn resolve() {
+        assert_eq!(
+            GroupOrder::Publisher.resolve(GroupOrder::Descending),
+            GroupOrder::Descending
+        );
+        assert_eq!(
+            GroupOrder::Publisher.resolve(GroupOrder::Publisher),
+            GroupOrder::Ascending
+        );
+        assert_eq!(
+            GroupOrder::Ascending.resolve(GroupOrder::Descending),
+            GroupOrder::Ascending
+        );
+        assert_eq!(
+            GroupOrder::Descending.resolve(GroupOrder::Ascending),
+            GroupOrder::Descending
+        );
+    }
+
     #[test]
     fn decode_bad_value() {
         let data: Vec<u8> = vec![0x03]; // Invalid filter type
@@ -5,6 +5,7 @@
 //! The only exception are OBJECT "messages", which are sent over dedicated QUIC streams.
 //!
 
+mod error_code;
 mod fetch;
 mod fetch_cancel;
 mod fetch_error;
@@ -14,6 +15,7 @@ mod filter_type;
 mod go_away;
 mod group_order;
 mod max_request_id;
+mod param_types;
 mod pubilsh_namespace_done;
 mod publish;
 mod publish_done;
@@ -39,6 +41,7 @@ mod track_status_ok;
 mod unsubscribe;
 mod unsubscribe_namespace;
 
+pub use error_code::*;
 pub use fetch::*;
 pub use fetch_cancel::*;
 pub use fetch_error::*;
@@ -48,6 +51,7 @@ pub use filter_type::*;
 pub use go_away::*;
 pub use group_order::*;
 pub use max_request_id::*;
+pub use param_types::*;
 pub use pubilsh_namespace_done::*;
 pub use publish::*;
 pub use publish_done::*;
@@ -73,7 +77,12 @@ pub use track_status_ok::*;
 pub use unsubscribe::*;
 pub use unsubscribe_namespace::*;
 
-use crate::coding::{Decode, DecodeError, Encode, EncodeError};
+use crate::coding::{
+    decode_length_prefixed, encode_length_prefixed, Decode, DecodeError, DecodeVersioned, Encode,
+    EncodeError, EncodeVersioned, KeyValuePairs,
+};
+use crate::setup::Version;
+use bytes::BytesMut;
 use std::fmt;
 
 // Use a macro to generate the message types rather than copy-paste.
@@ -88,45 +97,50 @@ macro_rules! message_types {
 
 		impl Decode for Message {
 			fn decode<R: bytes::Buf>(r: &mut R) -> Result<Self, DecodeError> {
-				let t = u64::decode(r)?;
-				let _len = u16::decode(r)?;
+				Self::decode_versioned(r, Version::DRAFT_14)
+			}
+		}
+
+		impl Encode for Message {
+			fn encode<W: bytes::BufMut>(&self, w: &mut W) -> Result<(), EncodeError> {
+				self.encode_versioned(w, Version::DRAFT_14)
+			}
+		}
 
-				// TODO: Check the length of the message.
+		// Each message picks its own wire format for the version.
+		impl DecodeVersioned for Message {
+			fn decode_versioned<R: bytes::Buf>(r: &mut R, version: Version) -> Result<Self, DecodeError> {
+				let t = u64::decode(r)?;
 
-				match t {
+				// The body is bounded by its length, so a malformed message can't bleed into the next one.
+				decode_length_prefixed(r, |body| match t {
 					$($val => {
-						let msg = $name::decode(r)?;
+						let msg = $name::decode_versioned(body, version)?;
 						Ok(Self::$name(msg))
 					})*
+					// Unknown control messages are a protocol violation, but skip the body so the error
+					// is reported for this message rather than as garbage in the next one.
 					_ => Err(DecodeError::InvalidMessage(t)),
-				}
+				})
 			}
 		}
 
-		impl Encode for Message {
-			fn encode<W: bytes::BufMut>(&self, w: &mut W) -> Result<(), EncodeError> {
+		impl EncodeVersioned for Message {
+			fn encode_versioned<W: bytes::BufMut>(&self, w: &mut W, version: Version) -> Result<(), EncodeError> {
+				// A generic writer can't be patched once written, so the length has to be found by
+				// encoding into a buffer first.  The session writers use encode_bytes to avoid this.
+				let mut buf = BytesMut::new();
+				self.encode_bytes(&mut buf, version)?;
+				Self::encode_remaining(w, buf.len())?;
+				w.put(buf);
+				Ok(())
+			}
+
+			/// Encode the type and body, filling in the length once the body is written.
+			fn encode_bytes(&self, buf: &mut BytesMut, version: Version) -> Result<(), EncodeError> {
+				self.id().encode(buf)?;
 				match self {
-					$(Self::$name(ref m) => {
-						self.id().encode(w)?;
-
-						// Find out the length of the message
-						// by encoding it into a buffer and then encoding the length.
-						// This is a bit wasteful, but it's the only way to know the length.
-                        // TODO SLG - perhaps we can store the position of the Length field in the BufMut and
-                        //       write the length later, to avoid the copy of the message bytes?
-						let mut buf = Vec::new();
-						m.encode(&mut buf).unwrap();
-                        if buf.len() > u16::MAX as usize {
-                            return Err(EncodeError::MsgBoundsExceeded);
-                        }
-                        (buf.len() as u16).encode(w)?;
-
-						// At least don't encode the message twice.
-						// Instead, write the buffer directly to the writer.
-                        Self::encode_remaining(w, buf.len())?;
-						w.put_slice(&buf);
-						Ok(())
-					},)*
+					$(Self::$name(ref m) => encode_length_prefixed(buf, |buf| m.encode_versioned(buf, version)),)*
 				}
 			}
 		}
@@ -222,3 +236,202 @@ message_types! {
     PublishOk = 0x1e,
     PublishError = 0x1f,
 }
+
+// The messages whose wire format hasn't changed since draft-14, which use the default versioned codecs.
+// A message that changes in a later draft is moved out of this list and implements them itself.
+macro_rules! draft_14_format {
+    {$($name:ident,)*} => {
+        $(
+            impl DecodeVersioned for $name {}
+            impl EncodeVersioned for $name {}
+        )*
+    }
+}
+
+draft_14_format! {
+    GoAway,
+    MaxRequestId,
+    RequestsBlocked,
+    SubscribeUpdate,
+    Subscribe,
+    Unsubscribe,
+    SubscribeError,
+    PublishNamespace,
+    PublishNamespaceDone,
+    PublishNamespaceOk,
+    PublishNamespaceError,
+    PublishNamespaceCancel,
+    TrackStatus,
+    TrackStatusOk,
+    TrackStatusError,
+    SubscribeNamespace,
+    UnsubscribeNamespace,
+    SubscribeNamespaceOk,
+    SubscribeNamespaceError,
+    Fetch,
+    FetchCancel,
+    FetchOk,
+    FetchError,
+    Publish,
+    PublishDone,
+    PublishOk,
+    PublishError,
+}
+
+impl Message {
+    /// The request ID of a message that starts a new request, counting against MAX_REQUEST_ID.
+    pub fn request_id(&self) -> Option<u64> {
+        match self {
+            Self::Subscribe(m) => Some(m.id),
+            Self::SubscribeUpdate(m) => Some(m.id),
+            Self::PublishNamespace(m) => Some(m.id),
+            Self::TrackStatus(m) => Some(m.id),
+            Self::SubscribeNamespace(m) => Some(m.id),
+            Self::Fetch(m) => Some(m.id),
+            Self::Publish(m) => Some(m.id),
+            _ => None,
+        }
+    }
+
+    /// True if this message cancels the given request, such as an UNSUBSCRIBE for a SUBSCRIBE.
+    pub fn cancels(&self, request: &Message) -> bool {
+        match (self, request) {
+            (Self::Unsubscribe(m), Self::Subscribe(r)) => m.id == r.id,
+            (Self::FetchCancel(m), Self::Fetch(r)) => m.id == r.id,
+            (Self::PublishNamespaceDone(m), Self::PublishNamespace(r)) => {
+                m.track_namespace == r.track_namespace
+            }
+            (Self::UnsubscribeNamespace(m), Self::SubscribeNamespace(r)) => {
+                m.track_namespace_prefix == r.track_namespace_prefix
+            }
+            _ => false,
+        }
+    }
+
+    /// The parameters of a request message, which may carry an AUTHORIZATION_TOKEN.
+    pub fn params(&self) -> Option<&KeyValuePairs> {
+        match self {
+            Self::Subscribe(m) => Some(&m.params),
+            Self::SubscribeUpdate(m) => Some(&m.params),
+            Self::PublishNamespace(m) => Some(&m.params),
+            Self::TrackStatus(m) => Some(&m.params),
+            Self::SubscribeNamespace(m) => Some(&m.params),
+            Self::Fetch(m) => Some(&m.params),
+            Self::Publish(m) => Some(&m.params),
+            _ => None,
+        }
+    }
+
+    /// The request ID answered by a response message.
+    pub fn response_id(&self) -> Option<u64> {
+        match self {
+            Self::SubscribeOk(m) => Some(m.id),
+            Self::SubscribeError(m) => Some(m.id),
+            Self::PublishNamespaceOk(m) => Some(m.id),
+            Self::PublishNamespaceError(m) => Some(m.id),
+            Self::TrackStatusOk(m) => Some(m.id),
+            Self::TrackStatusError(m) => Some(m.id),
+            Self::SubscribeNamespaceOk(m) => Some(m.id),
+            Self::SubscribeNamespaceError(m) => Some(m.id),
+            Self::FetchOk(m) => Some(m.id),
+            Self::FetchError(m) => Some(m.id),
+            Self::PublishOk(m) => Some(m.id),
+            Self::PublishError(m) => Some(m.id),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_decode() {
+        let msg: Message = MaxRequestId { request_id: 1000 }.into();
+
+        let mut buf = BytesMut::new();
+        msg.encode_bytes(&mut buf, Version::DRAFT_14).unwrap();
+        assert_eq!(
+            buf.to_vec(),
+            vec![
+                0x15, // Type
+                0x00, 0x02, // Length
+                0x43, 0xe8, // Request ID
+            ]
+        );
+
+        // The generic encoder produces the same bytes.
+        let mut generic = Vec::new();
+        msg.encode(&mut generic).unwrap();
+        assert_eq!(generic, buf.to_vec());
+
+        let decoded = Message::decode(&mut buf).unwrap();
+        assert!(matches!(
+            decoded,
+            Message::MaxRequestId(MaxRequestId { request_id: 1000 })
+        ));
+    }
+
+    #[test]
+    fn encode_decode_versioned() {
+        let msg: Message = MaxRequestId { request_id: 1000 }.into();
+
+        let mut buf = BytesMut::new();
+        msg.encode_versioned(&mut buf, Version::DRAFT_14).unwrap();
+        let decoded = Message::decode_versioned(&mut buf, Version::DRAFT_14).unwrap();
+        assert!(matches!(
+            decoded,
+            Message::MaxRequestId(MaxRequestId { request_id: 1000 })
+        ));
+
+        // The message body picks the format, which doesn't exist for older drafts.
+        assert!(matches!(
+            msg.encode_bytes(&mut buf, Version::DRAFT_13),
+            Err(EncodeError::UnsupportedVersion(Version::DRAFT_13))
+        ));
+        let mut buf: &[u8] = &[0x15, 0x00, 0x02, 0x43, 0xe8];
+        assert!(matches!(
+            Message::decode_versioned(&mut buf, Version::DRAFT_13),
+            Err(DecodeError::UnsupportedVersion(Version::DRAFT_13))
+        ));
+    }
+
+    #[test]
+    fn decode_partial() {
+        // The body hasn't fully arrived yet, so wait for more.
+        let mut buf: &[u8] = &[0x15, 0x00, 0x02, 0x43];
+        assert!(matches!(
+            Message::decode(&mut buf),
+            Err(DecodeError::More(1))
+        ));
+    }
+
+    #[test]
+    fn decode_bad_length() {
+        // Trailing bytes after the request ID
+        let mut buf: &[u8] = &[0x15, 0x00, 0x02, 0x07, 0x00];
+        assert!(matches!(
+            Message::decode(&mut buf),
+            Err(DecodeError::InvalidLength(2, 1))
+        ));
+
+        // The request ID runs past the declared length
+        let mut buf: &[u8] = &[0x15, 0x00, 0x01, 0x43, 0xe8];
+        assert!(matches!(
+            Message::decode(&mut buf),
+            Err(DecodeError::InvalidLength(1, 2))
+        ));
+    }
+
+    #[test]
+    fn decode_unknown() {
+        // The unknown body is skipped, leaving the next message intact.
+        let mut buf: &[u8] = &[0x3f, 0x00, 0x02, 0xaa, 0xbb, 0x15];
+        assert!(matches!(
+            Message::decode(&mut buf),
+            Err(DecodeError::InvalidMessage(0x3f))
+        ));
+        assert_eq!(buf, &[0x15]);
+    }
+}
@@ -0,0 +1,16 @@
+/// Version Specific Parameter Types, sent in the parameters of control messages.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u64)]
+pub enum ParameterType {
+    DeliveryTimeout = 0x2,
+    AuthorizationToken = 0x3,
+    MaxCacheDuration = 0x4,
+    /// Carries SUBSCRIBE_OK's expires from draft-15, which dropped the field from the message.
+    Expires = 0x8,
+}
+
+impl From<ParameterType> for u64 {
+    fn from(value: ParameterType) -> Self {
+        value as u64
+    }
+}
@@ -1,6 +1,6 @@
 use crate::coding::{Decode, DecodeError, Encode, EncodeError, ReasonPhrase};
 
-// TODO SLG - add an enum for status_codes
+// The status codes are defined by [super::PublishDoneStatus].
 
 /// Sent by the publisher to cleanly terminate a Subscription.
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -1,5 +1,9 @@
-use crate::coding::{Decode, DecodeError, Encode, EncodeError, KeyValuePairs, Location};
-use crate::message::GroupOrder;
+use crate::coding::{
+    Decode, DecodeError, DecodeVersioned, Encode, EncodeError, EncodeVersioned, KeyValuePairs,
+    Location,
+};
+use crate::message::{GroupOrder, ParameterType};
+use crate::setup::Version;
 
 /// Sent by the publisher to accept a Subscribe.
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -70,6 +74,75 @@ impl Encode for SubscribeOk {
     }
 }
 
+// Draft-15 sends the expires as a parameter rather than a field, and leaves it out when it's zero.
+impl DecodeVersioned for SubscribeOk {
+    fn decode_versioned<R: bytes::Buf>(r: &mut R, version: Version) -> Result<Self, DecodeError> {
+        match version {
+            Version::DRAFT_14 => Self::decode(r),
+            Version::DRAFT_15 => {
+                let id = u64::decode(r)?;
+                let track_alias = u64::decode(r)?;
+                let group_order = GroupOrder::decode(r)?;
+                let content_exists = bool::decode(r)?;
+                let largest_location = match content_exists {
+                    true => Some(Location::decode(r)?),
+                    false => None,
+                };
+                let mut params = KeyValuePairs::decode(r)?;
+                let expires = params
+                    .get_intvalue(ParameterType::Expires.into())
+                    .unwrap_or(0);
+                params.0.remove(&ParameterType::Expires.into());
+
+                Ok(Self {
+                    id,
+                    track_alias,
+                    expires,
+                    group_order,
+                    content_exists,
+                    largest_location,
+                    params,
+                })
+            }
+            _ => Err(DecodeError::UnsupportedVersion(version)),
+        }
+    }
+}
+
+impl EncodeVersioned for SubscribeOk {
+    fn encode_versioned<W: bytes::BufMut>(
+        &self,
+        w: &mut W,
+        version: Version,
+    ) -> Result<(), EncodeError> {
+        match version {
+            Version::DRAFT_14 => self.encode(w),
+            Version::DRAFT_15 => {
+                self.id.encode(w)?;
+                self.track_alias.encode(w)?;
+                self.group_order.encode(w)?;
+                self.content_exists.encode(w)?;
+                if self.content_exists {
+                    if let Some(largest) = &self.largest_location {
+                        largest.encode(w)?;
+                    } else {
+                        return Err(EncodeError::MissingField("LargestLocation".to_string()));
+                    }
+                }
+
+                let mut params = self.params.clone();
+                if self.expires > 0 {
+                    params.set_intvalue(ParameterType::Expires.into(), self.expires);
+                }
+                params.encode(w)?;
+
+                Ok(())
+            }
+            _ => Err(EncodeError::UnsupportedVersion(version)),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -113,4 +186,66 @@ mod tests {
         let encoded = msg.encode(&mut buf);
         assert!(matches!(encoded.unwrap_err(), EncodeError::MissingField(_)));
     }
+
+    #[test]
+    fn encode_decode_versioned() {
+        let mut kvps = KeyValuePairs::new();
+        kvps.set_bytesvalue(123, vec![0x00, 0x01, 0x02, 0x03]);
+
+        let msg = SubscribeOk {
+            id: 12345,
+            track_alias: 100,
+            expires: 3600,
+            group_order: GroupOrder::Publisher,
+            content_exists: true,
+            largest_location: Some(Location::new(2, 3)),
+            params: kvps,
+        };
+
+        for version in Version::SUPPORTED {
+            let mut buf = BytesMut::new();
+            msg.encode_versioned(&mut buf, *version).unwrap();
+            let decoded = SubscribeOk::decode_versioned(&mut buf, *version).unwrap();
+            assert_eq!(decoded, msg, "{version}");
+        }
+    }
+
+    #[test]
+    fn encode_versioned_expires() {
+        let mut msg = SubscribeOk {
+            id: 1,
+            track_alias: 2,
+            expires: 0,
+            group_order: GroupOrder::Ascending,
+            content_exists: false,
+            largest_location: None,
+            params: Default::default(),
+        };
+
+        let mut buf = BytesMut::new();
+        msg.encode_versioned(&mut buf, Version::DRAFT_15).unwrap();
+        // Request ID, track alias, group order, content exists, no parameters.
+        assert_eq!(buf.to_vec(), vec![0x01, 0x02, 0x01, 0x00, 0x00]);
+        let decoded = SubscribeOk::decode_versioned(&mut buf, Version::DRAFT_15).unwrap();
+        assert_eq!(decoded, msg);
+
+        // Draft-15 moves the expires into the parameters.
+        msg.expires = 10;
+        msg.encode_versioned(&mut buf, Version::DRAFT_15).unwrap();
+        #[rustfmt::skip]
+        assert_eq!(
+            buf.to_vec(),
+            vec![
+                0x01, 0x02, 0x01, 0x00,
+                0x01, // 1 Parameter
+                0x08, 0x0a, // Expires = 10
+            ]
+        );
+        let decoded = SubscribeOk::decode_versioned(&mut buf, Version::DRAFT_15).unwrap();
+        assert_eq!(decoded, msg);
+
+        // Draft-14 keeps it as a field.
+        msg.encode_versioned(&mut buf, Version::DRAFT_14).unwrap();
+        assert_eq!(buf.to_vec(), vec![0x01, 0x02, 0x0a, 0x01, 0x00, 0x00]);
+    }
 }
@@ -1,8 +1,6 @@
 use crate::coding::{Decode, DecodeError, Encode, EncodeError, ReasonPhrase};
 
-// TODO SLG - The next draft is going to merge all these error messages to a
-//            common RequestError message, so we won't do a lot of work on these
-//            existing messages.  We should add an enum for all the various error codes.
+// The error codes are shared with SUBSCRIBE_ERROR, see [super::SubscribeErrorCode].
 
 /// Sent by the subscriber to reject an Announce.
 #[derive(Clone, Debug)]
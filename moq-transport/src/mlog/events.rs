@@ -596,12 +596,12 @@ fn subgroup_object_ext_to_json(
     object_id: u64,
     object: &data::SubgroupObjectExt,
 ) -> JsonValue {
-    // TODO encode extension headers
     let mut object_data = json!({
         "group_id": group_id,
         "subgroup_id": subgroup_id,
         "object_id": object_id,
         "extension_headers_length": object.extension_headers.0.len(),
+        "extension_headers": key_value_pairs_to_vec(&object.extension_headers),
         "object_payload_length": object.payload_length,
     });
 
@@ -1,5 +1,7 @@
-use std::{fmt, sync::Arc};
+use std::{fmt, future::Future, sync::Arc};
 
+use crate::coding::KeyValuePairs;
+use crate::data::ObjectStatus;
 use crate::watch::State;
 
 use super::{ServeError, Track};
@@ -68,6 +70,16 @@ impl DatagramsWriter {
 
         Ok(())
     }
+
+    /// Returns true once every reader has been dropped.
+    pub fn is_unused(&self) -> bool {
+        self.state.is_dropped()
+    }
+
+    /// Returns a future that resolves once every reader has been dropped, or this writer is.
+    pub fn unused(&self) -> impl Future<Output = ()> {
+        self.state.dropped()
+    }
 }
 
 #[derive(Clone)]
@@ -124,6 +136,12 @@ pub struct Datagram {
     pub object_id: u64,
     pub priority: u8,
     pub payload: bytes::Bytes,
+
+    /// The status of the object, such as the end of the group or track, in which case the payload is empty.
+    pub status: ObjectStatus,
+
+    /// Extension headers, such as capture timestamps, forwarded untouched by relays.
+    pub extension_headers: KeyValuePairs,
 }
 
 impl fmt::Debug for Datagram {
@@ -133,6 +151,8 @@ impl fmt::Debug for Datagram {
             .field("group_id", &self.group_id)
             .field("priority", &self.priority)
             .field("payload", &self.payload.len())
+            .field("status", &self.status)
+            .field("extension_headers", &self.extension_headers)
             .finish()
     }
 }
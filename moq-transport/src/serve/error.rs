@@ -1,3 +1,8 @@
+use crate::message::{
+    FetchErrorCode, PublishDoneStatus, PublishErrorCode, PublishNamespaceErrorCode,
+    SubscribeErrorCode, SubscribeNamespaceErrorCode,
+};
+
 #[derive(thiserror::Error, Debug, Clone, PartialEq)]
 pub enum ServeError {
     // TODO stop using?
@@ -22,6 +27,24 @@ pub enum ServeError {
     #[error("wrong size")]
     Size,
 
+    #[error("invalid range")]
+    InvalidRange,
+
+    #[error("unauthorized")]
+    Unauthorized,
+
+    #[error("timeout")]
+    Timeout,
+
+    #[error("going away")]
+    GoingAway,
+
+    #[error("expired")]
+    Expired,
+
+    #[error("not supported: {0}")]
+    NotSupported(String),
+
     #[error("internal error: {0}")]
     Internal(String),
 }
@@ -36,7 +59,164 @@ impl ServeError {
             Self::Duplicate => 409,
             Self::Mode => 400,
             Self::Size => 413,
+            Self::InvalidRange => 416,
+            Self::Unauthorized => 401,
+            Self::Timeout => 408,
+            Self::GoingAway => 503,
+            Self::Expired => 410,
+            Self::NotSupported(_) => 501,
             Self::Internal(_) => 500,
         }
     }
+
+    /// The code sent in SUBSCRIBE_ERROR or TRACK_STATUS_ERROR when rejecting a request with this error.
+    /// A code received from upstream, such as by a relay, is passed through as is.
+    pub fn subscribe_error_code(&self) -> SubscribeErrorCode {
+        match self {
+            Self::NotFound => SubscribeErrorCode::TrackDoesNotExist,
+            Self::InvalidRange => SubscribeErrorCode::InvalidRange,
+            Self::NotSupported(_) | Self::Mode => SubscribeErrorCode::NotSupported,
+            Self::Unauthorized => SubscribeErrorCode::Unauthorized,
+            Self::Timeout => SubscribeErrorCode::Timeout,
+            Self::Closed(code) => SubscribeErrorCode::from(*code),
+            _ => SubscribeErrorCode::InternalError,
+        }
+    }
+
+    /// The code sent in FETCH_ERROR when rejecting a fetch with this error.
+    pub fn fetch_error_code(&self) -> FetchErrorCode {
+        match self {
+            Self::NotFound => FetchErrorCode::TrackDoesNotExist,
+            Self::InvalidRange => FetchErrorCode::InvalidRange,
+            Self::NotSupported(_) | Self::Mode => FetchErrorCode::NotSupported,
+            Self::Unauthorized => FetchErrorCode::Unauthorized,
+            Self::Timeout => FetchErrorCode::Timeout,
+            Self::Closed(code) => FetchErrorCode::from(*code),
+            _ => FetchErrorCode::InternalError,
+        }
+    }
+
+    /// The code sent in PUBLISH_NAMESPACE_ERROR or PUBLISH_NAMESPACE_CANCEL with this error.
+    pub fn publish_namespace_error_code(&self) -> PublishNamespaceErrorCode {
+        match self {
+            Self::Done | Self::Cancel | Self::NotFound => PublishNamespaceErrorCode::Uninterested,
+            Self::NotSupported(_) | Self::Mode => PublishNamespaceErrorCode::NotSupported,
+            Self::Unauthorized => PublishNamespaceErrorCode::Unauthorized,
+            Self::Timeout => PublishNamespaceErrorCode::Timeout,
+            Self::Closed(code) => PublishNamespaceErrorCode::from(*code),
+            _ => PublishNamespaceErrorCode::InternalError,
+        }
+    }
+
+    /// The code sent in PUBLISH_ERROR when rejecting a PUBLISH with this error.
+    pub fn publish_error_code(&self) -> PublishErrorCode {
+        match self {
+            Self::Done | Self::Cancel | Self::NotFound => PublishErrorCode::Uninterested,
+            Self::NotSupported(_) | Self::Mode => PublishErrorCode::NotSupported,
+            Self::Unauthorized => PublishErrorCode::Unauthorized,
+            Self::Timeout => PublishErrorCode::Timeout,
+            Self::Closed(code) => PublishErrorCode::from(*code),
+            _ => PublishErrorCode::InternalError,
+        }
+    }
+
+    /// The code sent in SUBSCRIBE_NAMESPACE_ERROR when rejecting a SUBSCRIBE_NAMESPACE with this error.
+    pub fn subscribe_namespace_error_code(&self) -> SubscribeNamespaceErrorCode {
+        match self {
+            Self::NotFound => SubscribeNamespaceErrorCode::NamespacePrefixUnknown,
+            Self::Duplicate => SubscribeNamespaceErrorCode::NamespacePrefixOverlap,
+            Self::NotSupported(_) | Self::Mode => SubscribeNamespaceErrorCode::NotSupported,
+            Self::Unauthorized => SubscribeNamespaceErrorCode::Unauthorized,
+            Self::Timeout => SubscribeNamespaceErrorCode::Timeout,
+            Self::Closed(code) => SubscribeNamespaceErrorCode::from(*code),
+            _ => SubscribeNamespaceErrorCode::InternalError,
+        }
+    }
+
+    /// The status sent in PUBLISH_DONE when a subscription ends with this error.
+    pub fn publish_done_status(&self) -> PublishDoneStatus {
+        match self {
+            Self::Done => PublishDoneStatus::TrackEnded,
+            Self::Cancel => PublishDoneStatus::SubscriptionEnded,
+            Self::Unauthorized => PublishDoneStatus::Unauthorized,
+            Self::GoingAway => PublishDoneStatus::GoingAway,
+            Self::Expired => PublishDoneStatus::Expired,
+            Self::Closed(code) => PublishDoneStatus::from(*code),
+            _ => PublishDoneStatus::InternalError,
+        }
+    }
+
+    /// The error for a received SUBSCRIBE_ERROR or TRACK_STATUS_ERROR.
+    pub fn from_subscribe_error(code: SubscribeErrorCode, reason: &str) -> Self {
+        match code {
+            SubscribeErrorCode::TrackDoesNotExist => Self::NotFound,
+            SubscribeErrorCode::InvalidRange => Self::InvalidRange,
+            SubscribeErrorCode::NotSupported => Self::NotSupported(reason.to_string()),
+            SubscribeErrorCode::Unauthorized => Self::Unauthorized,
+            SubscribeErrorCode::Timeout => Self::Timeout,
+            SubscribeErrorCode::InternalError => Self::Internal(reason.to_string()),
+            code => Self::Closed(code.code()),
+        }
+    }
+
+    /// The error for a received FETCH_ERROR.
+    pub fn from_fetch_error(code: FetchErrorCode, reason: &str) -> Self {
+        match code {
+            FetchErrorCode::TrackDoesNotExist | FetchErrorCode::NoObjects => Self::NotFound,
+            FetchErrorCode::InvalidRange => Self::InvalidRange,
+            FetchErrorCode::NotSupported => Self::NotSupported(reason.to_string()),
+            FetchErrorCode::Unauthorized => Self::Unauthorized,
+            FetchErrorCode::Timeout => Self::Timeout,
+            FetchErrorCode::InternalError => Self::Internal(reason.to_string()),
+            code => Self::Closed(code.code()),
+        }
+    }
+
+    /// The error for a received PUBLISH_NAMESPACE_ERROR.
+    pub fn from_publish_namespace_error(code: PublishNamespaceErrorCode, reason: &str) -> Self {
+        match code {
+            PublishNamespaceErrorCode::NotSupported => Self::NotSupported(reason.to_string()),
+            PublishNamespaceErrorCode::Unauthorized => Self::Unauthorized,
+            PublishNamespaceErrorCode::Timeout => Self::Timeout,
+            PublishNamespaceErrorCode::InternalError => Self::Internal(reason.to_string()),
+            code => Self::Closed(code.code()),
+        }
+    }
+
+    /// The error for a received PUBLISH_ERROR.
+    pub fn from_publish_error(code: PublishErrorCode, reason: &str) -> Self {
+        match code {
+            PublishErrorCode::Uninterested => Self::NotFound,
+            PublishErrorCode::NotSupported => Self::NotSupported(reason.to_string()),
+            PublishErrorCode::Unauthorized => Self::Unauthorized,
+            PublishErrorCode::Timeout => Self::Timeout,
+            PublishErrorCode::InternalError => Self::Internal(reason.to_string()),
+            code => Self::Closed(code.code()),
+        }
+    }
+
+    /// The error for a received SUBSCRIBE_NAMESPACE_ERROR.
+    pub fn from_subscribe_namespace_error(code: SubscribeNamespaceErrorCode, reason: &str) -> Self {
+        match code {
+            SubscribeNamespaceErrorCode::NamespacePrefixUnknown => Self::NotFound,
+            SubscribeNamespaceErrorCode::NamespacePrefixOverlap => Self::Duplicate,
+            SubscribeNamespaceErrorCode::NotSupported => Self::NotSupported(reason.to_string()),
+            SubscribeNamespaceErrorCode::Unauthorized => Self::Unauthorized,
+            SubscribeNamespaceErrorCode::Timeout => Self::Timeout,
+            SubscribeNamespaceErrorCode::InternalError => Self::Internal(reason.to_string()),
+            code => Self::Closed(code.code()),
+        }
+    }
+
+    /// The error for a received PUBLISH_DONE.  A track or subscription that ended normally is [ServeError::Done].
+    pub fn from_publish_done(status: PublishDoneStatus, reason: &str) -> Self {
+        match status {
+            PublishDoneStatus::TrackEnded | PublishDoneStatus::SubscriptionEnded => Self::Done,
+            PublishDoneStatus::Unauthorized => Self::Unauthorized,
+            PublishDoneStatus::GoingAway => Self::GoingAway,
+            PublishDoneStatus::Expired => Self::Expired,
+            PublishDoneStatus::InternalError => Self::Internal(reason.to_string()),
+            status => Self::Closed(status.code()),
+        }
+    }
 }
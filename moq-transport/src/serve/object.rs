@@ -8,7 +8,7 @@
 //! You can clone the [Reader] and each will read a copy of of all future chunks. (fanout)
 //!
 //! The fragment is closed with [ServeError::Closed] when all writers or readers are dropped.
-use std::{cmp, collections::BinaryHeap, ops::Deref, sync::Arc};
+use std::{cmp, collections::BinaryHeap, future::Future, ops::Deref, sync::Arc};
 
 use super::{ServeError, Track};
 use crate::watch::State;
@@ -104,6 +104,16 @@ impl ObjectsWriter {
 
         Ok(())
     }
+
+    /// Returns true once every reader has been dropped.
+    pub fn is_unused(&self) -> bool {
+        self.state.is_dropped()
+    }
+
+    /// Returns a future that resolves once every reader has been dropped, or this writer is.
+    pub fn unused(&self) -> impl Future<Output = ()> {
+        self.state.dropped()
+    }
 }
 
 impl Deref for ObjectsWriter {
@@ -1,5 +1,5 @@
 use bytes::Bytes;
-use std::{ops::Deref, sync::Arc};
+use std::{future::Future, ops::Deref, sync::Arc};
 
 use crate::data::ObjectStatus;
 use crate::watch::State;
@@ -121,6 +121,16 @@ impl StreamWriter {
 
         Ok(())
     }
+
+    /// Returns true once every reader has been dropped.
+    pub fn is_unused(&self) -> bool {
+        self.state.is_dropped()
+    }
+
+    /// Returns a future that resolves once every reader has been dropped, or this writer is.
+    pub fn unused(&self) -> impl Future<Output = ()> {
+        self.state.dropped()
+    }
 }
 
 impl Deref for StreamWriter {
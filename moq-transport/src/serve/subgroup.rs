@@ -7,10 +7,11 @@
 //! The reader can be cloned, in which case each reader receives a copy of each object. (fanout)
 //!
 //! The stream is closed with [ServeError::Closed] when all writers or readers are dropped.
-use std::{cmp, ops::Deref, sync::Arc};
+use std::{cmp, collections::BTreeMap, future::Future, ops::Deref, sync::Arc};
 
 use bytes::Bytes;
 
+use crate::coding::KeyValuePairs;
 use crate::data::ObjectStatus;
 use crate::watch::State;
 
@@ -22,7 +23,11 @@ pub struct Subgroups {
 
 impl Subgroups {
     pub fn produce(self) -> (SubgroupsWriter, SubgroupsReader) {
-        let (writer, reader) = State::default().split();
+        let state = SubgroupsState {
+            max_cached_groups: self.track.cached_groups,
+            ..Default::default()
+        };
+        let (writer, reader) = State::new(state).split();
 
         let writer = SubgroupsWriter::new(writer, self.track.clone());
         let reader = SubgroupsReader::new(reader, self.track);
@@ -43,14 +48,35 @@ impl Deref for Subgroups {
 struct SubgroupsState {
     latest_subgroup_reader: Option<SubgroupReader>,
     epoch: u64, // Updated each time latest changes
+
+    // Recent subgroups keyed by group_id then subgroup_id, retained so they can be fetched.
+    cache: BTreeMap<u64, BTreeMap<u64, SubgroupReader>>,
+    max_cached_groups: usize,
+
     closed: Result<(), ServeError>,
 }
 
+impl SubgroupsState {
+    fn insert_cache(&mut self, reader: SubgroupReader) {
+        self.cache
+            .entry(reader.group_id)
+            .or_default()
+            .insert(reader.subgroup_id, reader);
+
+        // Evict the oldest groups once we exceed the limit.
+        while self.cache.len() > self.max_cached_groups {
+            self.cache.pop_first();
+        }
+    }
+}
+
 impl Default for SubgroupsState {
     fn default() -> Self {
         Self {
             latest_subgroup_reader: None,
             epoch: 0,
+            cache: BTreeMap::new(),
+            max_cached_groups: 0,
             closed: Ok(()),
         }
     }
@@ -114,19 +140,27 @@ impl SubgroupsWriter {
             // TODO: Check this logic again
             if writer.group_id.cmp(&latest.group_id) == cmp::Ordering::Equal {
                 match writer.subgroup_id.cmp(&latest.subgroup_id) {
-                    cmp::Ordering::Less => return Ok(writer), // dropped immediately, lul
+                    cmp::Ordering::Less => {
+                        // Not the latest, but keep it around for FETCH.
+                        state.insert_cache(reader);
+                        return Ok(writer);
+                    }
                     cmp::Ordering::Equal => return Err(ServeError::Duplicate),
-                    cmp::Ordering::Greater => state.latest_subgroup_reader = Some(reader),
+                    cmp::Ordering::Greater => state.latest_subgroup_reader = Some(reader.clone()),
                 }
             } else if writer.group_id.cmp(&latest.group_id) == cmp::Ordering::Greater {
-                state.latest_subgroup_reader = Some(reader);
+                state.latest_subgroup_reader = Some(reader.clone());
             } else {
-                return Ok(writer); // drop here as well
+                // Not the latest, but keep it around for FETCH.
+                state.insert_cache(reader);
+                return Ok(writer);
             }
         } else {
-            state.latest_subgroup_reader = Some(reader);
+            state.latest_subgroup_reader = Some(reader.clone());
         }
 
+        state.insert_cache(reader);
+
         self.next_subgroup_id = state.latest_subgroup_reader.as_ref().unwrap().subgroup_id + 1;
         self.next_group_id = state.latest_subgroup_reader.as_ref().unwrap().group_id + 1;
         self.last_group_id = state.latest_subgroup_reader.as_ref().unwrap().group_id;
@@ -135,6 +169,17 @@ impl SubgroupsWriter {
         Ok(writer)
     }
 
+    /// Set the number of most recent groups retained for FETCH.
+    pub fn set_max_cached_groups(&mut self, max: usize) -> Result<(), ServeError> {
+        let mut state = self.state.lock_mut().ok_or(ServeError::Cancel)?;
+        state.max_cached_groups = max;
+        while state.cache.len() > max {
+            state.cache.pop_first();
+        }
+
+        Ok(())
+    }
+
     /// Close the segment with an error.
     pub fn close(self, err: ServeError) -> Result<(), ServeError> {
         let state = self.state.lock();
@@ -145,6 +190,16 @@ impl SubgroupsWriter {
 
         Ok(())
     }
+
+    /// Returns true once every reader has been dropped.
+    pub fn is_unused(&self) -> bool {
+        self.state.is_dropped()
+    }
+
+    /// Returns a future that resolves once every reader has been dropped, or this writer is.
+    pub fn unused(&self) -> impl Future<Output = ()> {
+        self.state.dropped()
+    }
 }
 
 impl Deref for SubgroupsWriter {
@@ -199,6 +254,27 @@ impl SubgroupsReader {
             .as_ref()
             .map(|group| (group.group_id, group.latest()))
     }
+
+    /// Returns the oldest group_id still in the cache.
+    pub fn oldest_cached(&self) -> Option<u64> {
+        let state = self.state.lock();
+        state.cache.keys().next().copied()
+    }
+
+    /// Returns the cached subgroups with a group_id between start and end (inclusive),
+    /// ordered by group_id and then subgroup_id.
+    pub fn cached(&self, start: u64, end: u64) -> Vec<SubgroupReader> {
+        if start > end {
+            return Vec::new();
+        }
+
+        let state = self.state.lock();
+        state
+            .cache
+            .range(start..=end)
+            .flat_map(|(_, subgroups)| subgroups.values().cloned())
+            .collect()
+    }
 }
 
 impl Deref for SubgroupsReader {
@@ -288,6 +364,9 @@ pub struct SubgroupWriter {
 
     // The next object sequence number to use.
     next_object_id: u64,
+
+    // Set once the end of the group or track is written, after which there are no more objects.
+    finished: bool,
 }
 
 impl SubgroupWriter {
@@ -296,12 +375,22 @@ impl SubgroupWriter {
             state,
             info: group,
             next_object_id: 0,
+            finished: false,
         }
     }
 
     /// Create the next object ID with the given payload.
     pub fn write(&mut self, payload: bytes::Bytes) -> Result<(), ServeError> {
-        let mut object = self.create(payload.len())?;
+        self.write_with_extensions(payload, KeyValuePairs::new())
+    }
+
+    /// Create the next object ID with the given payload and extension headers.
+    pub fn write_with_extensions(
+        &mut self,
+        payload: bytes::Bytes,
+        extension_headers: KeyValuePairs,
+    ) -> Result<(), ServeError> {
+        let mut object = self.create_with_extensions(payload.len(), extension_headers)?;
         object.write(payload)?;
         Ok(())
     }
@@ -310,15 +399,107 @@ impl SubgroupWriter {
     ///
     /// BAD STUFF will happen if the size is wrong; this is an advanced feature.
     pub fn create(&mut self, size: usize) -> Result<SubgroupObjectWriter, ServeError> {
+        self.create_with_extensions(size, KeyValuePairs::new())
+    }
+
+    /// Write an object with extension headers over multiple writes.
+    pub fn create_with_extensions(
+        &mut self,
+        size: usize,
+        extension_headers: KeyValuePairs,
+    ) -> Result<SubgroupObjectWriter, ServeError> {
+        self.create_object(
+            self.next_object_id,
+            size,
+            ObjectStatus::NormalObject,
+            extension_headers,
+        )
+    }
+
+    /// Write a status object with the next object ID, such as [ObjectStatus::EndOfGroup] or
+    /// [ObjectStatus::EndOfTrack] to mark that no more objects follow.
+    pub fn write_status(&mut self, status: ObjectStatus) -> Result<(), ServeError> {
+        self.write_status_with_extensions(status, KeyValuePairs::new())
+    }
+
+    /// Write a status object with the next object ID and the given extension headers.
+    pub fn write_status_with_extensions(
+        &mut self,
+        status: ObjectStatus,
+        extension_headers: KeyValuePairs,
+    ) -> Result<(), ServeError> {
+        self.create_object(self.next_object_id, 0, status, extension_headers)?;
+        Ok(())
+    }
+
+    /// Create an object with the given ID and payload, such as when forwarding objects with sparse IDs.
+    /// The ID must be larger than any written before.
+    pub fn write_at(
+        &mut self,
+        object_id: u64,
+        payload: bytes::Bytes,
+        extension_headers: KeyValuePairs,
+    ) -> Result<(), ServeError> {
+        let mut object = self.create_at(object_id, payload.len(), extension_headers)?;
+        object.write(payload)?;
+        Ok(())
+    }
+
+    /// Write an object with the given ID over multiple writes.
+    /// The ID must be larger than any written before.
+    pub fn create_at(
+        &mut self,
+        object_id: u64,
+        size: usize,
+        extension_headers: KeyValuePairs,
+    ) -> Result<SubgroupObjectWriter, ServeError> {
+        self.create_object(
+            object_id,
+            size,
+            ObjectStatus::NormalObject,
+            extension_headers,
+        )
+    }
+
+    /// Write a status object with the given ID, such as an [ObjectStatus::EndOfGroup] placed after a gap.
+    /// The ID must be larger than any written before.
+    pub fn write_status_at(
+        &mut self,
+        object_id: u64,
+        status: ObjectStatus,
+        extension_headers: KeyValuePairs,
+    ) -> Result<(), ServeError> {
+        self.create_object(object_id, 0, status, extension_headers)?;
+        Ok(())
+    }
+
+    fn create_object(
+        &mut self,
+        object_id: u64,
+        size: usize,
+        status: ObjectStatus,
+        extension_headers: KeyValuePairs,
+    ) -> Result<SubgroupObjectWriter, ServeError> {
+        if self.finished {
+            return Err(ServeError::Done);
+        }
+
+        // Object IDs within a subgroup always increase.
+        if object_id < self.next_object_id {
+            return Err(ServeError::Duplicate);
+        }
+
         let (writer, reader) = SubgroupObject {
             group: self.info.clone(),
-            object_id: self.next_object_id,
-            status: ObjectStatus::NormalObject,
+            object_id,
+            status,
             size,
+            extension_headers,
         }
         .produce();
 
-        self.next_object_id += 1;
+        self.next_object_id = object_id + 1;
+        self.finished = matches!(status, ObjectStatus::EndOfGroup | ObjectStatus::EndOfTrack);
 
         let mut state = self.state.lock_mut().ok_or(ServeError::Cancel)?;
         state.objects.push(reader);
@@ -385,12 +566,16 @@ impl SubgroupReader {
             .unwrap_or_default()
     }
 
+    /// Read the payload of the next normal object, skipping status objects.
+    /// Use [Self::next] to see the status objects, such as the end of the group or track.
     pub async fn read_next(&mut self) -> Result<Option<Bytes>, ServeError> {
-        let object = self.next().await?;
-        match object {
-            Some(mut object) => Ok(Some(object.read_all().await?)),
-            None => Ok(None),
+        while let Some(mut object) = self.next().await? {
+            if object.status == ObjectStatus::NormalObject {
+                return Ok(Some(object.read_all().await?));
+            }
         }
+
+        Ok(None)
     }
 
     pub async fn next(&mut self) -> Result<Option<SubgroupObjectReader>, ServeError> {
@@ -447,6 +632,9 @@ pub struct SubgroupObject {
 
     // Object status
     pub status: ObjectStatus,
+
+    // Extension headers, such as capture timestamps, forwarded untouched by relays.
+    pub extension_headers: KeyValuePairs,
 }
 
 impl SubgroupObject {
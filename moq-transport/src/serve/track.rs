@@ -19,19 +19,56 @@ use super::{
     StreamWriter, Subgroups, SubgroupsReader, SubgroupsWriter,
 };
 use crate::coding::{Location, TrackNamespace};
+use crate::message::GroupOrder;
+use futures::{future::BoxFuture, FutureExt};
 use paste::paste;
-use std::{ops::Deref, sync::Arc};
+use std::{future::Future, ops::Deref, sync::Arc, time::Duration};
 
 /// Static information about a track.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Track {
     pub namespace: TrackNamespace,
     pub name: String,
+
+    /// The publisher's preferred group order, used when a subscriber defers to it.
+    pub group_order: GroupOrder,
+
+    /// How long a subscription lasts unless it's refreshed with a SUBSCRIBE_UPDATE, or None if it doesn't expire.
+    pub expires: Option<Duration>,
+
+    /// The number of most recent groups retained so they can be served to FETCH, or 0 to disable the cache.
+    pub cached_groups: usize,
 }
 
 impl Track {
     pub fn new(namespace: TrackNamespace, name: String) -> Self {
-        Self { namespace, name }
+        Self {
+            namespace,
+            name,
+            group_order: GroupOrder::Ascending,
+            expires: None,
+            cached_groups: 0,
+        }
+    }
+
+    /// Set the publisher's preferred group order.
+    pub fn with_group_order(mut self, group_order: GroupOrder) -> Self {
+        self.group_order = group_order;
+        self
+    }
+
+    /// Set how long subscriptions to the track last, sent to subscribers in SUBSCRIBE_OK.
+    /// A subscription that isn't refreshed with a SUBSCRIBE_UPDATE in time is ended with PUBLISH_DONE.
+    pub fn with_expires(mut self, expires: Duration) -> Self {
+        self.expires = Some(expires);
+        self
+    }
+
+    /// Retain the most recent groups so they can be served to FETCH and joining FETCH requests.
+    /// Nothing is cached by default, as the cache holds on to every object in those groups.
+    pub fn with_cached_groups(mut self, groups: usize) -> Self {
+        self.cached_groups = groups;
+        self
     }
 
     pub fn produce(self) -> (TrackWriter, TrackReader) {
@@ -131,6 +168,18 @@ impl TrackWriter {
         state.closed = Err(err);
         Ok(())
     }
+
+    /// Returns true once every [TrackReader] has been dropped.
+    pub fn is_unused(&self) -> bool {
+        self.state.is_dropped()
+    }
+
+    /// Returns a future that resolves once every [TrackReader] has been dropped, so nobody will read the track.
+    /// It also resolves once this writer is dropped, including when it's turned into a mode writer, which
+    /// has its own [TrackWriterMode::unused].
+    pub fn unused(&self) -> impl Future<Output = ()> {
+        self.state.dropped()
+    }
 }
 
 impl Deref for TrackWriter {
@@ -174,9 +223,9 @@ impl TrackReader {
 
     // Returns the largest group/sequence
     pub fn largest_location(&self) -> Option<Location> {
-        // We don't even know the mode yet.
-        // TODO populate from SUBSCRIBE_OK
-        None
+        let state = self.state.lock();
+        let (group_id, object_id) = state.reader_mode.as_ref()?.latest()?;
+        Some(Location::new(group_id, object_id))
     }
 
     /// Wait until the track is closed, returning the closing error.
@@ -250,6 +299,20 @@ macro_rules! track_writers {
 						$(Self::$name(writer) => writer.close(err),)*
 					}
 				}
+
+				/// Returns true once every reader of the track has been dropped.
+				pub fn is_unused(&self) -> bool {
+					match self {
+						$(Self::$name(writer) => writer.is_unused(),)*
+					}
+				}
+
+				/// Returns a future that resolves once every reader of the track has been dropped, or this writer is.
+				pub fn unused(&self) -> BoxFuture<'static, ()> {
+					match self {
+						$(Self::$name(writer) => writer.unused().boxed(),)*
+					}
+				}
 			}
 		}
 	}
@@ -20,11 +20,23 @@ use crate::watch::{Queue, State};
 #[derive(Debug)]
 pub struct Tracks {
     pub namespace: TrackNamespace,
+
+    /// The number of most recent groups each track retains for FETCH, see [Track::with_cached_groups].
+    pub cached_groups: usize,
 }
 
 impl Tracks {
     pub fn new(namespace: TrackNamespace) -> Self {
-        Self { namespace }
+        Self {
+            namespace,
+            cached_groups: 0,
+        }
+    }
+
+    /// Retain the most recent groups of each track so they can be fetched.
+    pub fn with_cached_groups(mut self, groups: usize) -> Self {
+        self.cached_groups = groups;
+        self
     }
 
     pub fn produce(self) -> (TracksWriter, TracksRequest, TracksReader) {
@@ -59,11 +71,9 @@ impl TracksWriter {
     /// Create a new track with the given name, inserting it into the broadcast.
     /// None is returned if all [TracksReader]s have been dropped.
     pub fn create(&mut self, track: &str) -> Option<TrackWriter> {
-        let (writer, reader) = Track {
-            namespace: self.namespace.clone(),
-            name: track.to_owned(),
-        }
-        .produce();
+        let (writer, reader) = Track::new(self.namespace.clone(), track.to_owned())
+            .with_cached_groups(self.cached_groups)
+            .produce();
 
         // NOTE: We overwrite the track if it already exists.
         self.state
@@ -163,11 +173,9 @@ impl TracksReader {
         }
 
         let mut state = state.into_mut()?;
-        let track_writer_reader = Track {
-            namespace: self.namespace.clone(),
-            name: track_name.to_owned(),
-        }
-        .produce();
+        let track_writer_reader = Track::new(self.namespace.clone(), track_name.to_owned())
+            .with_cached_groups(self.cached_groups)
+            .produce();
 
         if self.queue.push(track_writer_reader.0).is_err() {
             return None;
@@ -4,7 +4,7 @@ use crate::coding::TrackNamespace;
 use crate::watch::State;
 use crate::{message, serve::ServeError};
 
-use super::{Publisher, Subscribed, TrackStatusRequested};
+use super::{Fetched, Publisher, Subscribed, TrackStatusRequested};
 
 #[derive(Debug, Clone)]
 pub struct AnnounceInfo {
@@ -14,6 +14,7 @@ pub struct AnnounceInfo {
 
 struct AnnounceState {
     subscribers: VecDeque<Subscribed>,
+    fetches: VecDeque<Fetched>,
     track_statuses_requested: VecDeque<TrackStatusRequested>,
     ok: bool,
     closed: Result<(), ServeError>,
@@ -23,6 +24,7 @@ impl Default for AnnounceState {
     fn default() -> Self {
         Self {
             subscribers: Default::default(),
+            fetches: Default::default(),
             track_statuses_requested: Default::default(),
             ok: false,
             closed: Ok(()),
@@ -35,6 +37,10 @@ impl Drop for AnnounceState {
         for subscriber in self.subscribers.drain(..) {
             subscriber.close(ServeError::NotFound).ok();
         }
+
+        for fetched in self.fetches.drain(..) {
+            fetched.close(ServeError::NotFound).ok();
+        }
     }
 }
 
@@ -60,7 +66,7 @@ impl Announce {
         publisher.send_message(message::PublishNamespace {
             id: request_id,
             track_namespace: namespace.clone(),
-            params: Default::default(),
+            params: publisher.request_params(),
         });
 
         let (send, recv) = State::default().split();
@@ -115,6 +121,27 @@ impl Announce {
         }
     }
 
+    /// Wait until a fetch is received
+    pub async fn fetched(&self) -> Result<Option<Fetched>, ServeError> {
+        loop {
+            {
+                let state = self.state.lock();
+                if !state.fetches.is_empty() {
+                    return Ok(state
+                        .into_mut()
+                        .and_then(|mut state| state.fetches.pop_front()));
+                }
+
+                state.closed.clone()?;
+                match state.modified() {
+                    Some(notified) => notified,
+                    None => return Ok(None),
+                }
+            }
+            .await;
+        }
+    }
+
     pub async fn track_status_requested(&self) -> Result<Option<TrackStatusRequested>, ServeError> {
         loop {
             {
@@ -210,6 +237,13 @@ impl AnnounceRecv {
         Ok(())
     }
 
+    pub fn recv_fetch(&mut self, fetched: Fetched) -> Result<(), ServeError> {
+        let mut state = self.state.lock_mut().ok_or(ServeError::Done)?;
+        state.fetches.push_back(fetched);
+
+        Ok(())
+    }
+
     pub fn recv_track_status_requested(
         &mut self,
         track_status_requested: TrackStatusRequested,
@@ -90,17 +90,16 @@ impl Drop for Announced {
     fn drop(&mut self) {
         let err = self.error.clone().unwrap_or(ServeError::Done);
 
-        // TODO SLG - ServeError's do not align with draft-13 Announce error codes (section 8.25)
         if self.ok {
             self.session.send_message(message::PublishNamespaceCancel {
                 track_namespace: self.namespace.clone(),
-                error_code: err.code(),
+                error_code: err.publish_namespace_error_code().code(),
                 reason_phrase: ReasonPhrase(err.to_string()),
             });
         } else {
             self.session.send_message(message::PublishNamespaceError {
                 id: self.info.request_id,
-                error_code: err.code(),
+                error_code: err.publish_namespace_error_code().code(),
                 reason_phrase: ReasonPhrase(err.to_string()),
             });
         }
@@ -0,0 +1,394 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::coding::{AuthToken, AuthTokenParam, Decode, Encode, KeyValuePairs, ReasonPhrase};
+use crate::message::{self, Message};
+use crate::serve::ServeError;
+use crate::watch::Queue;
+
+use super::SessionError;
+
+/// Approves or rejects requests from the peer, before they're surfaced to the application as
+/// [super::Subscribed], [super::Announced], etc.
+pub trait Authorizer: Send + Sync {
+    /// Check the AUTHORIZATION_TOKEN the client sent in CLIENT_SETUP, if any.
+    /// An error closes the session as unauthorized.
+    fn authorize_setup(&self, token: Option<&AuthToken>) -> Result<(), ServeError> {
+        let _ = token;
+        Ok(())
+    }
+
+    /// Check a SUBSCRIBE, FETCH, TRACK_STATUS, PUBLISH_NAMESPACE, SUBSCRIBE_NAMESPACE or PUBLISH, along with its
+    /// AUTHORIZATION_TOKEN if any.  An error is sent back in the request's error response, usually
+    /// [ServeError::Unauthorized], or [ServeError::Closed] with a specific code such as EXPIRED_AUTH_TOKEN.
+    fn authorize(&self, request: &Message, token: Option<&AuthToken>) -> Result<(), ServeError>;
+}
+
+/// The tokens registered by one side of the session, bounded by the receiver's MAX_AUTH_TOKEN_CACHE_SIZE.
+/// We keep one for the tokens the peer registers with us, and one mirroring the tokens we registered with it.
+#[derive(Clone, Debug, Default)]
+pub struct AuthTokenCache {
+    max_size: usize,
+    size: usize,
+    tokens: HashMap<u64, AuthToken>,
+    next_alias: u64,
+}
+
+impl AuthTokenCache {
+    pub fn new(max_size: u64) -> Self {
+        Self {
+            max_size: max_size.try_into().unwrap_or(usize::MAX),
+            ..Default::default()
+        }
+    }
+
+    /// The total size of the registered tokens.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn get(&self, alias: u64) -> Option<&AuthToken> {
+        self.tokens.get(&alias)
+    }
+
+    /// Register a token with the alias, failing if the alias is in use or the token doesn't fit.
+    pub fn register(&mut self, alias: u64, token: AuthToken) -> Result<(), SessionError> {
+        if self.tokens.contains_key(&alias) {
+            return Err(SessionError::DuplicateAuthTokenAlias(alias));
+        }
+
+        let size = token.size();
+        if self.size + size > self.max_size {
+            return Err(SessionError::AuthTokenCacheOverflow(
+                self.size + size,
+                self.max_size,
+            ));
+        }
+
+        self.size += size;
+        self.tokens.insert(alias, token);
+        Ok(())
+    }
+
+    /// Delete the token registered with the alias, freeing its space.
+    pub fn delete(&mut self, alias: u64) -> Result<AuthToken, SessionError> {
+        let token = self
+            .tokens
+            .remove(&alias)
+            .ok_or(SessionError::UnknownAuthTokenAlias(alias))?;
+        self.size -= token.size();
+        Ok(token)
+    }
+
+    /// Apply a received AUTHORIZATION_TOKEN, returning the token it refers to, if any.
+    pub fn recv(&mut self, param: AuthTokenParam) -> Result<Option<AuthToken>, SessionError> {
+        match param {
+            AuthTokenParam::Delete(alias) => {
+                self.delete(alias)?;
+                Ok(None)
+            }
+            AuthTokenParam::Register(alias, token) => {
+                self.register(alias, token.clone())?;
+                Ok(Some(token))
+            }
+            AuthTokenParam::UseAlias(alias) => self
+                .get(alias)
+                .cloned()
+                .map(Some)
+                .ok_or(SessionError::UnknownAuthTokenAlias(alias)),
+            AuthTokenParam::UseValue(token) => Ok(Some(token)),
+        }
+    }
+
+    /// Choose how to send a token: by alias if it's already registered, registering it if there's room, and by
+    /// value otherwise.
+    pub fn send(&mut self, token: &AuthToken) -> AuthTokenParam {
+        if let Some((alias, _)) = self.tokens.iter().find(|(_, t)| *t == token) {
+            return AuthTokenParam::UseAlias(*alias);
+        }
+
+        let alias = self.next_alias;
+        match self.register(alias, token.clone()) {
+            Ok(()) => {
+                self.next_alias += 1;
+                AuthTokenParam::Register(alias, token.clone())
+            }
+            Err(_) => AuthTokenParam::UseValue(token.clone()),
+        }
+    }
+}
+
+/// Read the AUTHORIZATION_TOKEN parameter, if any.
+pub(super) fn decode_auth_token(
+    params: &KeyValuePairs,
+    key: u64,
+) -> Result<Option<AuthTokenParam>, SessionError> {
+    let mut value = match params.get_bytesvalue(key) {
+        Some(value) => value,
+        None => return Ok(None),
+    };
+
+    AuthTokenParam::decode(&mut value)
+        .map(Some)
+        .map_err(|_| SessionError::MalformedAuthToken)
+}
+
+/// Set the AUTHORIZATION_TOKEN parameter.
+pub(super) fn encode_auth_token(params: &mut KeyValuePairs, key: u64, param: &AuthTokenParam) {
+    let mut value = Vec::new();
+    // Writing to a Vec can't run out of space.
+    param.encode(&mut value).unwrap();
+    params.set_bytesvalue(key, value);
+}
+
+/// The token attached to our requests, shared by the publisher and subscriber of a session.
+/// It's registered with the peer if its MAX_AUTH_TOKEN_CACHE_SIZE has room, so later requests only send the alias.
+#[derive(Clone)]
+pub(super) struct RequestToken {
+    state: Arc<Mutex<(Option<AuthToken>, AuthTokenCache)>>,
+}
+
+impl RequestToken {
+    pub fn new(peer_cache_size: u64) -> Self {
+        Self {
+            state: Arc::new(Mutex::new((None, AuthTokenCache::new(peer_cache_size)))),
+        }
+    }
+
+    pub fn get(&self) -> Option<AuthToken> {
+        self.state.lock().unwrap().0.clone()
+    }
+
+    pub fn set(&self, token: Option<AuthToken>) {
+        self.state.lock().unwrap().0 = token;
+    }
+
+    /// The parameters for a new request, carrying the token if one is set.
+    pub fn params(&self) -> KeyValuePairs {
+        let mut params = KeyValuePairs::new();
+
+        let mut state = self.state.lock().unwrap();
+        let (token, cache) = &mut *state;
+        if let Some(token) = token {
+            let param = cache.send(token);
+            encode_auth_token(
+                &mut params,
+                message::ParameterType::AuthorizationToken.into(),
+                &param,
+            );
+        }
+
+        params
+    }
+}
+
+/// Resolves the tokens on the peer's requests against the ones it registered, and asks the [Authorizer] to
+/// approve each request before it's handled.
+pub(super) struct RequestAuth {
+    cache: AuthTokenCache,
+    authorizer: Option<Arc<dyn Authorizer>>,
+    outgoing: Queue<Message>,
+}
+
+impl RequestAuth {
+    pub fn new(
+        cache: AuthTokenCache,
+        authorizer: Option<Arc<dyn Authorizer>>,
+        outgoing: Queue<Message>,
+    ) -> Self {
+        Self {
+            cache,
+            authorizer,
+            outgoing,
+        }
+    }
+
+    /// Returns false if the request was rejected, after sending the error response.
+    /// Errors with the peer's token cache close the session.
+    pub fn recv(&mut self, msg: &Message) -> Result<bool, SessionError> {
+        let params = match msg.params() {
+            Some(params) => params,
+            None => return Ok(true),
+        };
+
+        let token =
+            match decode_auth_token(params, message::ParameterType::AuthorizationToken.into())? {
+                Some(param) => self.cache.recv(param)?,
+                None => None,
+            };
+
+        // SUBSCRIBE_UPDATE only changes an existing subscription, and has no response to reject it with.
+        if let Message::SubscribeUpdate(_) = msg {
+            return Ok(true);
+        }
+
+        let authorizer = match &self.authorizer {
+            Some(authorizer) => authorizer,
+            None => return Ok(true),
+        };
+
+        let err = match authorizer.authorize(msg, token.as_ref()) {
+            Ok(()) => return Ok(true),
+            Err(err) => err,
+        };
+
+        log::info!("rejected request: {:?}: {}", msg, err);
+
+        let reason_phrase = ReasonPhrase(err.to_string());
+        let response: Message = match msg {
+            Message::Subscribe(msg) => message::SubscribeError {
+                id: msg.id,
+                error_code: err.subscribe_error_code().code(),
+                reason_phrase,
+            }
+            .into(),
+            Message::TrackStatus(msg) => message::TrackStatusError {
+                id: msg.id,
+                error_code: err.subscribe_error_code().code(),
+                reason_phrase,
+            }
+            .into(),
+            Message::Fetch(msg) => message::FetchError {
+                id: msg.id,
+                error_code: err.fetch_error_code().code(),
+                reason_phrase,
+            }
+            .into(),
+            Message::PublishNamespace(msg) => message::PublishNamespaceError {
+                id: msg.id,
+                error_code: err.publish_namespace_error_code().code(),
+                reason_phrase,
+            }
+            .into(),
+            Message::SubscribeNamespace(msg) => message::SubscribeNamespaceError {
+                id: msg.id,
+                error_code: err.subscribe_namespace_error_code().code(),
+                reason_phrase,
+            }
+            .into(),
+            Message::Publish(msg) => message::PublishError {
+                id: msg.id,
+                error_code: err.publish_error_code().code(),
+                reason_phrase,
+            }
+            .into(),
+            _ => return Ok(true),
+        };
+
+        // TODO report dropped messages?
+        let _ = self.outgoing.push(response);
+
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coding::TrackNamespace;
+    use crate::message::{PublishErrorCode, SubscribeNamespaceErrorCode};
+
+    #[test]
+    fn cache_register_delete() {
+        let token = AuthToken::new(1, b"secret".to_vec());
+        let mut cache = AuthTokenCache::new(10);
+
+        cache.register(1, token.clone()).unwrap();
+        assert_eq!(cache.size(), 7);
+        assert!(matches!(
+            cache.register(1, token.clone()),
+            Err(SessionError::DuplicateAuthTokenAlias(1))
+        ));
+        assert!(matches!(
+            cache.register(2, token.clone()),
+            Err(SessionError::AuthTokenCacheOverflow(14, 10))
+        ));
+
+        assert_eq!(
+            cache.recv(AuthTokenParam::UseAlias(1)).unwrap(),
+            Some(token.clone())
+        );
+        assert_eq!(cache.recv(AuthTokenParam::Delete(1)).unwrap(), None);
+        assert_eq!(cache.size(), 0);
+        assert!(matches!(
+            cache.recv(AuthTokenParam::UseAlias(1)),
+            Err(SessionError::UnknownAuthTokenAlias(1))
+        ));
+    }
+
+    #[test]
+    fn cache_send() {
+        let token = AuthToken::new(1, b"secret".to_vec());
+        let other = AuthToken::new(1, b"other".to_vec());
+        let mut cache = AuthTokenCache::new(10);
+
+        assert_eq!(
+            cache.send(&token),
+            AuthTokenParam::Register(0, token.clone())
+        );
+        assert_eq!(cache.send(&token), AuthTokenParam::UseAlias(0));
+
+        // No room left, so it's sent by value.
+        assert_eq!(cache.send(&other), AuthTokenParam::UseValue(other.clone()));
+
+        // Without a cache, every token is sent by value.
+        let mut cache = AuthTokenCache::new(0);
+        assert_eq!(cache.send(&token), AuthTokenParam::UseValue(token.clone()));
+    }
+
+    /// Rejects every request with the same error.
+    struct Reject(ServeError);
+
+    impl Authorizer for Reject {
+        fn authorize(&self, _: &Message, _: Option<&AuthToken>) -> Result<(), ServeError> {
+            Err(self.0.clone())
+        }
+    }
+
+    /// Reject the request, returning the error response that was sent.
+    fn reject(msg: Message, err: ServeError) -> Message {
+        let outgoing = Queue::default();
+        let mut auth = RequestAuth::new(
+            AuthTokenCache::new(0),
+            Some(Arc::new(Reject(err))),
+            outgoing.clone(),
+        );
+
+        assert!(!auth.recv(&msg).unwrap());
+        outgoing.close().pop().expect("no response")
+    }
+
+    #[test]
+    fn reject_with_message_codes() {
+        let msg = message::SubscribeNamespace {
+            id: 1,
+            track_namespace_prefix: TrackNamespace::from_utf8_path("live"),
+            params: Default::default(),
+        };
+        match reject(msg.into(), ServeError::Duplicate) {
+            Message::SubscribeNamespaceError(err) => assert_eq!(
+                err.error_code,
+                SubscribeNamespaceErrorCode::NamespacePrefixOverlap.code()
+            ),
+            msg => panic!("unexpected response: {:?}", msg),
+        }
+
+        let msg = message::Publish {
+            id: 3,
+            track_namespace: TrackNamespace::from_utf8_path("live"),
+            track_name: "video".to_string(),
+            track_alias: 0,
+            group_order: message::GroupOrder::Ascending,
+            content_exists: false,
+            largest_location: None,
+            forward: true,
+            params: Default::default(),
+        };
+        match reject(msg.into(), ServeError::Cancel) {
+            Message::PublishError(err) => {
+                assert_eq!(err.error_code, PublishErrorCode::Uninterested.code())
+            }
+            msg => panic!("unexpected response: {:?}", msg),
+        }
+    }
+}
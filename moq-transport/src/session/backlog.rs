@@ -0,0 +1,246 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use crate::watch::State;
+
+/// What to do with SUBSCRIBE and TRACK_STATUS requests for namespaces we haven't announced, which wait for
+/// [super::Publisher::subscribed] and [super::Publisher::track_status_requested].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnknownRequestPolicy {
+    /// Reply "not found" straight away, for applications that don't read them.
+    NotFound,
+
+    /// Queue up to `max` requests of each kind, replying "not found" once full.  Requests that the
+    /// application hasn't read within `timeout` are answered with a timeout error.
+    Backlog { max: usize, timeout: Duration },
+}
+
+impl Default for UnknownRequestPolicy {
+    fn default() -> Self {
+        Self::Backlog {
+            max: 64,
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+/// How requests for namespaces we haven't announced were handled, per the [UnknownRequestPolicy].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct UnknownRequestMetrics {
+    /// Waiting for the application to read them.
+    pub pending: usize,
+
+    /// Read by the application.
+    pub read: u64,
+
+    /// Answered "not found", by the policy or because the backlog was full.
+    pub rejected: u64,
+
+    /// Answered with a timeout error, because the application didn't read them in time.
+    pub timed_out: u64,
+}
+
+struct BacklogState<T> {
+    /// The requests, along with when they were received.
+    queue: VecDeque<(Instant, T)>,
+    metrics: UnknownRequestMetrics,
+}
+
+impl<T> Default for BacklogState<T> {
+    fn default() -> Self {
+        Self {
+            queue: VecDeque::new(),
+            metrics: Default::default(),
+        }
+    }
+}
+
+/// Requests for namespaces we haven't announced, waiting for the application to read them.
+/// Unlike a [crate::watch::Queue], it's bounded by the [UnknownRequestPolicy], so a peer can't pile up
+/// requests that nobody reads.
+pub(super) struct Backlog<T> {
+    state: State<BacklogState<T>>,
+    policy: UnknownRequestPolicy,
+}
+
+impl<T> Backlog<T> {
+    pub fn new(policy: UnknownRequestPolicy) -> Self {
+        Self {
+            state: Default::default(),
+            policy,
+        }
+    }
+
+    /// Queue a request for the application, or return it if it should be rejected.
+    pub fn push(&mut self, item: T) -> Result<(), T> {
+        let max = match self.policy {
+            UnknownRequestPolicy::NotFound => 0,
+            UnknownRequestPolicy::Backlog { max, .. } => max,
+        };
+
+        let mut state = match self.state.lock_mut() {
+            Some(state) => state,
+            None => return Err(item),
+        };
+
+        if state.queue.len() >= max {
+            state.metrics.rejected += 1;
+            return Err(item);
+        }
+
+        state.queue.push_back((Instant::now(), item));
+        state.metrics.pending = state.queue.len();
+
+        Ok(())
+    }
+
+    /// Pop the oldest request, waiting if necessary.
+    pub async fn pop(&mut self) -> Option<T> {
+        loop {
+            let notify = {
+                let state = self.state.lock();
+                if !state.queue.is_empty() {
+                    let mut state = state.into_mut()?;
+                    let (_, item) = state.queue.pop_front()?;
+                    state.metrics.read += 1;
+                    state.metrics.pending = state.queue.len();
+                    return Some(item);
+                }
+
+                state.modified()?
+            };
+
+            notify.await;
+        }
+    }
+
+    /// Wait until requests have gone unread for longer than the timeout, and remove them.
+    /// Never resolves if the policy doesn't queue requests.
+    pub async fn timed_out(&mut self) -> Vec<T> {
+        let timeout = match self.policy {
+            UnknownRequestPolicy::NotFound => return std::future::pending().await,
+            UnknownRequestPolicy::Backlog { timeout, .. } => timeout,
+        };
+
+        loop {
+            let (oldest, changed) = {
+                let state = self.state.lock();
+                (state.queue.front().map(|(at, _)| *at), state.modified())
+            };
+
+            match (oldest, changed) {
+                (Some(oldest), Some(changed)) => tokio::select! {
+                    _ = tokio::time::sleep_until(oldest + timeout) => {},
+                    _ = changed => continue,
+                },
+                (None, Some(changed)) => {
+                    changed.await;
+                    continue;
+                }
+                (_, None) => return std::future::pending().await,
+            }
+
+            let expired = self.remove_timed_out(Instant::now());
+            if !expired.is_empty() {
+                return expired;
+            }
+        }
+    }
+
+    /// Remove the requests that were received more than the timeout before now.
+    fn remove_timed_out(&mut self, now: Instant) -> Vec<T> {
+        let timeout = match self.policy {
+            UnknownRequestPolicy::NotFound => return Vec::new(),
+            UnknownRequestPolicy::Backlog { timeout, .. } => timeout,
+        };
+
+        let mut state = match self.state.lock_mut() {
+            Some(state) => state,
+            None => return Vec::new(),
+        };
+
+        let mut expired = Vec::new();
+        while state
+            .queue
+            .front()
+            .is_some_and(|(at, _)| *at + timeout <= now)
+        {
+            if let Some((_, item)) = state.queue.pop_front() {
+                expired.push(item);
+            }
+        }
+
+        state.metrics.timed_out += expired.len() as u64;
+        state.metrics.pending = state.queue.len();
+
+        expired
+    }
+
+    pub fn metrics(&self) -> UnknownRequestMetrics {
+        self.state.lock().metrics
+    }
+}
+
+impl<T> Clone for Backlog<T> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            policy: self.policy,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn not_found() {
+        let mut backlog = Backlog::new(UnknownRequestPolicy::NotFound);
+        assert_eq!(backlog.push(1), Err(1));
+        assert_eq!(backlog.metrics().rejected, 1);
+        assert_eq!(backlog.metrics().pending, 0);
+    }
+
+    #[test]
+    fn bounded() {
+        let mut backlog = Backlog::new(UnknownRequestPolicy::Backlog {
+            max: 2,
+            timeout: Duration::from_secs(10),
+        });
+
+        assert_eq!(backlog.push(1), Ok(()));
+        assert_eq!(backlog.push(2), Ok(()));
+        assert_eq!(backlog.push(3), Err(3));
+        assert_eq!(futures::executor::block_on(backlog.pop()), Some(1));
+        assert_eq!(backlog.push(4), Ok(()));
+
+        assert_eq!(
+            backlog.metrics(),
+            UnknownRequestMetrics {
+                pending: 2,
+                read: 1,
+                rejected: 1,
+                timed_out: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn timed_out() {
+        let timeout = Duration::from_secs(10);
+        let mut backlog = Backlog::new(UnknownRequestPolicy::Backlog { max: 2, timeout });
+
+        backlog.push(1).unwrap();
+        let now = Instant::now();
+        assert!(backlog.remove_timed_out(now).is_empty());
+
+        std::thread::sleep(Duration::from_millis(1));
+        backlog.push(2).unwrap();
+        assert_eq!(backlog.remove_timed_out(now + timeout), vec![1]);
+        assert_eq!(backlog.metrics().timed_out, 1);
+        assert_eq!(backlog.metrics().pending, 1);
+    }
+}
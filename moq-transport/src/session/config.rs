@@ -0,0 +1,227 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::coding::{AuthToken, AuthTokenParam, KeyValuePairs};
+use crate::setup;
+
+use super::auth::encode_auth_token;
+use super::{Authorizer, SessionError, UnknownRequestPolicy};
+
+/// The MAX_REQUEST_ID we advertise in SETUP by default, and the number of request IDs granted to the peer at a time.
+pub const DEFAULT_MAX_REQUEST_ID: u64 = 100;
+
+/// Configuration for the SETUP handshake of a [super::Session], built from the defaults with the `with_` methods.
+#[derive(Clone)]
+pub struct SessionConfig {
+    /// The versions offered by a client, or accepted by a server, defaulting to every supported version.
+    /// The server picks the largest version both sides support.  Every version must be in [setup::Version::SUPPORTED].
+    pub versions: setup::Versions,
+
+    /// The MAX_REQUEST_ID advertised to the peer, which is also how many request IDs are granted at a time.
+    pub max_request_id: u64,
+
+    /// Sent as the MOQT_IMPLEMENTATION parameter.
+    pub implementation: Option<String>,
+
+    /// Sent by clients as the PATH parameter, when connecting over raw QUIC.
+    pub path: Option<String>,
+
+    /// Sent by clients as the AUTHORITY parameter, when connecting over raw QUIC.
+    pub authority: Option<String>,
+
+    /// Sent by clients as the AUTHORIZATION_TOKEN parameter.
+    /// Use [super::Subscriber::set_auth_token] for the token attached to requests.
+    pub auth_token: Option<AuthToken>,
+
+    /// Sent as the MAX_AUTH_TOKEN_CACHE_SIZE parameter, the total size of the tokens the peer can register
+    /// with us.  The default of 0 means every token must be sent by value.
+    pub max_auth_token_cache_size: u64,
+
+    /// Approves or rejects the SETUP and requests from the peer.  Everything is allowed by default.
+    pub authorizer: Option<Arc<dyn Authorizer>>,
+
+    /// Write an mlog of the session's events to this path.
+    pub mlog_path: Option<PathBuf>,
+
+    /// What to do with SUBSCRIBE and TRACK_STATUS requests for namespaces we haven't announced.
+    /// By default a bounded number wait for the application, which must read them in time.
+    pub unknown_requests: UnknownRequestPolicy,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            versions: setup::Version::SUPPORTED.to_vec().into(),
+            max_request_id: DEFAULT_MAX_REQUEST_ID,
+            implementation: None,
+            path: None,
+            authority: None,
+            auth_token: None,
+            max_auth_token_cache_size: 0,
+            authorizer: None,
+            mlog_path: None,
+            unknown_requests: Default::default(),
+        }
+    }
+}
+
+impl SessionConfig {
+    pub fn with_versions(mut self, versions: setup::Versions) -> Self {
+        self.versions = versions;
+        self
+    }
+
+    pub fn with_max_request_id(mut self, max_request_id: u64) -> Self {
+        self.max_request_id = max_request_id;
+        self
+    }
+
+    pub fn with_implementation(mut self, implementation: &str) -> Self {
+        self.implementation = Some(implementation.to_string());
+        self
+    }
+
+    pub fn with_path(mut self, path: &str) -> Self {
+        self.path = Some(path.to_string());
+        self
+    }
+
+    pub fn with_authority(mut self, authority: &str) -> Self {
+        self.authority = Some(authority.to_string());
+        self
+    }
+
+    pub fn with_auth_token(mut self, auth_token: AuthToken) -> Self {
+        self.auth_token = Some(auth_token);
+        self
+    }
+
+    pub fn with_max_auth_token_cache_size(mut self, size: u64) -> Self {
+        self.max_auth_token_cache_size = size;
+        self
+    }
+
+    pub fn with_authorizer(mut self, authorizer: Arc<dyn Authorizer>) -> Self {
+        self.authorizer = Some(authorizer);
+        self
+    }
+
+    pub fn with_mlog_path(mut self, mlog_path: Option<PathBuf>) -> Self {
+        self.mlog_path = mlog_path;
+        self
+    }
+
+    pub fn with_unknown_requests(mut self, policy: UnknownRequestPolicy) -> Self {
+        self.unknown_requests = policy;
+        self
+    }
+
+    /// The configured versions, which must all have a wire format.
+    pub(super) fn supported_versions(&self) -> Result<setup::Versions, SessionError> {
+        let unsupported: Vec<_> = self
+            .versions
+            .iter()
+            .filter(|version| !version.is_supported())
+            .cloned()
+            .collect();
+
+        if self.versions.is_empty() || !unsupported.is_empty() {
+            return Err(SessionError::UnsupportedVersions(unsupported.into()));
+        }
+
+        Ok(self.versions.clone())
+    }
+
+    /// The parameters sent in our CLIENT_SETUP or SERVER_SETUP.
+    /// PATH, AUTHORITY and AUTHORIZATION_TOKEN are only sent by clients.
+    pub(super) fn setup_params(&self, client: bool) -> KeyValuePairs {
+        let mut params = KeyValuePairs::default();
+        params.set_intvalue(
+            setup::ParameterType::MaxRequestId.into(),
+            self.max_request_id,
+        );
+
+        if let Some(implementation) = &self.implementation {
+            params.set_bytesvalue(
+                setup::ParameterType::MOQTImplementation.into(),
+                implementation.as_bytes().to_vec(),
+            );
+        }
+
+        if self.max_auth_token_cache_size > 0 {
+            params.set_intvalue(
+                setup::ParameterType::MaxAuthTokenCacheSize.into(),
+                self.max_auth_token_cache_size,
+            );
+        }
+
+        if client {
+            // We don't know the size of the server's cache yet, so the token is sent by value.
+            if let Some(auth_token) = &self.auth_token {
+                encode_auth_token(
+                    &mut params,
+                    setup::ParameterType::AuthorizationToken.into(),
+                    &AuthTokenParam::UseValue(auth_token.clone()),
+                );
+            }
+
+            if let Some(path) = &self.path {
+                params.set_bytesvalue(setup::ParameterType::Path.into(), path.as_bytes().to_vec());
+            }
+
+            if let Some(authority) = &self.authority {
+                params.set_bytesvalue(
+                    setup::ParameterType::Authority.into(),
+                    authority.as_bytes().to_vec(),
+                );
+            }
+        }
+
+        params
+    }
+}
+
+impl fmt::Debug for SessionConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SessionConfig")
+            .field("versions", &self.versions)
+            .field("max_request_id", &self.max_request_id)
+            .field("implementation", &self.implementation)
+            .field("path", &self.path)
+            .field("authority", &self.authority)
+            .field("auth_token", &self.auth_token)
+            .field("max_auth_token_cache_size", &self.max_auth_token_cache_size)
+            .field("authorizer", &self.authorizer.is_some())
+            .field("mlog_path", &self.mlog_path)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn supported_versions() {
+        let config = SessionConfig::default();
+        assert_eq!(
+            config.supported_versions().unwrap(),
+            setup::Version::SUPPORTED.to_vec().into()
+        );
+
+        // Versions without a wire format aren't silently dropped.
+        let config =
+            config.with_versions([setup::Version::DRAFT_13, setup::Version::DRAFT_14].into());
+        assert!(matches!(
+            config.supported_versions(),
+            Err(SessionError::UnsupportedVersions(versions)) if versions == [setup::Version::DRAFT_13].into()
+        ));
+
+        let config = config.with_versions(Vec::new().into());
+        assert!(matches!(
+            config.supported_versions(),
+            Err(SessionError::UnsupportedVersions(_))
+        ));
+    }
+}
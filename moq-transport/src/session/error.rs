@@ -3,13 +3,13 @@ use crate::{coding, serve, setup};
 #[derive(thiserror::Error, Debug, Clone)]
 pub enum SessionError {
     #[error("webtransport session: {0}")]
-    Session(#[from] web_transport::SessionError),
+    Session(web_transport::SessionError),
 
     #[error("webtransport write: {0}")]
-    Write(#[from] web_transport::WriteError),
+    Write(web_transport::WriteError),
 
     #[error("webtransport read: {0}")]
-    Read(#[from] web_transport::ReadError),
+    Read(web_transport::ReadError),
 
     #[error("encode error: {0}")]
     Encode(#[from] coding::EncodeError),
@@ -21,6 +21,10 @@ pub enum SessionError {
     #[error("unsupported versions: client={0:?} server={1:?}")]
     Version(setup::Versions, setup::Versions),
 
+    /// The config offered versions without a wire format, or none at all.
+    #[error("unsupported versions configured: {0:?}")]
+    UnsupportedVersions(setup::Versions),
+
     /// TODO SLG - eventually remove or morph into error for incorrect control message for publisher/subscriber
     /// The role negiotiated in the handshake was violated. For example, a publisher sent a SUBSCRIBE, or a subscriber sent an OBJECT.
     #[error("role violation")]
@@ -34,6 +38,14 @@ pub enum SessionError {
     #[error("duplicate")]
     Duplicate,
 
+    /// The peer reused the request ID of an active request.
+    #[error("duplicate request id: {0}")]
+    DuplicateRequestId(u64),
+
+    /// The peer used a request ID other than the next one in its sequence.
+    #[error("invalid request id: id={0} expected={1}")]
+    InvalidRequestId(u64, u64),
+
     #[error("internal error")]
     Internal,
 
@@ -42,26 +54,159 @@ pub enum SessionError {
 
     #[error("wrong size")]
     WrongSize,
+
+    /// The peer used a request ID beyond the MAX_REQUEST_ID we advertised.
+    #[error("too many requests: id={0} max={1}")]
+    TooManyRequests(u64, u64),
+
+    #[error("protocol violation: {0}")]
+    ProtocolViolation(String),
+
+    /// The peer sent an AUTHORIZATION_TOKEN that couldn't be parsed.
+    #[error("malformed auth token")]
+    MalformedAuthToken,
+
+    /// The peer used an auth token alias it never registered.
+    #[error("unknown auth token alias: {0}")]
+    UnknownAuthTokenAlias(u64),
+
+    /// The peer registered an auth token alias that's already in use.
+    #[error("duplicate auth token alias: {0}")]
+    DuplicateAuthTokenAlias(u64),
+
+    /// The peer registered more auth tokens than our MAX_AUTH_TOKEN_CACHE_SIZE allows.
+    #[error("auth token cache overflow: size={0} max={1}")]
+    AuthTokenCacheOverflow(usize, usize),
+
+    /// The peer used a track alias that's already in use by a different track.
+    #[error("duplicate track alias: {0}")]
+    DuplicateTrackAlias(u64),
+
+    /// The client's SETUP was rejected by the [super::Authorizer].
+    #[error("unauthorized: {0}")]
+    Unauthorized(String),
+
+    /// The peer closed the session with a termination code and reason.
+    #[error("closed by peer: {0}: {1}")]
+    Closed(TerminationCode, String),
 }
 
-// TODO SLG - update with errors from moq-transport draft-13 section 3.4 Termination
 impl SessionError {
-    /// An integer code that is sent over the wire.
-    pub fn code(&self) -> u64 {
+    /// The termination code used to close the session because of this error.
+    pub fn termination_code(&self) -> TerminationCode {
         match self {
-            Self::RoleViolation => 405,
-            Self::Session(_) => 503,
-            Self::Read(_) => 500,
-            Self::Write(_) => 500,
-            Self::Version(..) => 406,
-            Self::Decode(_) => 400,
-            Self::Encode(_) => 500,
-            Self::BoundsExceeded(_) => 500,
-            Self::Duplicate => 409,
-            Self::Internal => 500,
-            Self::WrongSize => 400,
-            Self::Serve(err) => err.code(),
+            Self::RoleViolation => TerminationCode::ProtocolViolation,
+            Self::Session(_) => TerminationCode::InternalError,
+            Self::Read(_) => TerminationCode::InternalError,
+            Self::Write(_) => TerminationCode::InternalError,
+            Self::Version(..) => TerminationCode::VersionNegotiationFailed,
+            Self::UnsupportedVersions(_) => TerminationCode::InternalError,
+            Self::Decode(err) => match err {
+                coding::DecodeError::DupliateParameter
+                | coding::DecodeError::MissingParameter
+                | coding::DecodeError::InvalidParameter
+                | coding::DecodeError::KeyValuePairLengthExceeded() => {
+                    TerminationCode::KeyValueFormattingError
+                }
+                _ => TerminationCode::ProtocolViolation,
+            },
+            Self::Encode(_) => TerminationCode::InternalError,
+            Self::BoundsExceeded(_) => TerminationCode::InternalError,
+            Self::Duplicate => TerminationCode::ProtocolViolation,
+            Self::DuplicateRequestId(_) => TerminationCode::InvalidRequestId,
+            Self::InvalidRequestId(..) => TerminationCode::InvalidRequestId,
+            Self::Internal => TerminationCode::InternalError,
+            Self::WrongSize => TerminationCode::ProtocolViolation,
+            Self::TooManyRequests(..) => TerminationCode::TooManyRequests,
+            Self::ProtocolViolation(_) => TerminationCode::ProtocolViolation,
+            Self::MalformedAuthToken => TerminationCode::MalformedAuthToken,
+            Self::UnknownAuthTokenAlias(_) => TerminationCode::UnknownAuthTokenAlias,
+            Self::DuplicateAuthTokenAlias(_) => TerminationCode::DuplicateAuthTokenAlias,
+            Self::AuthTokenCacheOverflow(..) => TerminationCode::AuthTokenCacheOverflow,
+            Self::Unauthorized(_) => TerminationCode::Unauthorized,
+            Self::DuplicateTrackAlias(_) => TerminationCode::DuplicateTrackAlias,
+            Self::Closed(code, _) => *code,
+            Self::Serve(_) => TerminationCode::InternalError,
+        }
+    }
+
+    /// An integer code that is sent over the wire when closing the session.
+    pub fn code(&self) -> u64 {
+        self.termination_code().code()
+    }
+
+    /// Returns the code and reason if the peer closed the session.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn closed_by_peer(err: &web_transport::SessionError) -> Option<Self> {
+        let web_transport::SessionError::ConnectionError(
+            quinn::ConnectionError::ApplicationClosed(close),
+        ) = err
+        else {
+            return None;
+        };
+
+        // WebTransport maps application codes into the HTTP/3 error space, while raw QUIC sends them as is.
+        let code = close.error_code.into_inner();
+        let code = error_from_http3(code).unwrap_or(code);
+        let reason = String::from_utf8_lossy(&close.reason).into_owned();
+
+        Some(Self::Closed(code.into(), reason))
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn closed_by_peer(_err: &web_transport::SessionError) -> Option<Self> {
+        None
+    }
+}
+
+/// The first and last application error codes in the HTTP/3 error space, as used by WebTransport.
+const HTTP3_ERROR_FIRST: u64 = 0x52e4a40fa8db;
+const HTTP3_ERROR_LAST: u64 = 0x52e5ac983162;
+
+/// Map an HTTP/3 error code back to the WebTransport application code, skipping the reserved
+/// codepoints that occur every 0x1f codes.  Returns None for codes outside the range, or reserved ones.
+fn error_from_http3(code: u64) -> Option<u64> {
+    if !(HTTP3_ERROR_FIRST..=HTTP3_ERROR_LAST).contains(&code) {
+        return None;
+    }
+
+    let code = code - HTTP3_ERROR_FIRST;
+    if code % 0x1f == 0x1e {
+        return None;
+    }
+
+    Some(code - code / 0x1f)
+}
+
+impl From<web_transport::SessionError> for SessionError {
+    fn from(err: web_transport::SessionError) -> Self {
+        Self::closed_by_peer(&err).unwrap_or(Self::Session(err))
+    }
+}
+
+impl From<web_transport::ReadError> for SessionError {
+    fn from(err: web_transport::ReadError) -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let web_transport::ReadError::SessionError(session) = &err {
+            if let Some(closed) = Self::closed_by_peer(session) {
+                return closed;
+            }
         }
+
+        Self::Read(err)
+    }
+}
+
+impl From<web_transport::WriteError> for SessionError {
+    fn from(err: web_transport::WriteError) -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let web_transport::WriteError::SessionError(session) = &err {
+            if let Some(closed) = Self::closed_by_peer(session) {
+                return closed;
+            }
+        }
+
+        Self::Write(err)
     }
 }
 
@@ -73,3 +218,201 @@ impl From<SessionError> for serve::ServeError {
         }
     }
 }
+
+/// The codes used to terminate a session, from draft-14 section 3.4 Termination.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminationCode {
+    #[error("no error")]
+    NoError,
+
+    #[error("internal error")]
+    InternalError,
+
+    #[error("unauthorized")]
+    Unauthorized,
+
+    #[error("protocol violation")]
+    ProtocolViolation,
+
+    #[error("invalid request id")]
+    InvalidRequestId,
+
+    #[error("duplicate track alias")]
+    DuplicateTrackAlias,
+
+    #[error("key-value formatting error")]
+    KeyValueFormattingError,
+
+    #[error("too many requests")]
+    TooManyRequests,
+
+    #[error("invalid path")]
+    InvalidPath,
+
+    #[error("malformed path")]
+    MalformedPath,
+
+    #[error("goaway timeout")]
+    GoawayTimeout,
+
+    #[error("control message timeout")]
+    ControlMessageTimeout,
+
+    #[error("data stream timeout")]
+    DataStreamTimeout,
+
+    #[error("auth token cache overflow")]
+    AuthTokenCacheOverflow,
+
+    #[error("duplicate auth token alias")]
+    DuplicateAuthTokenAlias,
+
+    #[error("version negotiation failed")]
+    VersionNegotiationFailed,
+
+    #[error("malformed auth token")]
+    MalformedAuthToken,
+
+    #[error("unknown auth token alias")]
+    UnknownAuthTokenAlias,
+
+    #[error("expired auth token")]
+    ExpiredAuthToken,
+
+    #[error("invalid authority")]
+    InvalidAuthority,
+
+    #[error("malformed authority")]
+    MalformedAuthority,
+
+    #[error("unknown termination code: {0:#x}")]
+    Unknown(u64),
+}
+
+impl TerminationCode {
+    /// An integer code that is sent over the wire.
+    pub fn code(&self) -> u64 {
+        match self {
+            Self::NoError => 0x0,
+            Self::InternalError => 0x1,
+            Self::Unauthorized => 0x2,
+            Self::ProtocolViolation => 0x3,
+            Self::InvalidRequestId => 0x4,
+            Self::DuplicateTrackAlias => 0x5,
+            Self::KeyValueFormattingError => 0x6,
+            Self::TooManyRequests => 0x7,
+            Self::InvalidPath => 0x8,
+            Self::MalformedPath => 0x9,
+            Self::GoawayTimeout => 0x10,
+            Self::ControlMessageTimeout => 0x11,
+            Self::DataStreamTimeout => 0x12,
+            Self::AuthTokenCacheOverflow => 0x13,
+            Self::DuplicateAuthTokenAlias => 0x14,
+            Self::VersionNegotiationFailed => 0x15,
+            Self::MalformedAuthToken => 0x16,
+            Self::UnknownAuthTokenAlias => 0x17,
+            Self::ExpiredAuthToken => 0x18,
+            Self::InvalidAuthority => 0x19,
+            Self::MalformedAuthority => 0x1a,
+            Self::Unknown(code) => *code,
+        }
+    }
+}
+
+impl From<u64> for TerminationCode {
+    fn from(code: u64) -> Self {
+        match code {
+            0x0 => Self::NoError,
+            0x1 => Self::InternalError,
+            0x2 => Self::Unauthorized,
+            0x3 => Self::ProtocolViolation,
+            0x4 => Self::InvalidRequestId,
+            0x5 => Self::DuplicateTrackAlias,
+            0x6 => Self::KeyValueFormattingError,
+            0x7 => Self::TooManyRequests,
+            0x8 => Self::InvalidPath,
+            0x9 => Self::MalformedPath,
+            0x10 => Self::GoawayTimeout,
+            0x11 => Self::ControlMessageTimeout,
+            0x12 => Self::DataStreamTimeout,
+            0x13 => Self::AuthTokenCacheOverflow,
+            0x14 => Self::DuplicateAuthTokenAlias,
+            0x15 => Self::VersionNegotiationFailed,
+            0x16 => Self::MalformedAuthToken,
+            0x17 => Self::UnknownAuthTokenAlias,
+            0x18 => Self::ExpiredAuthToken,
+            0x19 => Self::InvalidAuthority,
+            0x1a => Self::MalformedAuthority,
+            _ => Self::Unknown(code),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn termination_code_round_trip() {
+        for code in (0x0..=0x9).chain(0x10..=0x1a) {
+            let termination = TerminationCode::from(code);
+            assert_ne!(termination, TerminationCode::Unknown(code));
+            assert_eq!(termination.code(), code);
+        }
+
+        assert_eq!(TerminationCode::from(0xa), TerminationCode::Unknown(0xa));
+        assert_eq!(TerminationCode::Unknown(0x1b).code(), 0x1b);
+    }
+
+    #[test]
+    fn http3_error_codes() {
+        // The inverse of the WebTransport mapping, which skips a reserved codepoint every 0x1e codes.
+        let to_http3 = |code: u64| HTTP3_ERROR_FIRST + code + code / 0x1e;
+
+        for code in [0, 1, 0x1d, 0x1e, 0x1f, 0x3c, 0x3d, 0x1000, u32::MAX as u64] {
+            assert_eq!(error_from_http3(to_http3(code)), Some(code));
+        }
+
+        assert_eq!(error_from_http3(HTTP3_ERROR_FIRST + 0x1e), None);
+        assert_eq!(error_from_http3(HTTP3_ERROR_FIRST - 1), None);
+        assert_eq!(error_from_http3(0x10), None);
+        assert_eq!(to_http3(u32::MAX as u64), HTTP3_ERROR_LAST);
+    }
+
+    #[test]
+    fn session_error_termination_code() {
+        assert_eq!(
+            SessionError::TooManyRequests(10, 8).termination_code(),
+            TerminationCode::TooManyRequests
+        );
+        assert_eq!(
+            SessionError::Version(setup::Versions(vec![]), setup::Versions(vec![])).code(),
+            0x15
+        );
+        assert_eq!(
+            SessionError::Decode(coding::DecodeError::DupliateParameter).termination_code(),
+            TerminationCode::KeyValueFormattingError
+        );
+        assert_eq!(SessionError::DuplicateTrackAlias(3).code(), 0x5);
+        assert_eq!(
+            SessionError::DuplicateRequestId(3).termination_code(),
+            TerminationCode::InvalidRequestId
+        );
+        assert_eq!(
+            SessionError::Duplicate.termination_code(),
+            TerminationCode::ProtocolViolation
+        );
+        assert_eq!(
+            SessionError::Decode(coding::DecodeError::InvalidMessage(0x3f)).termination_code(),
+            TerminationCode::ProtocolViolation
+        );
+        assert_eq!(
+            SessionError::Decode(coding::DecodeError::InvalidLength(2, 1)).termination_code(),
+            TerminationCode::ProtocolViolation
+        );
+        assert_eq!(
+            SessionError::Closed(TerminationCode::GoawayTimeout, "bye".to_string()).code(),
+            0x10
+        );
+    }
+}
@@ -0,0 +1,287 @@
+use std::{collections::VecDeque, future::Future, ops};
+
+use bytes::Bytes;
+
+use crate::{
+    coding::{KeyValuePairs, Location},
+    data::ObjectStatus,
+    message::{self, FetchType, StandaloneFetch},
+    serve::ServeError,
+    watch::State,
+};
+
+use super::{FetchInfo, Subscriber};
+
+/// An object received on a fetch stream.
+#[derive(Debug, Clone)]
+pub struct FetchedObject {
+    pub group_id: u64,
+    pub subgroup_id: u64,
+    pub object_id: u64,
+
+    /// Publisher priority, where **smaller** values are sent first.
+    pub priority: u8,
+
+    pub extension_headers: KeyValuePairs,
+
+    /// The object status, NormalObject unless the payload is empty.
+    pub status: ObjectStatus,
+
+    pub payload: Bytes,
+}
+
+impl FetchedObject {
+    pub fn location(&self) -> Location {
+        Location::new(self.group_id, self.object_id)
+    }
+}
+
+/// The number of received objects queued for the application before we stop reading the fetch stream.
+/// QUIC flow control then pushes back on the publisher instead of buffering the whole range in memory.
+pub const MAX_QUEUED_FETCH_OBJECTS: usize = 64;
+
+struct FetchState {
+    /// The end location reported in FETCH_OK, once received.
+    end_location: Option<Location>,
+    end_of_track: bool,
+
+    /// Objects received but not yet read by the application.
+    objects: VecDeque<FetchedObject>,
+
+    /// Set once the fetch stream has been fully received.
+    fin: bool,
+
+    closed: Result<(), ServeError>,
+}
+
+impl Default for FetchState {
+    fn default() -> Self {
+        Self {
+            end_location: None,
+            end_of_track: false,
+            objects: VecDeque::new(),
+            fin: false,
+            closed: Ok(()),
+        }
+    }
+}
+
+// Held by the application
+#[must_use = "cancel on drop"]
+pub struct Fetch {
+    state: State<FetchState>,
+    subscriber: Subscriber,
+
+    pub info: FetchInfo,
+}
+
+impl Fetch {
+    pub(super) fn new(subscriber: Subscriber, info: FetchInfo) -> (Fetch, FetchRecv) {
+        let (send, recv) = State::default().split();
+
+        let send = Fetch {
+            state: send,
+            subscriber,
+            info,
+        };
+
+        let recv = FetchRecv { state: recv };
+
+        (send, recv)
+    }
+
+    /// Build the FETCH message for this request.
+    pub(super) fn message(&self) -> message::Fetch {
+        message::Fetch {
+            id: self.info.id,
+            subscriber_priority: self.info.subscriber_priority,
+            group_order: self.info.group_order,
+            fetch_type: self.info.fetch_type,
+            standalone_fetch: match self.info.fetch_type {
+                FetchType::Standalone => Some(StandaloneFetch {
+                    track_namespace: self.info.track_namespace.clone(),
+                    track_name: self.info.track_name.clone(),
+                    start_location: self.info.start_location,
+                    end_location: self.info.end_location,
+                }),
+                _ => None,
+            },
+            joining_fetch: self.info.joining_fetch.clone(),
+            params: self.info.params.clone(),
+        }
+    }
+
+    /// Returns the next object in the fetched range, or None once the publisher has finished the stream.
+    /// Returns an error with the FETCH_ERROR code if the publisher rejected the fetch.
+    pub async fn next(&mut self) -> Result<Option<FetchedObject>, ServeError> {
+        loop {
+            {
+                let state = self.state.lock();
+                state.closed.clone()?;
+
+                if !state.objects.is_empty() {
+                    // Only upgrade to a mutable lock when there's something to pop.
+                    let mut state = state.into_mut().ok_or(ServeError::Cancel)?;
+                    return Ok(state.objects.pop_front());
+                }
+
+                if state.fin {
+                    return Ok(None);
+                }
+
+                // The session went away before the stream was finished.
+                match state.modified() {
+                    Some(notify) => notify,
+                    None => return Err(ServeError::Cancel),
+                }
+            }
+            .await;
+        }
+    }
+
+    /// The end location reported by the publisher in FETCH_OK, if received yet.
+    pub fn end_location(&self) -> Option<Location> {
+        self.state.lock().end_location
+    }
+
+    /// True if the publisher reported that the fetched range covers the end of the track.
+    pub fn end_of_track(&self) -> bool {
+        self.state.lock().end_of_track
+    }
+}
+
+impl Drop for Fetch {
+    fn drop(&mut self) {
+        let state = self.state.lock();
+        let done = state.fin || state.closed.is_err();
+        drop(state); // Important to avoid a deadlock
+
+        if done {
+            self.subscriber.drop_fetch(self.info.id);
+        } else {
+            self.subscriber
+                .send_message(message::FetchCancel { id: self.info.id });
+        }
+    }
+}
+
+impl ops::Deref for Fetch {
+    type Target = FetchInfo;
+
+    fn deref(&self) -> &FetchInfo {
+        &self.info
+    }
+}
+
+pub(super) struct FetchRecv {
+    state: State<FetchState>,
+}
+
+impl FetchRecv {
+    pub fn ok(&mut self, msg: &message::FetchOk) -> Result<(), ServeError> {
+        let state = self.state.lock();
+        if state.end_location.is_some() {
+            return Err(ServeError::Duplicate);
+        }
+
+        if let Some(mut state) = state.into_mut() {
+            state.end_location = Some(msg.end_location);
+            state.end_of_track = msg.end_of_track;
+        }
+
+        Ok(())
+    }
+
+    pub fn object(&mut self, object: FetchedObject) -> Result<(), ServeError> {
+        let state = self.state.lock();
+        state.closed.clone()?;
+
+        let mut state = state.into_mut().ok_or(ServeError::Cancel)?;
+        state.objects.push_back(object);
+
+        Ok(())
+    }
+
+    /// Returns a future that resolves once the application reads from a full queue, or None if there's room.
+    pub fn full(&self) -> Result<Option<impl Future<Output = ()>>, ServeError> {
+        let state = self.state.lock();
+        state.closed.clone()?;
+
+        if state.objects.len() < MAX_QUEUED_FETCH_OBJECTS {
+            return Ok(None);
+        }
+
+        state.modified().map(Some).ok_or(ServeError::Cancel)
+    }
+
+    // NOTE: Kept around after the stream is finished, until the Fetch is dropped, so queued objects can be read.
+    pub fn fin(&mut self) -> Result<(), ServeError> {
+        let state = self.state.lock();
+        state.closed.clone()?;
+
+        let mut state = state.into_mut().ok_or(ServeError::Cancel)?;
+        state.fin = true;
+
+        Ok(())
+    }
+
+    pub fn error(self, err: ServeError) -> Result<(), ServeError> {
+        let state = self.state.lock();
+        state.closed.clone()?;
+
+        let mut state = state.into_mut().ok_or(ServeError::Cancel)?;
+        state.closed = Err(err);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(object_id: u64) -> FetchedObject {
+        FetchedObject {
+            group_id: 0,
+            subgroup_id: 0,
+            object_id,
+            priority: 0,
+            extension_headers: KeyValuePairs::new(),
+            status: ObjectStatus::NormalObject,
+            payload: Bytes::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn full_until_an_object_is_read() {
+        let (app, recv) = State::<FetchState>::default().split();
+        let mut recv = FetchRecv { state: recv };
+
+        for object_id in 0..MAX_QUEUED_FETCH_OBJECTS as u64 {
+            assert!(recv.full().unwrap().is_none());
+            recv.object(object(object_id)).unwrap();
+        }
+
+        let full = recv.full().unwrap().expect("queue should be full");
+
+        // Reading an object makes room again.
+        app.lock_mut().unwrap().objects.pop_front();
+        tokio::time::timeout(std::time::Duration::from_secs(1), full)
+            .await
+            .unwrap();
+        assert!(recv.full().unwrap().is_none());
+    }
+
+    #[test]
+    fn full_errors_once_cancelled() {
+        let (app, recv) = State::<FetchState>::default().split();
+        let mut recv = FetchRecv { state: recv };
+
+        for object_id in 0..MAX_QUEUED_FETCH_OBJECTS as u64 {
+            recv.object(object(object_id)).unwrap();
+        }
+
+        drop(app);
+        assert!(matches!(recv.full(), Err(ServeError::Cancel)));
+    }
+}
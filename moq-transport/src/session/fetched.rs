@@ -0,0 +1,383 @@
+use std::{cmp, ops};
+
+use crate::coding::{KeyValuePairs, Location, ReasonPhrase, TrackNamespace};
+use crate::message::{FetchType, GroupOrder, JoiningFetch};
+use crate::serve::{ServeError, TrackReaderMode};
+use crate::watch::State;
+use crate::{data, message, serve};
+
+use super::{subscribed::stream_priority, Publisher, SessionError, Writer};
+
+// This file defines Publisher handling of inbound Fetches
+
+#[derive(Debug, Clone)]
+pub struct FetchInfo {
+    pub id: u64,
+    pub track_namespace: TrackNamespace,
+    pub track_name: String,
+
+    /// Subscriber Priority
+    pub subscriber_priority: u8,
+    pub group_order: GroupOrder,
+
+    /// Standalone, or joining an existing subscription.
+    pub fetch_type: FetchType,
+    /// The subscription being joined and how far back to start, for joining fetches.
+    pub joining_fetch: Option<JoiningFetch>,
+
+    /// The first object requested.  For joining fetches, this is resolved when served.
+    pub start_location: Location,
+    /// The end of the requested range, as sent on the wire: the last object requested, plus 1.
+    /// An object_id of 0 means the entire end group is requested.
+    pub end_location: Location,
+
+    /// Optional parameters
+    pub params: KeyValuePairs,
+}
+
+impl FetchInfo {
+    pub fn new_standalone(
+        id: u64,
+        track_namespace: TrackNamespace,
+        track_name: String,
+        start_location: Location,
+        end_location: Location,
+    ) -> Self {
+        Self {
+            id,
+            track_namespace,
+            track_name,
+            subscriber_priority: 127, // default to mid value, see: https://github.com/moq-wg/moq-transport/issues/504
+            group_order: GroupOrder::Ascending,
+            fetch_type: FetchType::Standalone,
+            joining_fetch: None,
+            start_location,
+            end_location,
+            params: Default::default(),
+        }
+    }
+
+    pub fn new_joining(
+        id: u64,
+        track_namespace: TrackNamespace,
+        track_name: String,
+        fetch_type: FetchType,
+        joining_fetch: JoiningFetch,
+    ) -> Self {
+        Self {
+            fetch_type,
+            joining_fetch: Some(joining_fetch),
+            ..Self::new_standalone(
+                id,
+                track_namespace,
+                track_name,
+                Default::default(),
+                Default::default(),
+            )
+        }
+    }
+
+    /// Resolve the range of a joining fetch against the largest location of the track, so it ends
+    /// right where the joined subscription starts.
+    pub fn resolve_joining(&mut self, largest_location: Location) {
+        let joining_start = match &self.joining_fetch {
+            Some(joining_fetch) => joining_fetch.joining_start,
+            None => return,
+        };
+
+        let start_group = match self.fetch_type {
+            FetchType::RelativeJoining => largest_location.group_id.saturating_sub(joining_start),
+            FetchType::AbsoluteJoining => joining_start,
+            FetchType::Standalone => return,
+        };
+
+        self.start_location = Location::new(start_group, 0);
+        self.end_location =
+            Location::new(largest_location.group_id, largest_location.object_id + 1);
+    }
+
+    /// Returns the last requested location (inclusive).
+    pub fn last_location(&self) -> Location {
+        match self.end_location.object_id {
+            0 => Location::new(self.end_location.group_id, u64::MAX),
+            object_id => Location::new(self.end_location.group_id, object_id - 1),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct FetchedState {
+    closed: Result<(), ServeError>,
+}
+
+impl Default for FetchedState {
+    fn default() -> Self {
+        Self { closed: Ok(()) }
+    }
+}
+
+pub struct Fetched {
+    /// The sessions Publisher manager, used to send control messages
+    /// and create new QUIC streams
+    publisher: Publisher,
+
+    /// The tracknamespace, trackname and range for the fetch.
+    pub info: FetchInfo,
+
+    state: State<FetchedState>,
+
+    /// Tracks if FetchOk has been sent yet or not. Used to send
+    /// FetchError on drop.
+    ok: bool,
+}
+
+impl Fetched {
+    pub(super) fn new(publisher: Publisher, info: FetchInfo) -> (Self, FetchedRecv) {
+        let (send, recv) = State::default().split();
+        let send = Self {
+            publisher,
+            state: send,
+            info,
+            ok: false,
+        };
+
+        // Prevents updates after being closed
+        let recv = FetchedRecv { state: recv };
+
+        (send, recv)
+    }
+
+    pub async fn serve(mut self, track: serve::TrackReader) -> Result<(), SessionError> {
+        let res = self.serve_inner(track).await;
+        if let Err(err) = &res {
+            self.close(err.clone().into())?;
+        }
+
+        res
+    }
+
+    async fn serve_inner(&mut self, track: serve::TrackReader) -> Result<(), SessionError> {
+        let subgroups = match track.mode().await? {
+            TrackReaderMode::Subgroups(subgroups) => subgroups,
+            _ => {
+                return Err(ServeError::NotSupported(
+                    "fetch is only supported for subgroup tracks".to_string(),
+                )
+                .into())
+            }
+        };
+
+        // Clamp the requested range to what has been published so far.
+        let largest_location = track.largest_location().ok_or(ServeError::NotFound)?;
+        self.info.resolve_joining(largest_location);
+
+        let start = self.info.start_location;
+        let end = cmp::min(self.info.last_location(), largest_location);
+        if end < start {
+            return Err(ServeError::NotFound.into());
+        }
+
+        let mut subgroups = subgroups.cached(start.group_id, end.group_id);
+        if subgroups.is_empty() {
+            return Err(ServeError::NotFound.into());
+        }
+
+        // Publisher preference isn't allowed in FETCH_OK, so resolve it against the track.
+        let group_order = match self.info.group_order.resolve(track.group_order) {
+            GroupOrder::Descending => {
+                // Reverse the groups, but keep subgroups within a group ascending.
+                subgroups.sort_by(|a, b| {
+                    b.group_id
+                        .cmp(&a.group_id)
+                        .then(a.subgroup_id.cmp(&b.subgroup_id))
+                });
+                GroupOrder::Descending
+            }
+            _ => GroupOrder::Ascending,
+        };
+
+        // Send FetchOk using send_message_and_wait to ensure it is sent at least to the QUIC stack before
+        // we start sending the fetch stream.
+        self.publisher
+            .send_message_and_wait(message::FetchOk {
+                id: self.info.id,
+                group_order,
+                end_of_track: false,
+                end_location: end,
+                params: Default::default(),
+            })
+            .await;
+
+        self.ok = true; // So we don't send FetchError on drop
+
+        let mut send_stream = self.publisher.open_uni().await?;
+        // Objects from any subgroup share the stream, so only the subscriber priority applies.
+        send_stream.set_priority(stream_priority(self.info.subscriber_priority, 0, 0));
+
+        let mut writer = Writer::new(send_stream, self.publisher.version());
+        writer
+            .encode(&data::FetchHeader {
+                header_type: data::StreamHeaderType::Fetch,
+                request_id: self.info.id,
+            })
+            .await?;
+
+        tokio::select! {
+            res = Self::serve_objects(&mut writer, subgroups, start, end) => res,
+            res = self.closed() => {
+                // Reset the stream so the subscriber stops waiting for more objects.
+                let err = res.err().unwrap_or(ServeError::Cancel);
+                log::debug!(
+                    "[PUBLISHER] serve_fetch: fetch id={} cancelled: {}",
+                    self.info.id,
+                    err
+                );
+                writer.reset(err.fetch_error_code().code() as u32);
+                Ok(())
+            }
+        }
+    }
+
+    async fn serve_objects(
+        writer: &mut Writer,
+        subgroups: Vec<serve::SubgroupReader>,
+        start: Location,
+        end: Location,
+    ) -> Result<(), SessionError> {
+        let mut object_count = 0;
+
+        for mut subgroup_reader in subgroups {
+            // Only send the objects that exist right now; don't wait on live subgroups.
+            let available = subgroup_reader.len();
+
+            while subgroup_reader.pos() < available {
+                let mut object_reader = match subgroup_reader.next().await? {
+                    Some(object_reader) => object_reader,
+                    None => break,
+                };
+
+                let location = Location::new(subgroup_reader.group_id, object_reader.object_id);
+                if location < start {
+                    continue;
+                }
+                if location > end {
+                    break;
+                }
+
+                let fetch_object = data::FetchObject {
+                    group_id: subgroup_reader.group_id,
+                    subgroup_id: subgroup_reader.subgroup_id,
+                    object_id: object_reader.object_id,
+                    publisher_priority: subgroup_reader.priority,
+                    extension_headers: object_reader.extension_headers.clone(),
+                    payload_length: object_reader.size,
+                    status: if object_reader.size == 0 {
+                        // Only set status if payload length is zero
+                        Some(object_reader.status)
+                    } else {
+                        None
+                    },
+                };
+
+                log::debug!(
+                    "[PUBLISHER] serve_fetch: sending object #{} - group_id={}, subgroup_id={}, object_id={}, payload_length={}",
+                    object_count + 1,
+                    fetch_object.group_id,
+                    fetch_object.subgroup_id,
+                    fetch_object.object_id,
+                    fetch_object.payload_length
+                );
+
+                writer.encode(&fetch_object).await?;
+
+                while let Some(chunk) = object_reader.read().await? {
+                    writer.write(&chunk).await?;
+                }
+
+                object_count += 1;
+            }
+        }
+
+        log::info!(
+            "[PUBLISHER] serve_fetch: completed fetch ({} objects sent)",
+            object_count
+        );
+
+        Ok(())
+    }
+
+    pub fn close(self, err: ServeError) -> Result<(), ServeError> {
+        let state = self.state.lock();
+        state.closed.clone()?;
+
+        let mut state = state.into_mut().ok_or(ServeError::Done)?;
+        state.closed = Err(err);
+
+        Ok(())
+    }
+
+    pub async fn closed(&self) -> Result<(), ServeError> {
+        loop {
+            {
+                let state = self.state.lock();
+                state.closed.clone()?;
+
+                match state.modified() {
+                    Some(notify) => notify,
+                    None => return Ok(()),
+                }
+            }
+            .await;
+        }
+    }
+}
+
+impl ops::Deref for Fetched {
+    type Target = FetchInfo;
+
+    fn deref(&self) -> &Self::Target {
+        &self.info
+    }
+}
+
+impl Drop for Fetched {
+    fn drop(&mut self) {
+        if self.ok {
+            // The fetch stream is finished when the writer is dropped, nothing else to send.
+            self.publisher.drop_fetch(self.info.id);
+            return;
+        }
+
+        let state = self.state.lock();
+        let err = state
+            .closed
+            .as_ref()
+            .err()
+            .cloned()
+            .unwrap_or(ServeError::Done);
+        drop(state); // Important to avoid a deadlock
+
+        self.publisher.send_message(message::FetchError {
+            id: self.info.id,
+            error_code: err.fetch_error_code().code(),
+            reason_phrase: ReasonPhrase(err.to_string()),
+        });
+    }
+}
+
+pub(super) struct FetchedRecv {
+    state: State<FetchedState>,
+}
+
+impl FetchedRecv {
+    pub fn recv_cancel(&mut self) -> Result<(), ServeError> {
+        let state = self.state.lock();
+        state.closed.clone()?;
+
+        if let Some(mut state) = state.into_mut() {
+            state.closed = Err(ServeError::Cancel);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+use crate::coding::SessionUri;
+use crate::message::{self, Message};
+use crate::watch::{Queue, State};
+
+use super::TerminationCode;
+
+/// A handle to a running [super::Session], used to send or observe a GOAWAY.
+#[derive(Clone)]
+pub struct SessionHandle {
+    webtransport: web_transport::Session,
+    outgoing: Queue<Message>,
+
+    /// The URI from the peer's GOAWAY, once received.
+    go_away: State<Option<String>>,
+}
+
+impl SessionHandle {
+    pub(super) fn new(
+        webtransport: web_transport::Session,
+        outgoing: Queue<Message>,
+    ) -> (SessionHandle, SessionHandleRecv) {
+        let (send, recv) = State::default().split();
+
+        let send = Self {
+            webtransport,
+            outgoing,
+            go_away: send,
+        };
+        let recv = SessionHandleRecv { go_away: recv };
+
+        (send, recv)
+    }
+
+    /// Ask the peer to migrate to new_uri, or reconnect to the current URI if it's empty.
+    /// Only servers should send a GOAWAY; the session keeps running until the peer closes it.
+    pub fn go_away(&mut self, new_uri: &str) {
+        let msg = message::GoAway {
+            uri: SessionUri(new_uri.to_string()),
+        };
+
+        if self.outgoing.push(msg.into()).is_err() {
+            log::debug!("session closed before GOAWAY was sent");
+        }
+    }
+
+    /// Close the session immediately with a termination code, such as GOAWAY_TIMEOUT once the peer didn't
+    /// migrate in time.
+    pub fn close(self, code: TerminationCode, reason: &str) {
+        self.webtransport.close(code.code() as u32, reason);
+    }
+
+    /// Wait until the peer sends a GOAWAY, returning the URI to migrate to.
+    /// An empty URI means reconnecting to the current one.  Returns None if the session was closed first.
+    pub async fn go_away_received(&self) -> Option<String> {
+        loop {
+            {
+                let state = self.go_away.lock();
+                if let Some(uri) = state.clone() {
+                    return Some(uri);
+                }
+
+                state.modified()?
+            }
+            .await;
+        }
+    }
+}
+
+/// Held by the session to report a received GOAWAY.  Waiters see the session close once it's dropped.
+pub(super) struct SessionHandleRecv {
+    go_away: State<Option<String>>,
+}
+
+impl SessionHandleRecv {
+    pub fn recv_go_away(&mut self, msg: message::GoAway) {
+        if let Some(mut state) = self.go_away.lock_mut() {
+            *state = Some(msg.uri.0);
+        }
+    }
+}
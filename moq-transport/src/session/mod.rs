@@ -1,30 +1,56 @@
 mod announce;
 mod announced;
+mod auth;
+mod backlog;
+mod config;
 mod error;
+mod fetch;
+mod fetched;
+mod handle;
+mod publish;
+mod published;
 mod publisher;
 mod reader;
+mod request_ids;
 mod subscribe;
+mod subscribe_namespace;
 mod subscribed;
 mod subscriber;
+mod track_alias;
+mod track_status;
 mod track_status_requested;
 mod writer;
 
 pub use announce::*;
 pub use announced::*;
+pub use auth::*;
+pub use backlog::*;
+pub use config::*;
 pub use error::*;
+pub use fetch::*;
+pub use fetched::*;
+pub use handle::*;
+pub use publish::*;
+pub use published::*;
 pub use publisher::*;
 pub use subscribe::*;
+pub use subscribe_namespace::*;
 pub use subscribed::*;
 pub use subscriber::*;
+pub use track_status::*;
 pub use track_status_requested::*;
 
+use auth::{decode_auth_token, RequestAuth, RequestToken};
 use reader::*;
+use request_ids::*;
+use track_alias::*;
 use writer::*;
 
 use futures::{stream::FuturesUnordered, StreamExt};
+use std::collections::VecDeque;
 use std::sync::{atomic, Arc, Mutex};
 
-use crate::coding::KeyValuePairs;
+use crate::coding::{AuthToken, KeyValuePairs};
 use crate::message::Message;
 use crate::mlog;
 use crate::watch::Queue;
@@ -46,11 +72,51 @@ pub struct Session {
     /// Queue used by Publisher and Subscriber for sending Control Messages
     outgoing: Queue<Message>,
 
+    /// MAX_REQUEST_ID flow control in both directions
+    request_ids: RequestIds,
+
+    /// The next request id, shared with the Publisher and Subscriber
+    next_requestid: Arc<atomic::AtomicU64>,
+
+    /// The version negotiated in SETUP, and the parameters the peer sent
+    version: setup::Version,
+    peer_params: KeyValuePairs,
+
+    /// The token the client sent in CLIENT_SETUP, if we're the server
+    auth_token: Option<AuthToken>,
+
+    /// Checks the tokens on the peer's requests, and whether to allow them
+    request_auth: RequestAuth,
+
+    /// Used to send a GOAWAY, or observe one from the peer, while the session is running
+    handle: SessionHandle,
+    handle_recv: SessionHandleRecv,
+
     /// Optional mlog writer for MoQ Transport events
     /// Wrapped in Arc<Mutex<>> to share across send/recv tasks when enabled
     mlog: Option<Arc<Mutex<mlog::MlogWriter>>>,
 }
 
+/// The outcome of the SETUP exchange, used to create the [Session].
+struct Setup {
+    /// The control stream, which switches to the negotiated version once the session is created.
+    sender: Writer,
+    recver: Reader,
+
+    /// The first request id we use: 0 for the client and 1 for the server.
+    first_requestid: u64,
+
+    /// The version negotiated in SETUP, and the parameters the peer sent.
+    version: setup::Version,
+    peer_params: KeyValuePairs,
+
+    /// The tokens the client registered in CLIENT_SETUP, and the one it sent, if we're the server.
+    auth_cache: AuthTokenCache,
+    auth_token: Option<AuthToken>,
+
+    mlog: Option<mlog::MlogWriter>,
+}
+
 impl Session {
     // Helper for determining the largest supported version
     fn largest_common<T: Ord + Clone + Eq>(a: &[T], b: &[T]) -> Option<T> {
@@ -62,13 +128,38 @@ impl Session {
 
     fn new(
         webtransport: web_transport::Session,
-        sender: Writer,
-        recver: Reader,
-        first_requestid: u64,
-        mlog: Option<mlog::MlogWriter>,
+        setup: Setup,
+        config: &SessionConfig,
     ) -> (Self, Option<Publisher>, Option<Subscriber>) {
+        let Setup {
+            mut sender,
+            mut recver,
+            first_requestid,
+            version,
+            peer_params,
+            auth_cache,
+            auth_token,
+            mlog,
+        } = setup;
+
+        // The peer doesn't allow any requests or cached tokens unless it says otherwise.
+        let peer_max_requestid = peer_params
+            .get_intvalue(setup::ParameterType::MaxRequestId.into())
+            .unwrap_or(0);
+        let peer_auth_cache_size = peer_params
+            .get_intvalue(setup::ParameterType::MaxAuthTokenCacheSize.into())
+            .unwrap_or(0);
+
+        // Everything after SETUP uses the negotiated version's wire format.
+        sender.set_version(version);
+        recver.set_version(version);
+
         let next_requestid = Arc::new(atomic::AtomicU64::new(first_requestid));
         let outgoing = Queue::default().split();
+        let (handle, handle_recv) = SessionHandle::new(webtransport.clone(), outgoing.0.clone());
+        let request_auth =
+            RequestAuth::new(auth_cache, config.authorizer.clone(), outgoing.0.clone());
+        let request_token = RequestToken::new(peer_auth_cache_size);
 
         // Wrap mlog in Arc<Mutex<>> for sharing across tasks
         let mlog_shared = mlog.map(|m| Arc::new(Mutex::new(m)));
@@ -77,11 +168,16 @@ impl Session {
             outgoing.0.clone(),
             webtransport.clone(),
             next_requestid.clone(),
+            request_token.clone(),
+            version,
+            config.unknown_requests,
             mlog_shared.clone(),
         ));
         let subscriber = Some(Subscriber::new(
             outgoing.0,
-            next_requestid,
+            next_requestid.clone(),
+            request_token,
+            version,
             mlog_shared.clone(),
         ));
 
@@ -92,6 +188,19 @@ impl Session {
             publisher: publisher.clone(),
             subscriber: subscriber.clone(),
             outgoing: outgoing.1,
+            // The peer's requests use the other parity: odd for the server and even for the client.
+            request_ids: RequestIds::new(
+                config.max_request_id,
+                peer_max_requestid,
+                first_requestid ^ 1,
+            ),
+            next_requestid,
+            version,
+            peer_params,
+            auth_token,
+            request_auth,
+            handle,
+            handle_recv,
             mlog: mlog_shared,
         };
 
@@ -101,27 +210,32 @@ impl Session {
     /// Create an outbound/client QUIC connection, by opening a bi-directional QUIC stream for
     /// MOQT control messaging.  Performs SETUP messaging and version negotiation.
     pub async fn connect(
-        mut session: web_transport::Session,
+        session: web_transport::Session,
         mlog_path: Option<PathBuf>,
     ) -> Result<(Session, Publisher, Subscriber), SessionError> {
-        let mlog = mlog_path.and_then(|path| {
-            mlog::MlogWriter::new(path)
-                .map_err(|e| log::warn!("Failed to create mlog: {}", e))
-                .ok()
-        });
-        let control = session.open_bi().await?;
-        let mut sender = Writer::new(control.0);
-        let mut recver = Reader::new(control.1);
-
-        let versions: setup::Versions = [setup::Version::DRAFT_14].into();
+        Self::connect_with_config(session, SessionConfig::default().with_mlog_path(mlog_path)).await
+    }
 
-        // TODO SLG - make configurable?
-        let mut params = KeyValuePairs::default();
-        params.set_intvalue(setup::ParameterType::MaxRequestId.into(), 100);
+    /// Like [Session::connect], with the versions and SETUP parameters from the config.
+    pub async fn connect_with_config(
+        mut session: web_transport::Session,
+        config: SessionConfig,
+    ) -> Result<(Session, Publisher, Subscriber), SessionError> {
+        let versions = match config.supported_versions() {
+            Ok(versions) => versions,
+            Err(err) => {
+                session.close(err.code() as u32, &err.to_string());
+                return Err(err);
+            }
+        };
+        let mlog = Self::create_mlog(&config);
+        let control = session.open_bi().await?;
+        let mut sender = Writer::new(control.0, setup::Version::LATEST);
+        let mut recver = Reader::new(control.1, setup::Version::LATEST);
 
         let client = setup::Client {
             versions: versions.clone(),
-            params,
+            params: config.setup_params(true),
         };
 
         log::debug!("sending CLIENT_SETUP: {:?}", client);
@@ -132,27 +246,54 @@ impl Session {
         let server: setup::Server = recver.decode().await?;
         log::debug!("received SERVER_SETUP: {:?}", server);
 
+        if !versions.contains(&server.version) {
+            let err = SessionError::Version(versions, [server.version].into());
+            session.close(err.code() as u32, &err.to_string());
+            return Err(err);
+        }
+
         // TODO: emit server_setup_parsed event
 
         // We are the client, so the first request id is 0
-        let session = Session::new(session, sender, recver, 0, mlog);
+        let setup = Setup {
+            sender,
+            recver,
+            first_requestid: 0,
+            version: server.version,
+            peer_params: server.params,
+            auth_cache: AuthTokenCache::new(config.max_auth_token_cache_size),
+            auth_token: None,
+            mlog,
+        };
+        let session = Session::new(session, setup, &config);
         Ok((session.0, session.1.unwrap(), session.2.unwrap()))
     }
 
     /// Accepts an inbound/server QUIC connection, by accepting a bi-directional QUIC stream for
     /// MOQT control messaging.  Performs SETUP messaging and version negotiation.
     pub async fn accept(
-        mut session: web_transport::Session,
+        session: web_transport::Session,
         mlog_path: Option<PathBuf>,
     ) -> Result<(Session, Option<Publisher>, Option<Subscriber>), SessionError> {
-        let mut mlog = mlog_path.and_then(|path| {
-            mlog::MlogWriter::new(path)
-                .map_err(|e| log::warn!("Failed to create mlog: {}", e))
-                .ok()
-        });
+        Self::accept_with_config(session, SessionConfig::default().with_mlog_path(mlog_path)).await
+    }
+
+    /// Like [Session::accept], with the versions and SETUP parameters from the config.
+    pub async fn accept_with_config(
+        mut session: web_transport::Session,
+        config: SessionConfig,
+    ) -> Result<(Session, Option<Publisher>, Option<Subscriber>), SessionError> {
+        let versions = match config.supported_versions() {
+            Ok(versions) => versions,
+            Err(err) => {
+                session.close(err.code() as u32, &err.to_string());
+                return Err(err);
+            }
+        };
+        let mut mlog = Self::create_mlog(&config);
         let control = session.accept_bi().await?;
-        let mut sender = Writer::new(control.0);
-        let mut recver = Reader::new(control.1);
+        let mut sender = Writer::new(control.0, setup::Version::LATEST);
+        let mut recver = Reader::new(control.1, setup::Version::LATEST);
 
         let client: setup::Client = recver.decode().await?;
         log::debug!("received CLIENT_SETUP: {:?}", client);
@@ -163,18 +304,18 @@ impl Session {
             let _ = mlog.add_event(event);
         }
 
-        let server_versions = setup::Versions(vec![setup::Version::DRAFT_14]);
-
-        if let Some(largest_common_version) =
-            Self::largest_common(&server_versions, &client.versions)
-        {
-            // TODO SLG - make configurable?
-            let mut params = KeyValuePairs::default();
-            params.set_intvalue(setup::ParameterType::MaxRequestId.into(), 100);
+        let (auth_cache, auth_token) = match Self::authorize_setup(&config, &client.params) {
+            Ok(auth) => auth,
+            Err(err) => {
+                session.close(err.code() as u32, &err.to_string());
+                return Err(err);
+            }
+        };
 
+        if let Some(largest_common_version) = Self::largest_common(&versions, &client.versions) {
             let server = setup::Server {
                 version: largest_common_version,
-                params,
+                params: config.setup_params(false),
             };
 
             log::debug!("sending SERVER_SETUP: {:?}", server);
@@ -188,89 +329,274 @@ impl Session {
             sender.encode(&server).await?;
 
             // We are the server, so the first request id is 1
-            Ok(Session::new(session, sender, recver, 1, mlog))
+            let setup = Setup {
+                sender,
+                recver,
+                first_requestid: 1,
+                version: largest_common_version,
+                peer_params: client.params,
+                auth_cache,
+                auth_token,
+                mlog,
+            };
+            Ok(Session::new(session, setup, &config))
         } else {
-            Err(SessionError::Version(client.versions, server_versions))
+            let err = SessionError::Version(client.versions, versions);
+            session.close(err.code() as u32, &err.to_string());
+            Err(err)
         }
     }
 
+    /// Resolve the client's AUTHORIZATION_TOKEN, which may register an alias, and ask the authorizer to allow it.
+    fn authorize_setup(
+        config: &SessionConfig,
+        params: &KeyValuePairs,
+    ) -> Result<(AuthTokenCache, Option<AuthToken>), SessionError> {
+        let mut cache = AuthTokenCache::new(config.max_auth_token_cache_size);
+        let token =
+            match decode_auth_token(params, setup::ParameterType::AuthorizationToken.into())? {
+                Some(param) => cache.recv(param)?,
+                None => None,
+            };
+
+        if let Some(authorizer) = &config.authorizer {
+            authorizer
+                .authorize_setup(token.as_ref())
+                .map_err(|err| SessionError::Unauthorized(err.to_string()))?;
+        }
+
+        Ok((cache, token))
+    }
+
+    fn create_mlog(config: &SessionConfig) -> Option<mlog::MlogWriter> {
+        config.mlog_path.clone().and_then(|path| {
+            mlog::MlogWriter::new(path)
+                .map_err(|e| log::warn!("Failed to create mlog: {}", e))
+                .ok()
+        })
+    }
+
+    /// The version negotiated in SETUP.
+    pub fn version(&self) -> setup::Version {
+        self.version
+    }
+
+    /// The parameters the peer sent in its CLIENT_SETUP or SERVER_SETUP.
+    pub fn peer_params(&self) -> &KeyValuePairs {
+        &self.peer_params
+    }
+
+    /// The AUTHORIZATION_TOKEN the client sent in CLIENT_SETUP, if we're the server.
+    pub fn auth_token(&self) -> Option<&AuthToken> {
+        self.auth_token.as_ref()
+    }
+
+    /// Returns a handle used to send or observe a GOAWAY once the session is running.
+    pub fn handle(&self) -> SessionHandle {
+        self.handle.clone()
+    }
+
+    /// Ask the peer to migrate to new_uri, or reconnect to the current URI if it's empty.
+    pub fn go_away(&mut self, new_uri: &str) {
+        self.handle.go_away(new_uri)
+    }
+
+    /// Connect a new session, such as after a GOAWAY, and move the active subscriptions and announces of
+    /// the old session's publisher and subscriber onto it.  Subscriptions move over at the next group, so
+    /// the old session can finish the current one while it drains.
+    /// The returned session must be run in addition to the old one.
+    pub async fn migrate(
+        webtransport: web_transport::Session,
+        publisher: &mut Publisher,
+        subscriber: &mut Subscriber,
+        config: SessionConfig,
+    ) -> Result<(Session, Publisher, Subscriber), SessionError> {
+        let (session, new_publisher, new_subscriber) =
+            Session::connect_with_config(webtransport, config).await?;
+
+        subscriber.migrate(&new_subscriber);
+        publisher.migrate(&new_publisher);
+
+        Ok((session, new_publisher, new_subscriber))
+    }
+
     /// Run Tasks for the session, including sending of control messages, receiving and processing
     /// inbound control messages, receiving and processing new inbound uni-directional QUIC streams,
     /// and receiving and processing QUIC datagrams received
     pub async fn run(self) -> Result<(), SessionError> {
-        tokio::select! {
-            res = Self::run_recv(self.recver, self.publisher, self.subscriber.clone(), self.mlog.clone()) => res,
-            res = Self::run_send(self.sender, self.outgoing, self.mlog.clone()) => res,
+        let webtransport = self.webtransport.clone();
+
+        let res = tokio::select! {
+            res = Self::run_recv(self.recver, self.publisher.clone(), self.subscriber.clone(), self.request_ids.clone(), self.handle_recv, self.request_auth, self.mlog.clone()) => res,
+            res = Self::run_send(self.sender, self.outgoing, self.request_ids, self.next_requestid, self.mlog.clone()) => res,
             res = Self::run_streams(self.webtransport.clone(), self.subscriber.clone()) => res,
+            res = Self::run_unused(self.subscriber.clone()) => res,
+            res = Self::run_unknown(self.publisher) => res,
             res = Self::run_datagrams(self.webtransport, self.subscriber) => res,
+        };
+
+        // Let the peer know why the session was closed.
+        if let Err(err) = &res {
+            webtransport.close(err.code() as u32, &err.to_string());
         }
+
+        res
     }
 
     /// Processes the outgoing control message queue, and sends queued messages on the control stream sender/writer.
+    /// Requests beyond the peer's MAX_REQUEST_ID are held back, in order, until the peer grants more.  Other
+    /// messages are sent right away, since the peer may be waiting on our responses before it grants more,
+    /// except for cancels of a held-back request, which can't overtake it.
     async fn run_send(
         mut sender: Writer,
         mut outgoing: Queue<message::Message>,
+        request_ids: RequestIds,
+        next_requestid: Arc<atomic::AtomicU64>,
         mlog: Option<Arc<Mutex<mlog::MlogWriter>>>,
     ) -> Result<(), SessionError> {
-        while let Some(msg) = outgoing.pop().await {
-            log::debug!("sending message: {:?}", msg);
+        let mut blocked: VecDeque<Message> = VecDeque::new();
 
-            // Emit mlog event for sent control messages
-            if let Some(ref mlog) = mlog {
-                if let Ok(mut mlog_guard) = mlog.lock() {
-                    let time = mlog_guard.elapsed_ms();
-                    let stream_id = 0; // Control stream is always stream 0
+        loop {
+            let changed = request_ids.changed();
+
+            while let Some(msg) = blocked.front() {
+                if let Some(id) = msg.request_id().filter(|id| !request_ids.allowed(*id)) {
+                    if let Some(msg) = request_ids.blocked() {
+                        log::debug!(
+                            "blocked by the peer's MAX_REQUEST_ID at id={}: {:?}",
+                            id,
+                            msg
+                        );
+                        Self::send_message(&mut sender, msg.into(), &mlog).await?;
+                    }
+                    break;
+                }
 
-                    // Emit events based on message type
-                    let event = match &msg {
-                        Message::Subscribe(m) => {
-                            Some(mlog::events::subscribe_created(time, stream_id, m))
-                        }
-                        Message::SubscribeOk(m) => {
-                            Some(mlog::events::subscribe_ok_created(time, stream_id, m))
-                        }
-                        Message::SubscribeError(m) => {
-                            Some(mlog::events::subscribe_error_created(time, stream_id, m))
-                        }
-                        Message::Unsubscribe(m) => {
-                            Some(mlog::events::unsubscribe_created(time, stream_id, m))
-                        }
-                        Message::PublishNamespace(m) => {
-                            Some(mlog::events::publish_namespace_created(time, stream_id, m))
-                        }
-                        Message::PublishNamespaceOk(m) => Some(
-                            mlog::events::publish_namespace_ok_created(time, stream_id, m),
-                        ),
-                        Message::PublishNamespaceError(m) => Some(
-                            mlog::events::publish_namespace_error_created(time, stream_id, m),
-                        ),
-                        Message::GoAway(m) => {
-                            Some(mlog::events::go_away_created(time, stream_id, m))
+                let msg = blocked.pop_front().unwrap();
+                Self::send_message(&mut sender, msg, &mlog).await?;
+            }
+
+            // Grant the peer more request IDs as its requests are answered.
+            if let Some(msg) = request_ids.grant() {
+                Self::send_message(&mut sender, msg.into(), &mlog).await?;
+            }
+
+            tokio::select! {
+                msg = outgoing.pop() => match msg {
+                    Some(msg) if msg.request_id().is_some() => blocked.push_back(msg),
+                    Some(msg) if blocked.iter().any(|request| msg.cancels(request)) => {
+                        Self::cancel_blocked(&mut blocked, msg, &next_requestid)
+                    }
+                    Some(msg) => {
+                        let response = msg.response_id().is_some();
+                        Self::send_message(&mut sender, msg, &mlog).await?;
+
+                        // The peer's request is complete once answered, so it can use another ID.
+                        if response {
+                            request_ids.complete();
                         }
-                        _ => None, // TODO: Add other message types
-                    };
+                    }
+                    None => return Ok(()),
+                },
+                Some(()) = async move {
+                    changed?.await;
+                    Some(())
+                } => {},
+            }
+        }
+    }
 
-                    if let Some(event) = event {
-                        let _ = mlog_guard.add_event(event);
+    /// Cancel a request that's still held back.  If no later request ID was handed out, the request is dropped
+    /// without sending either message, and its ID is reused so the peer still sees IDs in sequence.  Otherwise the
+    /// cancel is queued behind the request, so the peer never sees it for an ID it doesn't know.
+    fn cancel_blocked(
+        blocked: &mut VecDeque<Message>,
+        cancel: Message,
+        next_requestid: &atomic::AtomicU64,
+    ) {
+        let Some(index) = blocked.iter().position(|request| cancel.cancels(request)) else {
+            return;
+        };
+        let id = blocked[index].request_id().unwrap_or_default();
+
+        let reused = next_requestid.compare_exchange(
+            id + REQUEST_ID_STEP,
+            id,
+            atomic::Ordering::Relaxed,
+            atomic::Ordering::Relaxed,
+        );
+
+        match reused {
+            Ok(_) => {
+                log::debug!("dropping held-back request id={}: {:?}", id, cancel);
+                blocked.remove(index);
+            }
+            Err(_) => blocked.push_back(cancel),
+        }
+    }
+
+    async fn send_message(
+        sender: &mut Writer,
+        msg: message::Message,
+        mlog: &Option<Arc<Mutex<mlog::MlogWriter>>>,
+    ) -> Result<(), SessionError> {
+        log::debug!("sending message: {:?}", msg);
+
+        // Emit mlog event for sent control messages
+        if let Some(ref mlog) = mlog {
+            if let Ok(mut mlog_guard) = mlog.lock() {
+                let time = mlog_guard.elapsed_ms();
+                let stream_id = 0; // Control stream is always stream 0
+
+                // Emit events based on message type
+                let event = match &msg {
+                    Message::Subscribe(m) => {
+                        Some(mlog::events::subscribe_created(time, stream_id, m))
+                    }
+                    Message::SubscribeOk(m) => {
+                        Some(mlog::events::subscribe_ok_created(time, stream_id, m))
+                    }
+                    Message::SubscribeError(m) => {
+                        Some(mlog::events::subscribe_error_created(time, stream_id, m))
                     }
+                    Message::Unsubscribe(m) => {
+                        Some(mlog::events::unsubscribe_created(time, stream_id, m))
+                    }
+                    Message::PublishNamespace(m) => {
+                        Some(mlog::events::publish_namespace_created(time, stream_id, m))
+                    }
+                    Message::PublishNamespaceOk(m) => Some(
+                        mlog::events::publish_namespace_ok_created(time, stream_id, m),
+                    ),
+                    Message::PublishNamespaceError(m) => Some(
+                        mlog::events::publish_namespace_error_created(time, stream_id, m),
+                    ),
+                    Message::GoAway(m) => Some(mlog::events::go_away_created(time, stream_id, m)),
+                    _ => None, // TODO: Add other message types
+                };
+
+                if let Some(event) = event {
+                    let _ = mlog_guard.add_event(event);
                 }
             }
-
-            sender.encode(&msg).await?;
         }
 
+        sender.encode(&msg).await?;
         Ok(())
     }
 
     /// Receives inbound messages from the control stream reader/receiver.  Analyzes if the message
     /// is to be handled by Subscriber or Publisher logic and calls recv_message on either the
     /// Publisher or Subscriber.
-    /// Note:  Should also be handling messages common to both roles, ie: GOAWAY, MAX_REQUEST_ID and
-    ///        REQUESTS_BLOCKED
+    /// GOAWAY, MAX_REQUEST_ID and REQUESTS_BLOCKED are common to both roles and handled here, as is checking
+    /// each request's AUTHORIZATION_TOKEN.
     async fn run_recv(
         mut recver: Reader,
         mut publisher: Option<Publisher>,
         mut subscriber: Option<Subscriber>,
+        request_ids: RequestIds,
+        mut handle: SessionHandleRecv,
+        mut auth: RequestAuth,
         mlog: Option<Arc<Mutex<mlog::MlogWriter>>>,
     ) -> Result<(), SessionError> {
         loop {
@@ -318,6 +644,20 @@ impl Session {
                 }
             }
 
+            if let Some(id) = msg.request_id() {
+                request_ids.recv_request(id)?;
+
+                // SUBSCRIBE_UPDATE has no response, so it's complete as soon as it's received.
+                if let Message::SubscribeUpdate(_) = msg {
+                    request_ids.complete();
+                }
+
+                // Rejected requests are answered with an error and never reach the publisher or subscriber.
+                if !auth.recv(&msg)? {
+                    continue;
+                }
+            }
+
             let msg = match TryInto::<message::Publisher>::try_into(msg) {
                 Ok(msg) => {
                     subscriber
@@ -340,8 +680,17 @@ impl Session {
                 Err(msg) => msg,
             };
 
-            // TODO GOAWAY, MAX_REQUEST_ID, REQUESTS_BLOCKED
-            unimplemented!("unknown message context: {:?}", msg)
+            match msg {
+                Message::MaxRequestId(msg) => request_ids.recv_max(msg.request_id)?,
+                Message::RequestsBlocked(_) => request_ids.recv_blocked(),
+                Message::GoAway(msg) => handle.recv_go_away(msg),
+                msg => {
+                    return Err(SessionError::ProtocolViolation(format!(
+                        "unexpected message: {:?}",
+                        msg
+                    )))
+                }
+            }
         }
     }
 
@@ -371,6 +720,22 @@ impl Session {
         }
     }
 
+    /// Answers requests for unknown namespaces that the application didn't read in time.
+    async fn run_unknown(publisher: Option<Publisher>) -> Result<(), SessionError> {
+        match publisher {
+            Some(publisher) => publisher.run_unknown().await,
+            None => std::future::pending().await,
+        }
+    }
+
+    /// Unsubscribes as soon as every reader of a subscribed track is dropped.
+    async fn run_unused(subscriber: Option<Subscriber>) -> Result<(), SessionError> {
+        match subscriber {
+            Some(subscriber) => subscriber.run_unused().await,
+            None => std::future::pending().await,
+        }
+    }
+
     /// Receives QUIC datagrams and processes them using the Subscriber logic
     async fn run_datagrams(
         mut webtransport: web_transport::Session,
@@ -0,0 +1,132 @@
+use std::ops;
+
+use crate::watch::State;
+use crate::{message, serve, serve::ServeError};
+
+use super::{Publisher, TrackAlias};
+
+// This file defines Publisher handling of outbound Publishes
+
+struct PublishState {
+    ok: Option<message::PublishOk>,
+    closed: Result<(), ServeError>,
+}
+
+impl Default for PublishState {
+    fn default() -> Self {
+        Self {
+            ok: None,
+            closed: Ok(()),
+        }
+    }
+}
+
+// Held by Publisher::publish while waiting for a response
+pub struct Publish {
+    publisher: Publisher,
+    state: State<PublishState>,
+    track_alias: TrackAlias,
+
+    pub request_msg: message::Publish,
+}
+
+impl Publish {
+    pub(super) fn new(
+        publisher: Publisher,
+        request_id: u64,
+        track: &serve::TrackReader,
+    ) -> (Publish, PublishRecv) {
+        let largest_location = track.largest_location();
+        let track_alias = publisher.track_alias(&track.namespace, &track.name);
+        let request_msg = message::Publish {
+            id: request_id,
+            track_namespace: track.namespace.clone(),
+            track_name: track.name.clone(),
+            track_alias: track_alias.id(),
+            group_order: track.group_order.resolve(message::GroupOrder::Ascending), // Publisher isn't allowed here
+            content_exists: largest_location.is_some(),
+            largest_location,
+            forward: true,
+            params: publisher.request_params(),
+        };
+
+        let (send, recv) = State::default().split();
+
+        let send = Self {
+            publisher,
+            state: send,
+            track_alias,
+            request_msg,
+        };
+        let recv = PublishRecv { state: recv };
+
+        (send, recv)
+    }
+
+    /// The alias of the published track, which the subscription it turns into keeps using.
+    pub(super) fn track_alias(&self) -> TrackAlias {
+        self.track_alias.clone()
+    }
+
+    /// Wait until a PUBLISH_OK is received, returning it.
+    pub async fn ok(&self) -> Result<message::PublishOk, ServeError> {
+        loop {
+            {
+                let state = self.state.lock();
+                if let Some(ok) = &state.ok {
+                    return Ok(ok.clone());
+                }
+                state.closed.clone()?;
+
+                match state.modified() {
+                    Some(notified) => notified,
+                    None => return Err(ServeError::Cancel),
+                }
+            }
+            .await;
+        }
+    }
+}
+
+impl ops::Deref for Publish {
+    type Target = message::Publish;
+
+    fn deref(&self) -> &Self::Target {
+        &self.request_msg
+    }
+}
+
+impl Drop for Publish {
+    fn drop(&mut self) {
+        self.publisher.drop_publish(self.request_msg.id);
+    }
+}
+
+pub(super) struct PublishRecv {
+    state: State<PublishState>,
+}
+
+impl PublishRecv {
+    pub fn recv_ok(self, msg: message::PublishOk) -> Result<(), ServeError> {
+        let state = self.state.lock();
+        if state.ok.is_some() {
+            return Err(ServeError::Duplicate);
+        }
+
+        if let Some(mut state) = state.into_mut() {
+            state.ok = Some(msg);
+        }
+
+        Ok(())
+    }
+
+    pub fn recv_error(self, err: ServeError) -> Result<(), ServeError> {
+        let state = self.state.lock();
+        state.closed.clone()?;
+
+        let mut state = state.into_mut().ok_or(ServeError::Done)?;
+        state.closed = Err(err);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,89 @@
+use std::ops;
+
+use crate::coding::ReasonPhrase;
+use crate::message::{self, FilterType};
+use crate::serve::{ServeError, TrackWriter};
+
+use super::{Subscribe, SubscribeInfo, Subscriber};
+
+// This file defines Subscriber handling of inbound Publishes
+
+pub struct Published {
+    subscriber: Subscriber,
+
+    pub info: message::Publish,
+
+    ok: bool,
+    error: Option<ServeError>,
+}
+
+impl Published {
+    pub(super) fn new(subscriber: Subscriber, info: message::Publish) -> Self {
+        Self {
+            subscriber,
+            info,
+            ok: false,
+            error: None,
+        }
+    }
+
+    /// Accept the publish with a PUBLISH_OK and write the pushed track into the provided [TrackWriter].
+    /// Block until the publisher is done.
+    pub async fn accept(mut self, track: TrackWriter) -> Result<(), ServeError> {
+        let ok = message::PublishOk {
+            id: self.info.id,
+            forward: true,
+            subscriber_priority: 127, // default to mid value, see: https://github.com/moq-wg/moq-transport/issues/504
+            group_order: self.info.group_order,
+            filter_type: FilterType::LargestObject,
+            start_location: None,
+            end_group_id: None,
+            params: Default::default(),
+        };
+
+        let info = SubscribeInfo::new_from_publish(&self.info, &ok);
+        let (send, recv) =
+            Subscribe::new_published(self.subscriber.clone(), info, (&self.info).into(), track);
+        if let Err(err) = self
+            .subscriber
+            .add_published(self.info.id, self.info.track_alias, recv)
+        {
+            self.error = Some(err.clone());
+            return Err(err);
+        }
+
+        self.subscriber.send_message(ok);
+        self.ok = true;
+
+        send.closed().await
+    }
+
+    /// Reject the publish with a PUBLISH_ERROR.
+    pub fn close(mut self, err: ServeError) -> Result<(), ServeError> {
+        self.error = Some(err);
+        Ok(())
+    }
+}
+
+impl ops::Deref for Published {
+    type Target = message::Publish;
+
+    fn deref(&self) -> &Self::Target {
+        &self.info
+    }
+}
+
+impl Drop for Published {
+    fn drop(&mut self) {
+        if self.ok {
+            return;
+        }
+
+        let err = self.error.clone().unwrap_or(ServeError::NotFound);
+        self.subscriber.send_message(message::PublishError {
+            id: self.info.id,
+            error_code: err.publish_error_code().code(),
+            reason_phrase: ReasonPhrase(err.to_string()),
+        });
+    }
+}
@@ -6,16 +6,19 @@ use std::{
 use futures::{stream::FuturesUnordered, StreamExt};
 
 use crate::{
-    coding::TrackNamespace,
+    coding::{AuthToken, DecodeError, KeyValuePairs, ReasonPhrase, TrackNamespace},
     message::{self, Message},
     mlog,
-    serve::{ServeError, TracksReader},
+    serve::{self, ServeError, TracksReader},
+    setup,
 };
 
-use crate::watch::Queue;
+use crate::watch::{Queue, State};
 
 use super::{
-    Announce, AnnounceRecv, Session, SessionError, Subscribed, SubscribedRecv, TrackStatusRequested,
+    Announce, AnnounceRecv, Backlog, FetchInfo, Fetched, FetchedRecv, Publish, PublishRecv,
+    RequestToken, Session, SessionError, SubscribeInfo, Subscribed, SubscribedRecv, TrackAlias,
+    TrackAliases, TrackStatusRequested, UnknownRequestMetrics, UnknownRequestPolicy,
 };
 
 // TODO remove Clone.
@@ -31,12 +34,28 @@ pub struct Publisher {
     subscribeds: Arc<Mutex<HashMap<u64, SubscribedRecv>>>,
 
     /// When a Subscribe is received and we DO NOT have a previous announce for the namespace, then a new entry is
-    /// added to this Queue to track the inbound subscription
-    unknown_subscribed: Queue<Subscribed>,
+    /// added to this Backlog to track the inbound subscription, unless the [UnknownRequestPolicy] rejects it
+    unknown_subscribed: Backlog<Subscribed>,
+
+    /// The currently active outbound publishes waiting for a response, keyed by request id.
+    publishes: Arc<Mutex<HashMap<u64, PublishRecv>>>,
+
+    /// The namespace prefixes the peer has subscribed to, with the SubscribeNamespace request id.
+    subscribe_namespaces: Arc<Mutex<HashMap<TrackNamespace, u64>>>,
+
+    /// Newly subscribed namespace prefixes, so the application can announce the matching namespaces it serves.
+    subscribed_namespaces: Queue<TrackNamespace>,
+
+    /// When a Fetch is received, a new entry is added to this HashMap to track the inbound fetch
+    fetches: Arc<Mutex<HashMap<u64, FetchedRecv>>>,
+
+    /// When a Fetch is received and we DO NOT have a previous announce for the namespace, then a new entry is
+    /// added to this Queue to track the inbound fetch
+    unknown_fetched: Queue<Fetched>,
 
     /// When a TrackStatus is received and we DO NOT have a previous announce for the namespace, then a new entry is
-    /// added to this Queue to track the inbound track status request
-    unknown_track_status_requested: Queue<TrackStatusRequested>,
+    /// added to this Backlog to track the inbound track status request, unless the [UnknownRequestPolicy] rejects it
+    unknown_track_status_requested: Backlog<TrackStatusRequested>,
 
     /// The queue we will write any outbound control messages we want to sent, the session run_send task
     /// will process the queue and send the message on the control stream.
@@ -49,26 +68,50 @@ pub struct Publisher {
     /// increment by 2 for each request (odd numbers).
     next_requestid: Arc<atomic::AtomicU64>,
 
+    /// The AUTHORIZATION_TOKEN attached to our requests, shared with the Subscriber.
+    auth: RequestToken,
+
+    /// The track aliases used by our subscriptions and publishes.
+    track_aliases: TrackAliases,
+
+    /// The version negotiated in SETUP, which picks the wire format of data streams and datagrams.
+    version: setup::Version,
+
     /// Optional mlog writer for logging transport events
     mlog: Option<Arc<Mutex<mlog::MlogWriter>>>,
+
+    /// The publisher of a new session to move announces to, such as after a GOAWAY.
+    migrated: State<Option<Publisher>>,
 }
 
 impl Publisher {
-    pub(crate) fn new(
+    pub(super) fn new(
         outgoing: Queue<Message>,
         webtransport: web_transport::Session,
         next_requestid: Arc<atomic::AtomicU64>,
+        auth: RequestToken,
+        version: setup::Version,
+        unknown_requests: UnknownRequestPolicy,
         mlog: Option<Arc<Mutex<mlog::MlogWriter>>>,
     ) -> Self {
         Self {
             webtransport,
             announces: Default::default(),
             subscribeds: Default::default(),
-            unknown_subscribed: Default::default(),
-            unknown_track_status_requested: Default::default(),
+            unknown_subscribed: Backlog::new(unknown_requests),
+            publishes: Default::default(),
+            subscribe_namespaces: Default::default(),
+            subscribed_namespaces: Default::default(),
+            fetches: Default::default(),
+            unknown_fetched: Default::default(),
+            unknown_track_status_requested: Backlog::new(unknown_requests),
             outgoing,
             next_requestid,
+            auth,
+            track_aliases: Default::default(),
+            version,
             mlog,
+            migrated: Default::default(),
         }
     }
 
@@ -111,15 +154,69 @@ impl Publisher {
             }
         };
 
+        let serve = Self::serve_announce(announce, tracks.clone());
+        tokio::pin!(serve);
+
+        let mut to = tokio::select! {
+            res = &mut serve => return res,
+            Some(to) = self.migrated() => to,
+        };
+
+        // Keep serving the existing requests while this session drains, and announce on the new one.
+        let (_, res) = tokio::join!(serve, Box::pin(to.announce(tracks)));
+        res
+    }
+
+    /// Attach an AUTHORIZATION_TOKEN to our future requests, such as PUBLISH_NAMESPACE, or None to stop.
+    /// The token is shared with the [super::Subscriber] of the same session.
+    pub fn set_auth_token(&mut self, token: Option<AuthToken>) {
+        self.auth.set(token)
+    }
+
+    /// The parameters for a new request, carrying our AUTHORIZATION_TOKEN if any.
+    pub(super) fn request_params(&self) -> KeyValuePairs {
+        self.auth.params()
+    }
+
+    /// Move announces onto the publisher of a new session, such as after a GOAWAY.
+    /// Each active [Publisher::announce] re-announces on the new session, and returns once that one is done.
+    pub fn migrate(&self, to: &Publisher) {
+        to.auth.set(self.auth.get());
+
+        if let Some(mut migrated) = self.migrated.lock_mut() {
+            *migrated = Some(to.clone());
+        }
+    }
+
+    async fn migrated(&self) -> Option<Publisher> {
+        loop {
+            {
+                let state = self.migrated.lock();
+                if let Some(to) = state.clone() {
+                    return Some(to);
+                }
+
+                state.modified()?
+            }
+            .await;
+        }
+    }
+
+    /// Serve the subscriptions, fetches and track status requests for an announce until it's closed.
+    async fn serve_announce(announce: Announce, tracks: TracksReader) -> Result<(), SessionError> {
         let mut subscribe_tasks = FuturesUnordered::new();
+        let mut fetch_tasks = FuturesUnordered::new();
         let mut status_tasks = FuturesUnordered::new();
         let mut subscribe_done = false;
+        let mut fetch_done = false;
         let mut status_done = false;
 
         // The code enters an infinite loop and waits for one of several events:
         // - A new subscription arrives.
+        // - A new fetch arrives.
         // - A new track status request arrives.
         // - One of the spawned subscription-handling tasks completes.
+        // - One of the spawned fetch-handling tasks completes.
         // - One of the spawned status-handling tasks completes.
         // Exit the loop when all input streams are done (None), and all tasks have completed
         loop {
@@ -141,6 +238,21 @@ impl Publisher {
                     }
 
                 },
+                res = announce.fetched(), if !fetch_done => {
+                    match res? {
+                        Some(fetched) => {
+                            let tracks = tracks.clone();
+
+                            fetch_tasks.push(async move {
+                                let info = fetched.info.clone();
+                                if let Err(err) = Self::serve_fetch(fetched, tracks).await {
+                                    log::warn!("failed serving fetch: {:?}, error: {}", info, err)
+                                }
+                            });
+                        },
+                        None => fetch_done = true,
+                    }
+                },
                 res = announce.track_status_requested(), if !status_done => {
                     match res? {
                         Some(status) => {
@@ -157,12 +269,46 @@ impl Publisher {
                     }
                 },
                 Some(res) = subscribe_tasks.next() => res,
+                Some(res) = fetch_tasks.next() => res,
                 Some(res) = status_tasks.next() => res,
                 else => return Ok(())
             }
         }
     }
 
+    /// Push a track to the peer with a PUBLISH, without waiting for a SUBSCRIBE.
+    /// Once accepted, the track is served until it ends or the peer unsubscribes.
+    pub async fn publish(&mut self, track: serve::TrackReader) -> Result<(), SessionError> {
+        // Get the current next request id to use and increment the value for by 2 for the next request
+        let request_id = self.next_requestid.fetch_add(2, atomic::Ordering::Relaxed);
+
+        let (publish, recv) = Publish::new(self.clone(), request_id, &track);
+
+        // Insert before sending, so the response can't beat us to the map.
+        self.publishes.lock().unwrap().insert(request_id, recv);
+        self.send_message(publish.request_msg.clone());
+
+        let publish_ok = publish.ok().await?;
+        let info = SubscribeInfo::new_from_publish(&publish, &publish_ok);
+
+        // The accepted publish is served just like a subscription, using the same request id.
+        let subscribed = match self.subscribeds.lock().unwrap().entry(request_id) {
+            hash_map::Entry::Occupied(_) => return Err(SessionError::Duplicate),
+            hash_map::Entry::Vacant(entry) => {
+                let (send, recv) = Subscribed::new_published(
+                    self.clone(),
+                    info,
+                    publish.track_alias(),
+                    self.mlog.clone(),
+                );
+                entry.insert(recv);
+                send
+            }
+        };
+
+        subscribed.serve(track).await
+    }
+
     pub async fn serve_subscribe(
         subscribed: Subscribed,
         mut tracks: TracksReader,
@@ -176,15 +322,27 @@ impl Publisher {
         Ok(())
     }
 
+    pub async fn serve_fetch(
+        fetched: Fetched,
+        mut tracks: TracksReader,
+    ) -> Result<(), SessionError> {
+        if let Some(track) = tracks.get_track_reader(&fetched.track_name) {
+            fetched.serve(track).await?;
+        } else {
+            fetched.close(ServeError::NotFound)?;
+        }
+
+        Ok(())
+    }
+
     pub async fn serve_track_status(
         track_status_request: TrackStatusRequested,
         mut tracks: TracksReader,
     ) -> Result<(), SessionError> {
-        let track = tracks
-            .subscribe(&track_status_request.request_msg.track_name.clone())
-            .ok_or(ServeError::NotFound)?;
-
-        track_status_request.respond_ok(&track)?;
+        match tracks.subscribe(&track_status_request.request_msg.track_name.clone()) {
+            Some(track) => track_status_request.respond_ok(&track)?,
+            None => track_status_request.respond_error(ServeError::NotFound)?,
+        }
 
         Ok(())
     }
@@ -194,21 +352,75 @@ impl Publisher {
         self.unknown_subscribed.pop().await
     }
 
+    // Returns fetches that do not map to an active announce.
+    pub async fn fetched(&mut self) -> Option<Fetched> {
+        self.unknown_fetched.pop().await
+    }
+
+    /// Returns the namespace prefixes the peer subscribes to with SUBSCRIBE_NAMESPACE.
+    /// Namespaces announced on this session are always sent to the peer, but the application should
+    /// [Publisher::announce] any others under the prefix it can serve, such as a relay's local namespaces.
+    pub async fn subscribed_namespace(&mut self) -> Option<TrackNamespace> {
+        self.subscribed_namespaces.pop().await
+    }
+
+    /// Returns true while the peer is subscribed to a prefix of the namespace.
+    pub fn is_namespace_subscribed(&self, namespace: &TrackNamespace) -> bool {
+        self.subscribe_namespaces
+            .lock()
+            .unwrap()
+            .keys()
+            .any(|prefix| namespace.has_prefix(prefix))
+    }
+
     // Returns track_status requests that do not map to an active announce.
     pub async fn track_status_requested(&mut self) -> Option<TrackStatusRequested> {
         self.unknown_track_status_requested.pop().await
     }
 
+    /// How subscriptions that do not map to an active announce were handled, per the [UnknownRequestPolicy].
+    pub fn unknown_subscribed_metrics(&self) -> UnknownRequestMetrics {
+        self.unknown_subscribed.metrics()
+    }
+
+    /// How track_status requests that do not map to an active announce were handled, per the [UnknownRequestPolicy].
+    pub fn unknown_track_status_metrics(&self) -> UnknownRequestMetrics {
+        self.unknown_track_status_requested.metrics()
+    }
+
+    /// Answer the subscriptions and track_status requests that the application didn't read in time with a timeout.
+    pub(super) async fn run_unknown(mut self) -> Result<(), SessionError> {
+        let mut track_statuses = self.unknown_track_status_requested.clone();
+
+        loop {
+            tokio::select! {
+                expired = self.unknown_subscribed.timed_out() => {
+                    log::warn!("timed out {} unread subscriptions for unknown namespaces", expired.len());
+                    for subscribed in expired {
+                        // Already closed if the peer unsubscribed in the meantime.
+                        let _ = subscribed.close(ServeError::Timeout);
+                    }
+                },
+                expired = track_statuses.timed_out() => {
+                    log::warn!("timed out {} unread track_status requests for unknown namespaces", expired.len());
+                    for track_status in expired {
+                        track_status.respond_error(ServeError::Timeout)?;
+                    }
+                },
+            }
+        }
+    }
+
     pub(crate) fn recv_message(&mut self, msg: message::Subscriber) -> Result<(), SessionError> {
         let res = match msg {
             message::Subscriber::Subscribe(msg) => self.recv_subscribe(msg),
             message::Subscriber::SubscribeUpdate(msg) => self.recv_subscribe_update(msg),
             message::Subscriber::Unsubscribe(msg) => self.recv_unsubscribe(msg),
-            message::Subscriber::Fetch(_msg) => todo!(), // TODO
-            message::Subscriber::FetchCancel(_msg) => todo!(), // TODO
+            message::Subscriber::Fetch(msg) => self.recv_fetch(msg),
+            message::Subscriber::FetchCancel(msg) => self.recv_fetch_cancel(msg),
             message::Subscriber::TrackStatus(msg) => self.recv_track_status(msg),
-            message::Subscriber::SubscribeNamespace(_msg) => todo!(), // TODO
-            message::Subscriber::UnsubscribeNamespace(_msg) => todo!(), // TODO
+            message::Subscriber::SubscribeNamespace(msg) => self.recv_subscribe_namespace(msg),
+            message::Subscriber::UnsubscribeNamespace(msg) => self.recv_unsubscribe_namespace(msg),
             message::Subscriber::PublishNamespaceCancel(msg) => {
                 self.recv_publish_namespace_cancel(msg)
             }
@@ -216,15 +428,17 @@ impl Publisher {
             message::Subscriber::PublishNamespaceError(msg) => {
                 self.recv_publish_namespace_error(msg)
             }
-            message::Subscriber::PublishOk(_msg) => todo!(), // TODO
-            message::Subscriber::PublishError(_msg) => todo!(), // TODO
+            message::Subscriber::PublishOk(msg) => self.recv_publish_ok(msg),
+            message::Subscriber::PublishError(msg) => self.recv_publish_error(msg),
         };
 
-        if let Err(err) = res {
+        // A failed request only affects that request, but anything else is a session error, such as a reused ID.
+        if let Err(SessionError::Serve(err)) = res {
             log::warn!("failed to process message: {}", err);
+            return Ok(());
         }
 
-        Ok(())
+        res
     }
 
     fn recv_publish_namespace_ok(
@@ -263,7 +477,10 @@ impl Publisher {
         if let Some(key) = key_opt {
             if let Some((_ns, v)) = announces.remove_entry(&key) {
                 // Step 3: call recv_error, consuming v
-                v.recv_error(ServeError::Closed(msg.error_code))?;
+                v.recv_error(ServeError::from_publish_namespace_error(
+                    msg.error_code.into(),
+                    &msg.reason_phrase.0,
+                ))?;
             }
         }
 
@@ -277,9 +494,80 @@ impl Publisher {
         // TODO: If a publisher receives new subscriptions for that namespace after receiving an ANNOUNCE_CANCEL,
         // it SHOULD close the session as a 'Protocol Violation'.
         if let Some(announce) = self.announces.lock().unwrap().remove(&msg.track_namespace) {
-            announce.recv_error(ServeError::Cancel)?;
+            announce.recv_error(ServeError::from_publish_namespace_error(
+                msg.error_code.into(),
+                &msg.reason_phrase.0,
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    fn recv_publish_ok(&mut self, msg: message::PublishOk) -> Result<(), SessionError> {
+        if let Some(publish) = self.publishes.lock().unwrap().remove(&msg.id) {
+            publish.recv_ok(msg)?;
+        }
+
+        Ok(())
+    }
+
+    fn recv_publish_error(&mut self, msg: message::PublishError) -> Result<(), SessionError> {
+        if let Some(publish) = self.publishes.lock().unwrap().remove(&msg.id) {
+            publish.recv_error(ServeError::from_publish_error(
+                msg.error_code.into(),
+                &msg.reason_phrase.0,
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    fn recv_subscribe_namespace(
+        &mut self,
+        msg: message::SubscribeNamespace,
+    ) -> Result<(), SessionError> {
+        let prefix = msg.track_namespace_prefix;
+        let overlap = {
+            let mut subscribe_namespaces = self.subscribe_namespaces.lock().unwrap();
+
+            // A prefix can't be a prefix of, or extend, one the peer already subscribed to.
+            let overlap = subscribe_namespaces
+                .keys()
+                .any(|existing| prefix.has_prefix(existing) || existing.has_prefix(&prefix));
+            if !overlap {
+                subscribe_namespaces.insert(prefix.clone(), msg.id);
+            }
+            overlap
+        };
+
+        if overlap {
+            let err = ServeError::Duplicate;
+            self.send_message(message::SubscribeNamespaceError {
+                id: msg.id,
+                error_code: err.subscribe_namespace_error_code().code(),
+                reason_phrase: ReasonPhrase(err.to_string()),
+            });
+            return Ok(());
         }
 
+        log::debug!("peer subscribed to namespace prefix: {}", prefix);
+        self.send_message(message::SubscribeNamespaceOk { id: msg.id });
+
+        // TODO report dropped messages?
+        let _ = self.subscribed_namespaces.push(prefix);
+
+        Ok(())
+    }
+
+    fn recv_unsubscribe_namespace(
+        &mut self,
+        msg: message::UnsubscribeNamespace,
+    ) -> Result<(), SessionError> {
+        self.subscribe_namespaces
+            .lock()
+            .unwrap()
+            .remove(&msg.track_namespace_prefix);
+
         Ok(())
     }
 
@@ -291,7 +579,9 @@ impl Publisher {
 
             // See if entry exists for this request id already, if so error out
             let entry = match subscribeds.entry(msg.id) {
-                hash_map::Entry::Occupied(_) => return Err(SessionError::Duplicate),
+                hash_map::Entry::Occupied(_) => {
+                    return Err(SessionError::DuplicateRequestId(msg.id))
+                }
                 hash_map::Entry::Vacant(entry) => entry,
             };
 
@@ -307,23 +597,118 @@ impl Publisher {
             return announce.recv_subscribe(subscribed).map_err(Into::into);
         }
 
-        // Otherwise, put it in the unknown queue.
-        // TODO Have some way to detect if the application is not reading from the unknown queue,
-        // then send SubscribeError.
+        // Otherwise, put it in the unknown backlog, unless the policy rejects it.
         if let Err(err) = self.unknown_subscribed.push(subscribed) {
-            // Default to closing with a not found error I guess.
+            log::debug!(
+                "rejecting subscribe for unknown namespace: {:?}",
+                err.info.track_namespace
+            );
             err.close(ServeError::NotFound)?;
         }
 
         Ok(())
     }
 
-    fn recv_subscribe_update(
-        &mut self,
-        _msg: message::SubscribeUpdate,
-    ) -> Result<(), SessionError> {
-        // TODO: Implement updating subscriptions.
-        Err(SessionError::Internal)
+    fn recv_subscribe_update(&mut self, msg: message::SubscribeUpdate) -> Result<(), SessionError> {
+        if let Some(subscribed) = self
+            .subscribeds
+            .lock()
+            .unwrap()
+            .get_mut(&msg.subscription_request_id)
+        {
+            subscribed.recv_update(&msg)?;
+        }
+
+        Ok(())
+    }
+
+    fn recv_fetch(&mut self, msg: message::Fetch) -> Result<(), SessionError> {
+        let mut info = match (msg.standalone_fetch, msg.joining_fetch) {
+            (Some(standalone), _) => FetchInfo::new_standalone(
+                msg.id,
+                standalone.track_namespace,
+                standalone.track_name,
+                standalone.start_location,
+                standalone.end_location,
+            ),
+            (None, Some(joining_fetch)) => {
+                // The joined subscription determines the track; the range is resolved when served.
+                let track = self
+                    .subscribeds
+                    .lock()
+                    .unwrap()
+                    .get(&joining_fetch.joining_request_id)
+                    .map(|subscribed| {
+                        (
+                            subscribed.info.track_namespace.clone(),
+                            subscribed.info.track_name.clone(),
+                        )
+                    });
+
+                match track {
+                    Some((track_namespace, track_name)) => FetchInfo::new_joining(
+                        msg.id,
+                        track_namespace,
+                        track_name,
+                        msg.fetch_type,
+                        joining_fetch,
+                    ),
+                    None => {
+                        let err = ServeError::NotFound;
+                        self.send_message(message::FetchError {
+                            id: msg.id,
+                            error_code: err.fetch_error_code().code(),
+                            reason_phrase: ReasonPhrase(err.to_string()),
+                        });
+                        return Ok(());
+                    }
+                }
+            }
+            (None, None) => return Err(DecodeError::InvalidFetchType.into()),
+        };
+        info.subscriber_priority = msg.subscriber_priority;
+        info.group_order = msg.group_order;
+        info.params = msg.params;
+
+        let namespace = info.track_namespace.clone();
+
+        let fetched = {
+            let mut fetches = self.fetches.lock().unwrap();
+
+            // See if entry exists for this request id already, if so error out
+            let entry = match fetches.entry(info.id) {
+                hash_map::Entry::Occupied(_) => {
+                    return Err(SessionError::DuplicateRequestId(info.id))
+                }
+                hash_map::Entry::Vacant(entry) => entry,
+            };
+
+            // Create new Fetched entry and add to HashMap
+            let (send, recv) = Fetched::new(self.clone(), info);
+            entry.insert(recv);
+
+            send
+        };
+
+        // If we have an announce, route the fetch to it.
+        if let Some(announce) = self.announces.lock().unwrap().get_mut(&namespace) {
+            return announce.recv_fetch(fetched).map_err(Into::into);
+        }
+
+        // Otherwise, put it in the unknown queue.
+        if let Err(err) = self.unknown_fetched.push(fetched) {
+            err.close(ServeError::NotFound)?;
+        }
+
+        Ok(())
+    }
+
+    fn recv_fetch_cancel(&mut self, msg: message::FetchCancel) -> Result<(), SessionError> {
+        if let Some(fetched) = self.fetches.lock().unwrap().get_mut(&msg.id) {
+            fetched.recv_cancel()?;
+        }
+
+        Ok(())
     }
 
     fn recv_track_status(&mut self, msg: message::TrackStatus) -> Result<(), SessionError> {
@@ -339,15 +724,16 @@ impl Publisher {
                 .map_err(Into::into);
         }
 
-        // Otherwise, put it in the unknown_track_status queue.
-        // TODO Have some way to detect if the application is not reading from the unknown_track_status queue,
-        // then send TrackStatusError.
-        if let Err(mut err) = self
+        // Otherwise, put it in the unknown_track_status backlog, unless the policy rejects it.
+        if let Err(err) = self
             .unknown_track_status_requested
             .push(track_status_requested)
         {
-            // push only fails if the queue is dropped, send  TrackStatusError, Internal error
-            err.respond_error(0, "Internal error")?;
+            log::debug!(
+                "rejecting track_status for unknown namespace: {:?}",
+                err.request_msg.track_namespace
+            );
+            err.respond_error(ServeError::NotFound)?;
         }
 
         Ok(())
@@ -370,6 +756,7 @@ impl Publisher {
         match &msg {
             message::Publisher::PublishDone(m) => self.drop_subscribe(m.id),
             message::Publisher::SubscribeError(m) => self.drop_subscribe(m.id),
+            message::Publisher::FetchError(m) => self.drop_fetch(m.id),
             message::Publisher::PublishNamespaceDone(m) => {
                 self.drop_publish_namespace(&m.track_namespace);
             }
@@ -400,6 +787,14 @@ impl Publisher {
         self.subscribeds.lock().unwrap().remove(&id);
     }
 
+    pub(super) fn drop_publish(&mut self, id: u64) {
+        self.publishes.lock().unwrap().remove(&id);
+    }
+
+    pub(super) fn drop_fetch(&mut self, id: u64) {
+        self.fetches.lock().unwrap().remove(&id);
+    }
+
     fn drop_publish_namespace(&mut self, namespace: &TrackNamespace) {
         self.announces.lock().unwrap().remove(namespace);
     }
@@ -408,6 +803,16 @@ impl Publisher {
         Ok(self.webtransport.open_uni().await?)
     }
 
+    /// The alias for a track we serve, shared by every subscription and publish of the track.
+    pub(super) fn track_alias(&self, namespace: &TrackNamespace, name: &str) -> TrackAlias {
+        self.track_aliases.acquire(namespace, name)
+    }
+
+    /// The version negotiated in SETUP, which picks the wire format of data streams and datagrams.
+    pub(super) fn version(&self) -> setup::Version {
+        self.version
+    }
+
     pub(super) async fn send_datagram(&mut self, data: bytes::Bytes) -> Result<(), SessionError> {
         Ok(self.webtransport.send_datagram(data).await?)
     }
@@ -2,24 +2,34 @@ use std::{cmp, io};
 
 use bytes::{Buf, Bytes, BytesMut};
 
-use crate::coding::{Decode, DecodeError};
+use crate::coding::{DecodeError, DecodeVersioned};
+use crate::setup;
 
 use super::SessionError;
 
 pub struct Reader {
     stream: web_transport::RecvStream,
     buffer: BytesMut,
+
+    /// The version whose wire format is decoded
+    version: setup::Version,
 }
 
 impl Reader {
-    pub fn new(stream: web_transport::RecvStream) -> Self {
+    pub fn new(stream: web_transport::RecvStream, version: setup::Version) -> Self {
         Self {
             stream,
             buffer: Default::default(),
+            version,
         }
     }
 
-    pub async fn decode<T: Decode>(&mut self) -> Result<T, SessionError> {
+    /// Switch to the version negotiated in SETUP.
+    pub fn set_version(&mut self, version: setup::Version) {
+        self.version = version;
+    }
+
+    pub async fn decode<T: DecodeVersioned>(&mut self) -> Result<T, SessionError> {
         log::trace!(
             "[READER] decode: attempting to decode {} (buffer_len={})",
             std::any::type_name::<T>(),
@@ -30,7 +40,7 @@ impl Reader {
             let mut cursor = io::Cursor::new(&self.buffer);
 
             // Try to decode with the current buffer.
-            let required = match T::decode(&mut cursor) {
+            let required = match T::decode_versioned(&mut cursor, self.version) {
                 Ok(msg) => {
                     let consumed = cursor.position() as usize;
                     self.buffer.advance(consumed);
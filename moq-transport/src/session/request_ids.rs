@@ -0,0 +1,261 @@
+use std::future::Future;
+
+use crate::message;
+use crate::watch::State;
+
+use super::SessionError;
+
+/// Request IDs advance by two, since the client and server each use every other one.
+pub(super) const REQUEST_ID_STEP: u64 = 2;
+
+struct RequestIdsState {
+    /// Our requests must use an ID below this, as advertised by the peer.
+    peer_max: u64,
+
+    /// The peer's limit we sent REQUESTS_BLOCKED for, so it's only sent once per limit.
+    blocked_at: Option<u64>,
+
+    /// The peer's requests must use an ID below this, as advertised by us.
+    local_max: u64,
+
+    /// The ID the peer's next request must use: 0 for the client and 1 for the server, increasing by two.
+    peer_next: u64,
+
+    /// Requests from the peer completed since we last sent MAX_REQUEST_ID.
+    completed: u64,
+
+    /// The peer sent REQUESTS_BLOCKED, so grant any completed requests right away.
+    peer_blocked: bool,
+}
+
+/// Tracks the MAX_REQUEST_ID flow control in both directions.
+#[derive(Clone)]
+pub(super) struct RequestIds {
+    state: State<RequestIdsState>,
+
+    /// The initial limit we advertised, used to batch grants.
+    window: u64,
+}
+
+impl RequestIds {
+    pub fn new(local_max: u64, peer_max: u64, peer_first: u64) -> Self {
+        let state = State::new(RequestIdsState {
+            peer_max,
+            blocked_at: None,
+            local_max,
+            peer_next: peer_first,
+            completed: 0,
+            peer_blocked: false,
+        });
+
+        Self {
+            state,
+            window: local_max,
+        }
+    }
+
+    /// Returns a future that resolves the next time the limits change.
+    pub fn changed(&self) -> Option<impl Future<Output = ()>> {
+        self.state.lock().modified()
+    }
+
+    /// True if the peer allows us to send a request with this ID.
+    pub fn allowed(&self, id: u64) -> bool {
+        id < self.state.lock().peer_max
+    }
+
+    /// Returns the REQUESTS_BLOCKED to send, the first time we're blocked by the peer's current limit.
+    pub fn blocked(&self) -> Option<message::RequestsBlocked> {
+        // Only take the lock mutably when there's something to change, since that wakes up anything waiting.
+        let state = self.state.lock();
+        if state.blocked_at == Some(state.peer_max) {
+            return None;
+        }
+
+        let mut state = state.into_mut()?;
+        state.blocked_at = Some(state.peer_max);
+        Some(message::RequestsBlocked {
+            max_request_id: state.peer_max,
+        })
+    }
+
+    /// Handle a MAX_REQUEST_ID from the peer, which must increase the limit.
+    pub fn recv_max(&self, max: u64) -> Result<(), SessionError> {
+        let mut state = self.state.lock_mut().ok_or(SessionError::Internal)?;
+        if max <= state.peer_max {
+            return Err(SessionError::ProtocolViolation(format!(
+                "MAX_REQUEST_ID decreased from {} to {}",
+                state.peer_max, max
+            )));
+        }
+
+        state.peer_max = max;
+        Ok(())
+    }
+
+    /// Handle a REQUESTS_BLOCKED from the peer.
+    pub fn recv_blocked(&self) {
+        if let Some(mut state) = self.state.lock_mut() {
+            state.peer_blocked = true;
+        }
+    }
+
+    /// Check a request from the peer is the next in sequence, and within the limit we advertised.
+    pub fn recv_request(&self, id: u64) -> Result<(), SessionError> {
+        let mut state = self.state.lock_mut().ok_or(SessionError::Internal)?;
+        if id != state.peer_next {
+            return Err(SessionError::InvalidRequestId(id, state.peer_next));
+        }
+
+        if id >= state.local_max {
+            return Err(SessionError::TooManyRequests(id, state.local_max));
+        }
+
+        state.peer_next += REQUEST_ID_STEP;
+        Ok(())
+    }
+
+    /// A request from the peer completed, so it can use another ID.
+    pub fn complete(&self) {
+        if let Some(mut state) = self.state.lock_mut() {
+            state.completed += 1;
+        }
+    }
+
+    /// Returns the MAX_REQUEST_ID to send, once enough requests completed to be worth a message,
+    /// or immediately if the peer is blocked.
+    pub fn grant(&self) -> Option<message::MaxRequestId> {
+        let state = self.state.lock();
+        let granted = state.completed * REQUEST_ID_STEP;
+        if granted == 0 || (granted < self.window / 2 && !state.peer_blocked) {
+            return None;
+        }
+
+        let mut state = state.into_mut()?;
+        state.local_max += granted;
+        state.completed = 0;
+        state.peer_blocked = false;
+
+        Some(message::MaxRequestId {
+            request_id: state.local_max,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocked_once_per_limit() {
+        let ids = RequestIds::new(100, 4, 1);
+        assert!(ids.allowed(0));
+        assert!(ids.allowed(2));
+        assert!(!ids.allowed(4));
+
+        assert_eq!(ids.blocked().unwrap().max_request_id, 4);
+        assert!(ids.blocked().is_none());
+
+        // A new limit can block us again.
+        ids.recv_max(8).unwrap();
+        assert!(ids.allowed(6));
+        assert!(!ids.allowed(8));
+        assert_eq!(ids.blocked().unwrap().max_request_id, 8);
+    }
+
+    #[test]
+    fn max_must_increase() {
+        let ids = RequestIds::new(100, 4, 1);
+        assert!(matches!(
+            ids.recv_max(4),
+            Err(SessionError::ProtocolViolation(_))
+        ));
+        assert!(ids.recv_max(2).is_err());
+        assert!(ids.allowed(2));
+    }
+
+    #[test]
+    fn peer_requests_limited() {
+        let ids = RequestIds::new(4, 100, 1);
+        assert!(ids.recv_request(1).is_ok());
+        assert!(ids.recv_request(3).is_ok());
+        assert!(matches!(
+            ids.recv_request(5),
+            Err(SessionError::TooManyRequests(5, 4))
+        ));
+    }
+
+    #[test]
+    fn peer_requests_in_sequence() {
+        let ids = RequestIds::new(100, 100, 0);
+        assert!(ids.recv_request(0).is_ok());
+        assert!(ids.recv_request(2).is_ok());
+
+        // Going backwards, repeating an ID or skipping ahead are all rejected.
+        for id in [0, 2, 6] {
+            assert!(matches!(
+                ids.recv_request(id),
+                Err(SessionError::InvalidRequestId(got, 4)) if got == id
+            ));
+        }
+        assert!(ids.recv_request(4).is_ok());
+    }
+
+    #[test]
+    fn peer_requests_use_their_parity() {
+        // The client's requests are even, and the server's are odd.
+        let ids = RequestIds::new(100, 100, 0);
+        assert!(matches!(
+            ids.recv_request(1),
+            Err(SessionError::InvalidRequestId(1, 0))
+        ));
+
+        let ids = RequestIds::new(100, 100, 1);
+        assert!(matches!(
+            ids.recv_request(0),
+            Err(SessionError::InvalidRequestId(0, 1))
+        ));
+        assert!(ids.recv_request(1).is_ok());
+    }
+
+    #[test]
+    fn grants_in_batches() {
+        let ids = RequestIds::new(100, 100, 1);
+        assert!(ids.grant().is_none());
+
+        // Wait for half the window before granting.
+        for _ in 0..24 {
+            ids.complete();
+        }
+        assert!(ids.grant().is_none());
+
+        ids.complete();
+        assert_eq!(ids.grant().unwrap().request_id, 150);
+        assert!(ids.grant().is_none());
+
+        // The new limit applies to the peer's requests.
+        for id in (1..150).step_by(2) {
+            assert!(ids.recv_request(id).is_ok());
+        }
+        assert!(matches!(
+            ids.recv_request(151),
+            Err(SessionError::TooManyRequests(151, 150))
+        ));
+    }
+
+    #[test]
+    fn grants_right_away_when_peer_blocked() {
+        let ids = RequestIds::new(100, 100, 1);
+
+        // Nothing to grant until a request completes.
+        ids.recv_blocked();
+        assert!(ids.grant().is_none());
+
+        ids.complete();
+        assert_eq!(ids.grant().unwrap().request_id, 102);
+
+        // Back to batching once the peer is unblocked.
+        ids.complete();
+        assert!(ids.grant().is_none());
+    }
+}
@@ -1,15 +1,23 @@
-use std::ops;
+use std::{
+    collections::HashMap,
+    future::Future,
+    ops,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tokio::time::Instant;
 
 use crate::{
     coding::{KeyValuePairs, Location, TrackNamespace},
     data,
-    message::{self, FilterType, GroupOrder},
+    message::{self, FilterType, GroupOrder, PublishDoneStatus},
     serve::{self, ServeError, TrackWriter, TrackWriterMode},
 };
 
 use crate::watch::State;
 
-use super::Subscriber;
+use super::{FetchedObject, Subscriber};
 
 // TODO rename to SubscriptionInfo when used for Publishes as well?
 #[derive(Debug, Clone)]
@@ -56,19 +64,141 @@ impl SubscribeInfo {
             track_status: false,
         }
     }
+
+    /// Build the subscription established by a PUBLISH once it's accepted with a PUBLISH_OK.
+    pub fn new_from_publish(msg: &message::Publish, ok: &message::PublishOk) -> Self {
+        Self {
+            id: msg.id,
+            track_namespace: msg.track_namespace.clone(),
+            track_name: msg.track_name.clone(),
+            subscriber_priority: ok.subscriber_priority,
+            group_order: ok.group_order,
+            forward: ok.forward,
+            filter_type: ok.filter_type,
+            start_location: ok.start_location,
+            end_group_id: ok.end_group_id,
+            params: ok.params.clone(),
+            track_status: false,
+        }
+    }
+}
+
+/// The subscription as accepted by the publisher, in SUBSCRIBE_OK or in the PUBLISH of a pushed track.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SubscribeOk {
+    /// The identifier used for the track in stream headers and datagrams.
+    pub track_alias: u64,
+
+    /// The time in milliseconds after which the subscription expires, or 0 if it never does.
+    pub expires: u64,
+
+    /// The order groups are delivered in, as resolved by the publisher.
+    pub group_order: GroupOrder,
+
+    /// The largest object published so far, or None if the track has no content yet.
+    pub largest_location: Option<Location>,
+
+    pub params: KeyValuePairs,
+}
+
+impl SubscribeOk {
+    pub fn content_exists(&self) -> bool {
+        self.largest_location.is_some()
+    }
+}
+
+impl From<&message::SubscribeOk> for SubscribeOk {
+    fn from(msg: &message::SubscribeOk) -> Self {
+        Self {
+            track_alias: msg.track_alias,
+            expires: msg.expires,
+            group_order: msg.group_order,
+            largest_location: msg.largest_location.filter(|_| msg.content_exists),
+            params: msg.params.clone(),
+        }
+    }
+}
+
+impl From<&message::Publish> for SubscribeOk {
+    fn from(msg: &message::Publish) -> Self {
+        Self {
+            track_alias: msg.track_alias,
+            expires: 0,
+            group_order: msg.group_order,
+            largest_location: msg.largest_location.filter(|_| msg.content_exists),
+            params: msg.params.clone(),
+        }
+    }
+}
+
+/// How the publisher ended the subscription, as reported in PUBLISH_DONE.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SubscribeDone {
+    pub status: PublishDoneStatus,
+
+    /// The number of data streams the publisher opened for the subscription.
+    pub stream_count: u64,
+
+    pub reason: String,
+}
+
+impl From<&message::PublishDone> for SubscribeDone {
+    fn from(msg: &message::PublishDone) -> Self {
+        Self {
+            status: msg.status_code.into(),
+            stream_count: msg.stream_count,
+            reason: msg.reason.0.clone(),
+        }
+    }
 }
 
 struct SubscribeState {
-    ok: bool,
-    track_alias: Option<u64>,
+    ok: Option<SubscribeOk>,
+
+    /// When the subscription expires, per the expires in SUBSCRIBE_OK, unless it's refreshed first.
+    expires_at: Option<Instant>,
+
+    /// Set once PUBLISH_DONE is received.
+    done: Option<SubscribeDone>,
+
+    /// Set while a joining fetch is filling in the head of the track.
+    joining: bool,
+
+    /// The subscription on a new session that replaced this one, after a GOAWAY.
+    migrated: Option<Arc<Subscribe>>,
+
+    /// Set once the session sent UNSUBSCRIBE because every reader of the track was dropped.
+    unsubscribed: bool,
+
     closed: Result<(), ServeError>,
 }
 
+impl SubscribeState {
+    /// Start the lifetime from SUBSCRIBE_OK over, as done by SUBSCRIBE_OK itself and each SUBSCRIBE_UPDATE.
+    fn restart_expiry(&mut self) {
+        self.expires_at = self
+            .ok
+            .as_ref()
+            .filter(|ok| ok.expires > 0)
+            .and_then(|ok| Instant::now().checked_add(Duration::from_millis(ok.expires)));
+    }
+
+    /// When to refresh the subscription, a quarter of its lifetime before it expires.
+    fn refresh_at(&self) -> Option<Instant> {
+        let ok = self.ok.as_ref()?;
+        Some(self.expires_at? - Duration::from_millis(ok.expires) / 4)
+    }
+}
+
 impl Default for SubscribeState {
     fn default() -> Self {
         Self {
-            ok: Default::default(),
-            track_alias: None,
+            ok: None,
+            expires_at: None,
+            done: None,
+            joining: false,
+            migrated: None,
+            unsubscribed: false,
             closed: Ok(()),
         }
     }
@@ -85,23 +215,72 @@ pub struct Subscribe {
 
 impl Subscribe {
     pub(super) fn new(
-        mut subscriber: Subscriber,
+        subscriber: Subscriber,
         request_id: u64,
         track: TrackWriter,
+        forward: bool,
     ) -> (Subscribe, SubscribeRecv) {
-        let subscribe_message = message::Subscribe {
+        let subscribe_message = Self::message(&subscriber, request_id, &track, forward);
+
+        Self::new_with(
+            subscriber,
+            subscribe_message,
+            Arc::new(Mutex::new(Some(track.into()))),
+        )
+    }
+
+    /// Subscribe starting at an absolute location, with an AbsoluteStart filter, or an AbsoluteRange
+    /// filter ending after end_group (inclusive).
+    pub(super) fn new_range(
+        subscriber: Subscriber,
+        request_id: u64,
+        track: TrackWriter,
+        start: Location,
+        end_group: Option<u64>,
+    ) -> (Subscribe, SubscribeRecv) {
+        let mut subscribe_message = Self::message(&subscriber, request_id, &track, true);
+        subscribe_message.filter_type = match end_group {
+            Some(_) => FilterType::AbsoluteRange,
+            None => FilterType::AbsoluteStart,
+        };
+        subscribe_message.start_location = Some(start);
+        subscribe_message.end_group_id = end_group;
+
+        Self::new_with(
+            subscriber,
+            subscribe_message,
+            Arc::new(Mutex::new(Some(track.into()))),
+        )
+    }
+
+    /// A SUBSCRIBE for the track starting at the largest object.
+    fn message(
+        subscriber: &Subscriber,
+        request_id: u64,
+        track: &TrackWriter,
+        forward: bool,
+    ) -> message::Subscribe {
+        message::Subscribe {
             id: request_id,
             track_namespace: track.namespace.clone(),
             track_name: track.name.clone(),
             // TODO add prioritization logic on the publisher side
             subscriber_priority: 127, // default to mid value, see: https://github.com/moq-wg/moq-transport/issues/504
             group_order: GroupOrder::Publisher, // defer to publisher send order
-            forward: true,            // default to forwarding objects
+            forward,
             filter_type: FilterType::LargestObject,
             start_location: None,
             end_group_id: None,
-            params: Default::default(),
-        };
+            params: subscriber.request_params(),
+        }
+    }
+
+    /// Send the SUBSCRIBE, writing to a writer that may be shared with a migrated subscription.
+    fn new_with(
+        mut subscriber: Subscriber,
+        subscribe_message: message::Subscribe,
+        writer: Arc<Mutex<Option<TrackWriterMode>>>,
+    ) -> (Subscribe, SubscribeRecv) {
         let info = SubscribeInfo::new_from_subscribe(&subscribe_message);
 
         subscriber.send_message(subscribe_message);
@@ -111,22 +290,260 @@ impl Subscribe {
         let send = Subscribe {
             state: send,
             subscriber,
+            info: info.clone(),
+        };
+
+        let recv = SubscribeRecv {
+            state: recv,
             info,
+            writer,
+            latest_group: None,
+            migrated: false,
+            joined: HashMap::new(),
+        };
+
+        (send, recv)
+    }
+
+    /// Create the subscription for an accepted PUBLISH; there's no SUBSCRIBE to send.
+    pub(super) fn new_published(
+        subscriber: Subscriber,
+        info: SubscribeInfo,
+        ok: SubscribeOk,
+        track: TrackWriter,
+    ) -> (Subscribe, SubscribeRecv) {
+        let (send, recv) = State::new(SubscribeState {
+            ok: Some(ok),
+            ..Default::default()
+        })
+        .split();
+
+        let send = Subscribe {
+            state: send,
+            subscriber,
+            info: info.clone(),
         };
 
         let recv = SubscribeRecv {
             state: recv,
-            writer: Some(track.into()),
+            info,
+            writer: Arc::new(Mutex::new(Some(track.into()))),
+            latest_group: None,
+            migrated: false,
+            joined: HashMap::new(),
         };
 
         (send, recv)
     }
 
-    pub async fn closed(&self) -> Result<(), ServeError> {
+    /// Update the subscription with a SUBSCRIBE_UPDATE.  The range can only be narrowed: the start can't move
+    /// backwards, and the end group, inclusive, can't be extended or removed once set.
+    pub fn update(
+        &mut self,
+        start: Location,
+        end_group: Option<u64>,
+        priority: u8,
+        forward: bool,
+    ) -> Result<(), ServeError> {
+        let state = self.state.lock();
+        if state.migrated.is_some() {
+            return Err(ServeError::NotSupported(
+                "subscription was migrated to a new session".to_string(),
+            ));
+        }
+        state.closed.clone()?;
+        drop(state);
+
+        if start < self.start_location() {
+            return Err(ServeError::NotSupported(
+                "subscribe update can't move the start backwards".to_string(),
+            ));
+        }
+
+        if let Some(current) = self.info.end_group_id {
+            if end_group.is_none_or(|end| end > current) {
+                return Err(ServeError::NotSupported(
+                    "subscribe update can't extend the end group".to_string(),
+                ));
+            }
+        }
+
+        self.info.start_location = Some(start);
+        self.info.end_group_id = end_group;
+        self.info.subscriber_priority = priority;
+        self.info.forward = forward;
+        self.send_update();
+
+        Ok(())
+    }
+
+    /// Refresh the subscription with a SUBSCRIBE_UPDATE that keeps the current range, priority and forward flag,
+    /// restarting its expiry timer.  Once migrated to a new session, this refreshes the new subscription.
+    pub fn refresh(&self) -> Result<(), ServeError> {
+        let state = self.state.lock();
+        if let Some(migrated) = state.migrated.clone() {
+            drop(state);
+            return migrated.refresh();
+        }
+        state.closed.clone()?;
+        drop(state);
+
+        self.send_update();
+
+        Ok(())
+    }
+
+    /// The start of the subscription.  For filters relative to the largest object, it's resolved the same way
+    /// as the publisher does, from the largest location in SUBSCRIBE_OK, so an update doesn't move it.
+    fn start_location(&self) -> Location {
+        if let Some(start) = self.info.start_location {
+            return start;
+        }
+
+        let largest = self
+            .state
+            .lock()
+            .ok
+            .as_ref()
+            .and_then(|ok| ok.largest_location);
+
+        match (self.info.filter_type, largest) {
+            (FilterType::NextGroupStart, Some(largest)) => Location::new(largest.group_id + 1, 0),
+            (_, Some(largest)) => Location::new(largest.group_id, largest.object_id + 1),
+            (_, None) => Location::default(),
+        }
+    }
+
+    /// Send a SUBSCRIBE_UPDATE with the current info.  Any SUBSCRIBE_UPDATE refreshes the subscription.
+    fn send_update(&self) {
+        let mut subscriber = self.subscriber.clone();
+        let id = subscriber.get_next_request_id();
+        subscriber.send_message(message::SubscribeUpdate {
+            id,
+            subscription_request_id: self.info.id,
+            start_location: self.start_location(),
+            end_group_id: self.info.end_group_id.map(|end| end + 1).unwrap_or(0),
+            subscriber_priority: self.info.subscriber_priority,
+            forward: self.info.forward,
+            params: Default::default(),
+        });
+
+        if let Some(mut state) = self.state.lock_mut() {
+            state.restart_expiry();
+        }
+    }
+
+    /// Pause or resume forwarding with a SUBSCRIBE_UPDATE, keeping the current range and priority.
+    /// The publisher resumes at the next group.
+    pub fn set_forward(&mut self, forward: bool) -> Result<(), ServeError> {
+        self.update(
+            self.start_location(),
+            self.info.end_group_id,
+            self.info.subscriber_priority,
+            forward,
+        )
+    }
+
+    /// Wait until the publisher accepts the subscription, returning the details from SUBSCRIBE_OK.
+    /// Returns an error with the SUBSCRIBE_ERROR code if it was rejected.  Objects keep being written to the
+    /// track afterwards, until [Self::closed].  Once migrated to a new session, this waits on the new subscription.
+    pub async fn ok(&self) -> Result<SubscribeOk, ServeError> {
+        let migrated = loop {
+            {
+                let state = self.state.lock();
+                if let Some(migrated) = state.migrated.clone() {
+                    break migrated;
+                }
+
+                if let Some(ok) = &state.ok {
+                    return Ok(ok.clone());
+                }
+
+                state.closed.clone()?;
+
+                match state.modified() {
+                    Some(notify) => notify,
+                    None => return Err(ServeError::Cancel),
+                }
+            }
+            .await;
+        };
+
+        Box::pin(migrated.ok()).await
+    }
+
+    /// Wait until the subscription expires, per the expires in SUBSCRIBE_OK, unless it's refreshed first.
+    /// Never resolves if the subscription doesn't expire or is closed first; the publisher ends an expired
+    /// subscription with PUBLISH_DONE, so [Self::closed] returns [ServeError::Expired].
+    /// Once migrated to a new session, this waits on the new subscription.
+    pub async fn expired(&self) {
+        self.expiry(false).await
+    }
+
+    /// Wait until the subscription is closed, like [Self::closed], refreshing it with a SUBSCRIBE_UPDATE
+    /// before each time it would expire.
+    pub async fn refresh_until_closed(&self) -> Result<(), ServeError> {
         loop {
+            tokio::select! {
+                res = self.closed() => return res,
+                _ = self.expiry(true) => self.refresh()?,
+            }
+        }
+    }
+
+    /// Wait until the subscription expires, or until it's time to refresh it if early is set.
+    async fn expiry(&self, early: bool) {
+        let migrated = loop {
+            let (deadline, changed) = {
+                let state = self.state.lock();
+                if let Some(migrated) = state.migrated.clone() {
+                    break migrated;
+                }
+
+                let deadline = match early {
+                    true => state.refresh_at(),
+                    false => state.expires_at,
+                };
+                (deadline, state.modified())
+            };
+
+            match (deadline, changed) {
+                (Some(deadline), Some(changed)) => tokio::select! {
+                    _ = tokio::time::sleep_until(deadline) => return,
+                    _ = changed => {},
+                },
+                (None, Some(changed)) => changed.await,
+                (_, None) => return std::future::pending().await,
+            }
+        };
+
+        Box::pin(migrated.expiry(early)).await
+    }
+
+    /// How the publisher ended the subscription, once PUBLISH_DONE is received, including the number of
+    /// streams it opened.  Once migrated to a new session, this is the new subscription's.
+    pub fn done(&self) -> Option<SubscribeDone> {
+        let state = self.state.lock();
+        match &state.migrated {
+            Some(migrated) => migrated.done(),
+            None => state.done.clone(),
+        }
+    }
+
+    /// Wait until the subscription is closed, including when the session unsubscribed because every reader of
+    /// the track was dropped.  Once migrated to a new session, this waits on the new subscription.
+    pub async fn closed(&self) -> Result<(), ServeError> {
+        let migrated = loop {
             {
                 let state = self.state.lock();
+                if let Some(migrated) = state.migrated.clone() {
+                    break migrated;
+                }
+
                 state.closed.clone()?;
+                if state.unsubscribed {
+                    return Ok(());
+                }
 
                 match state.modified() {
                     Some(notify) => notify,
@@ -134,14 +551,26 @@ impl Subscribe {
                 }
             }
             .await;
-        }
+        };
+
+        Box::pin(migrated.closed()).await
     }
 }
 
 impl Drop for Subscribe {
     fn drop(&mut self) {
-        self.subscriber
-            .send_message(message::Unsubscribe { id: self.info.id });
+        // The session already unsubscribed once the track was unused.
+        if !self.state.lock().unsubscribed {
+            self.subscriber
+                .send_message(message::Unsubscribe { id: self.info.id });
+        }
+
+        // Unsubscribe on the new session too, since the application is done with the track.
+        let migrated = self
+            .state
+            .lock_mut()
+            .and_then(|mut state| state.migrated.take());
+        drop(migrated);
     }
 }
 
@@ -155,19 +584,33 @@ impl ops::Deref for Subscribe {
 
 pub(super) struct SubscribeRecv {
     state: State<SubscribeState>,
-    writer: Option<TrackWriterMode>,
+    info: SubscribeInfo,
+
+    /// Shared with the subscription on the new session after a GOAWAY, so both can write to the track.
+    writer: Arc<Mutex<Option<TrackWriterMode>>>,
+
+    /// The largest group received so far.
+    latest_group: Option<u64>,
+
+    /// Set once migrated to a new session.  Only the groups up to latest_group are still written,
+    /// the rest come from the new session.
+    migrated: bool,
+
+    /// Subgroups started by a joining fetch, keyed by (group_id, subgroup_id), along with the
+    /// next object_id expected.  Live streams for the same subgroup continue these writers.
+    joined: HashMap<(u64, u64), (serve::SubgroupWriter, u64)>,
 }
 
 impl SubscribeRecv {
-    pub fn ok(&mut self, alias: u64) -> Result<(), ServeError> {
+    pub fn ok(&mut self, msg: &message::SubscribeOk) -> Result<(), ServeError> {
         let state = self.state.lock();
-        if state.ok {
+        if state.ok.is_some() {
             return Err(ServeError::Duplicate);
         }
 
         if let Some(mut state) = state.into_mut() {
-            state.ok = true;
-            state.track_alias = Some(alias);
+            state.ok = Some(msg.into());
+            state.restart_expiry();
         }
 
         Ok(())
@@ -175,12 +618,90 @@ impl SubscribeRecv {
 
     pub fn track_alias(&self) -> Option<u64> {
         let state = self.state.lock();
-        state.track_alias
+        state.ok.as_ref().map(|ok| ok.track_alias)
+    }
+
+    /// The publisher ended the subscription with a PUBLISH_DONE.
+    pub fn done(self, msg: &message::PublishDone) -> Result<(), ServeError> {
+        if let Some(mut state) = self.state.lock_mut() {
+            state.done = Some(msg.into());
+        }
+
+        self.error(ServeError::from_publish_done(
+            msg.status_code.into(),
+            &msg.reason.0,
+        ))
+    }
+
+    pub fn info(&self) -> &SubscribeInfo {
+        &self.info
+    }
+
+    /// True if this subscription is for the given track.
+    pub fn is_track(&self, namespace: &TrackNamespace, name: &str) -> bool {
+        self.info.track_namespace == *namespace && self.info.track_name == name
+    }
+
+    /// The session sent UNSUBSCRIBE because every reader of the track was dropped.
+    pub fn unsubscribe(self) {
+        if !self.migrated {
+            self.writer.lock().unwrap().take();
+        }
+
+        if let Some(mut state) = self.state.lock_mut() {
+            state.unsubscribed = true;
+        }
+    }
+
+    /// Returns a future that resolves with true once every reader of the track has been dropped,
+    /// or with false if the writer is closed first.
+    pub fn unused(&self) -> impl Future<Output = bool> {
+        let writer = self.writer.clone();
+
+        async move {
+            loop {
+                // The writer changes mode on the first object, so wait on whichever is current.
+                let unused = match writer.lock().unwrap().as_ref() {
+                    Some(writer) if writer.is_unused() => return true,
+                    Some(writer) => writer.unused(),
+                    None => return false,
+                };
+
+                unused.await;
+            }
+        }
+    }
+
+    /// Returns a future that resolves once we've unsubscribed, so the streams for the subscription can be stopped.
+    pub fn unsubscribed(&self) -> impl Future<Output = ()> {
+        let state = self.state.clone();
+
+        async move {
+            loop {
+                let notify = {
+                    let state = state.lock();
+                    if state.unsubscribed {
+                        return;
+                    }
+
+                    // The application dropped its handle, which sends UNSUBSCRIBE.
+                    match state.modified() {
+                        Some(notify) => notify,
+                        None => return,
+                    }
+                };
+
+                notify.await;
+            }
+        }
     }
 
-    pub fn error(mut self, err: ServeError) -> Result<(), ServeError> {
-        if let Some(writer) = self.writer.take() {
-            writer.close(err.clone())?;
+    pub fn error(self, err: ServeError) -> Result<(), ServeError> {
+        // The track lives on in the new session once migrated.
+        if !self.migrated {
+            if let Some(writer) = self.writer.lock().unwrap().take() {
+                writer.close(err.clone())?;
+            }
         }
 
         let state = self.state.lock();
@@ -192,11 +713,39 @@ impl SubscribeRecv {
         Ok(())
     }
 
+    /// Returns the writer for a live subgroup stream, along with the first object_id to keep.
+    /// Objects before it were already delivered by a joining fetch.
     pub fn subgroup(
         &mut self,
         header: data::SubgroupHeader,
+    ) -> Result<(serve::SubgroupWriter, u64), ServeError> {
+        // When subgroup_id is not present in the header type, it implicitly means subgroup 0
+        let subgroup_id = header.subgroup_id.unwrap_or(0);
+
+        if self.migrated_away(header.group_id) {
+            return Err(ServeError::Done);
+        }
+
+        // Continue where the joining fetch left off.
+        if let Some(joined) = self.joined.remove(&(header.group_id, subgroup_id)) {
+            return Ok(joined);
+        }
+
+        let writer = self.create_subgroup(serve::Subgroup {
+            group_id: header.group_id,
+            subgroup_id,
+            priority: header.publisher_priority,
+        })?;
+
+        Ok((writer, 0))
+    }
+
+    fn create_subgroup(
+        &mut self,
+        subgroup: serve::Subgroup,
     ) -> Result<serve::SubgroupWriter, ServeError> {
-        let writer = self.writer.take().ok_or(ServeError::Done)?;
+        let mut slot = self.writer.lock().unwrap();
+        let writer = slot.take().ok_or(ServeError::Done)?;
 
         let mut subgroups = match writer {
             // TODO SLG - understand why both of these are needed, clock demo won't run if I comment out TrackWriteMode::Track
@@ -205,20 +754,123 @@ impl SubscribeRecv {
             _ => return Err(ServeError::Mode),
         };
 
-        let writer = subgroups.create(serve::Subgroup {
-            group_id: header.group_id,
-            // When subgroup_id is not present in the header type, it implicitly means subgroup 0
-            subgroup_id: header.subgroup_id.unwrap_or(0),
-            priority: header.publisher_priority,
-        })?;
+        let group_id = subgroup.group_id;
+        let writer = subgroups.create(subgroup);
+        *slot = Some(subgroups.into());
+
+        self.latest_group = self.latest_group.max(Some(group_id));
+
+        writer
+    }
+
+    /// True if the group is served by the new session this subscription was migrated to.
+    fn migrated_away(&self, group_id: u64) -> bool {
+        self.migrated && self.latest_group.is_none_or(|latest| group_id > latest)
+    }
+
+    /// Re-issue the subscription on the subscriber of a new session, such as after a GOAWAY, starting at the
+    /// next group.  The end group isn't carried over.  Returns the new subscription to track, unless this one
+    /// is already closed, migrated, or no longer wanted by the application.
+    pub fn migrate(&mut self, to: Subscriber) -> Option<SubscribeRecv> {
+        if self.migrated {
+            return None;
+        }
+
+        let mut state = self.state.lock_mut()?;
+        if state.closed.is_err() {
+            return None;
+        }
+
+        let subscribe_message = message::Subscribe {
+            id: to.get_next_request_id(),
+            track_namespace: self.info.track_namespace.clone(),
+            track_name: self.info.track_name.clone(),
+            subscriber_priority: self.info.subscriber_priority,
+            group_order: self.info.group_order,
+            forward: self.info.forward,
+            filter_type: FilterType::NextGroupStart,
+            start_location: None,
+            end_group_id: None,
+            // Any token alias we registered belongs to the old session.
+            params: to.request_params(),
+        };
+
+        let (send, recv) = Subscribe::new_with(to, subscribe_message, self.writer.clone());
+        state.migrated = Some(Arc::new(send));
+        self.migrated = true;
+
+        Some(recv)
+    }
+
+    pub fn id(&self) -> u64 {
+        self.info.id
+    }
+
+    /// Mark the subscription as being joined by a fetch; live subgroups wait until it's done.
+    pub fn join(&mut self) -> Result<(), ServeError> {
+        let mut state = self.state.lock_mut().ok_or(ServeError::Cancel)?;
+        state.joining = true;
+
+        Ok(())
+    }
+
+    /// Returns a future that resolves on the next state change, if the joining fetch is still running.
+    pub fn joining(&self) -> Option<impl Future<Output = ()>> {
+        let state = self.state.lock();
+        if !state.joining {
+            return None;
+        }
+
+        state.modified()
+    }
+
+    /// Write an object received by the joining fetch in front of the live objects.
+    pub fn fetched(&mut self, object: FetchedObject) -> Result<(), ServeError> {
+        let key = (object.group_id, object.subgroup_id);
+
+        let (mut writer, _) = match self.joined.remove(&key) {
+            Some(joined) => joined,
+            None => {
+                let writer = self.create_subgroup(serve::Subgroup {
+                    group_id: object.group_id,
+                    subgroup_id: object.subgroup_id,
+                    priority: object.priority,
+                })?;
+                (writer, 0)
+            }
+        };
+
+        match object.status {
+            data::ObjectStatus::NormalObject => {
+                writer.write_at(object.object_id, object.payload, object.extension_headers)?
+            }
+            status => writer.write_status_at(object.object_id, status, object.extension_headers)?,
+        }
+        self.joined.insert(key, (writer, object.object_id + 1));
+
+        Ok(())
+    }
+
+    /// The joining fetch is done, so release any live subgroups that were waiting on it.
+    pub fn joined(&mut self) -> Result<(), ServeError> {
+        // Only the latest group can be continued by a live stream; finish the rest.
+        if let Some(latest) = self.joined.keys().map(|(group_id, _)| *group_id).max() {
+            self.joined.retain(|(group_id, _), _| *group_id == latest);
+        }
 
-        self.writer = Some(subgroups.into());
+        let mut state = self.state.lock_mut().ok_or(ServeError::Cancel)?;
+        state.joining = false;
 
-        Ok(writer)
+        Ok(())
     }
 
     pub fn datagram(&mut self, datagram: data::Datagram) -> Result<(), ServeError> {
-        let writer = self.writer.take().ok_or(ServeError::Done)?;
+        if self.migrated_away(datagram.group_id) {
+            return Ok(());
+        }
+
+        let mut slot = self.writer.lock().unwrap();
+        let writer = slot.take().ok_or(ServeError::Done)?;
 
         let mut datagrams = match writer {
             TrackWriterMode::Track(track) => track.datagrams()?, // TODO SLG - is this needed?
@@ -232,10 +884,14 @@ impl SubscribeRecv {
             // When object_id is not present in the datagram type, it implicitly means object 0
             object_id: datagram.object_id.unwrap_or(0),
             priority: datagram.publisher_priority,
-            // TODO: Handle status datagrams separately - they don't have payload
-            // For now, use empty bytes as fallback (shouldn't happen in practice for payload datagrams)
+            // Status datagrams don't have a payload.
             payload: datagram.payload.unwrap_or_default(),
+            status: datagram.status.unwrap_or(data::ObjectStatus::NormalObject),
+            extension_headers: datagram.extension_headers.unwrap_or_default(),
         })?;
+        *slot = Some(datagrams.into());
+
+        self.latest_group = self.latest_group.max(Some(datagram.group_id));
 
         Ok(())
     }
@@ -0,0 +1,167 @@
+use std::{collections::VecDeque, ops};
+
+use crate::coding::TrackNamespace;
+use crate::watch::State;
+use crate::{message, serve::ServeError};
+
+use super::{Announced, Subscriber};
+
+#[derive(Debug, Clone)]
+pub struct SubscribeNamespaceInfo {
+    pub request_id: u64,
+    pub namespace_prefix: TrackNamespace,
+}
+
+struct SubscribeNamespaceState {
+    announced: VecDeque<Announced>,
+    ok: bool,
+    closed: Result<(), ServeError>,
+}
+
+impl Default for SubscribeNamespaceState {
+    fn default() -> Self {
+        Self {
+            announced: Default::default(),
+            ok: false,
+            closed: Ok(()),
+        }
+    }
+}
+
+// Held by the application
+#[must_use = "unsubscribe namespace on drop"]
+pub struct SubscribeNamespace {
+    subscriber: Subscriber,
+    state: State<SubscribeNamespaceState>,
+
+    pub info: SubscribeNamespaceInfo,
+}
+
+impl SubscribeNamespace {
+    pub(super) fn new(
+        mut subscriber: Subscriber,
+        request_id: u64,
+        namespace_prefix: TrackNamespace,
+    ) -> (SubscribeNamespace, SubscribeNamespaceRecv) {
+        subscriber.send_message(message::SubscribeNamespace {
+            id: request_id,
+            track_namespace_prefix: namespace_prefix.clone(),
+            params: subscriber.request_params(),
+        });
+
+        let info = SubscribeNamespaceInfo {
+            request_id,
+            namespace_prefix,
+        };
+
+        let (send, recv) = State::default().split();
+
+        let send = Self {
+            subscriber,
+            state: send,
+            info,
+        };
+        let recv = SubscribeNamespaceRecv { state: recv };
+
+        (send, recv)
+    }
+
+    // Wait until an OK is received
+    pub async fn ok(&self) -> Result<(), ServeError> {
+        loop {
+            {
+                let state = self.state.lock();
+                if state.ok {
+                    return Ok(());
+                }
+                state.closed.clone()?;
+
+                match state.modified() {
+                    Some(notified) => notified,
+                    None => return Err(ServeError::Cancel),
+                }
+            }
+            .await;
+        }
+    }
+
+    /// Wait until a namespace matching the prefix is announced
+    pub async fn announced(&mut self) -> Result<Option<Announced>, ServeError> {
+        loop {
+            {
+                let state = self.state.lock();
+                if !state.announced.is_empty() {
+                    return Ok(state
+                        .into_mut()
+                        .and_then(|mut state| state.announced.pop_front()));
+                }
+
+                state.closed.clone()?;
+                match state.modified() {
+                    Some(notified) => notified,
+                    None => return Ok(None),
+                }
+            }
+            .await;
+        }
+    }
+}
+
+impl Drop for SubscribeNamespace {
+    fn drop(&mut self) {
+        if self.state.lock().closed.is_err() {
+            return;
+        }
+
+        self.subscriber.send_message(message::UnsubscribeNamespace {
+            track_namespace_prefix: self.namespace_prefix.clone(),
+        });
+    }
+}
+
+impl ops::Deref for SubscribeNamespace {
+    type Target = SubscribeNamespaceInfo;
+
+    fn deref(&self) -> &Self::Target {
+        &self.info
+    }
+}
+
+pub(super) struct SubscribeNamespaceRecv {
+    state: State<SubscribeNamespaceState>,
+}
+
+impl SubscribeNamespaceRecv {
+    pub fn recv_ok(&mut self) -> Result<(), ServeError> {
+        if let Some(mut state) = self.state.lock_mut() {
+            if state.ok {
+                return Err(ServeError::Duplicate);
+            }
+
+            state.ok = true;
+        }
+
+        Ok(())
+    }
+
+    pub fn recv_error(self, err: ServeError) -> Result<(), ServeError> {
+        let state = self.state.lock();
+        state.closed.clone()?;
+
+        let mut state = state.into_mut().ok_or(ServeError::Done)?;
+        state.closed = Err(err);
+
+        Ok(())
+    }
+
+    /// Hands the announced namespace back if the application is no longer listening.
+    pub fn recv_announced(&mut self, announced: Announced) -> Option<Announced> {
+        match self.state.lock_mut() {
+            Some(mut state) => {
+                state.announced.push_back(announced);
+                None
+            }
+            None => Some(announced),
+        }
+    }
+}
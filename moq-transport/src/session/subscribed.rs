@@ -1,26 +1,99 @@
+use std::future::Future;
 use std::ops;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
+use tokio::time::Instant;
+
+use futures::future::BoxFuture;
 use futures::stream::FuturesUnordered;
-use futures::StreamExt;
+use futures::{FutureExt, StreamExt};
 
-use crate::coding::{Encode, KeyValuePairs, Location, ReasonPhrase};
+use crate::coding::{EncodeVersioned, Location, ReasonPhrase};
+use crate::message::FilterType;
 use crate::mlog;
 use crate::serve::{ServeError, TrackReaderMode};
 use crate::watch::State;
 use crate::{data, message, serve};
 
-use super::{Publisher, SessionError, SubscribeInfo, Writer};
+use super::{Publisher, SessionError, SubscribeInfo, TrackAlias, Writer};
 
 // This file defines Publisher handling of inbound Subscriptions
 
 #[derive(Debug)]
 struct SubscribedState {
     largest_location: Option<Location>,
+
+    /// Objects before this location are not delivered, if set.
+    start_location: Option<Location>,
+    /// The last group delivered, inclusive, if set.
+    end_group_id: Option<u64>,
+
+    /// Subscriber priority, applied to newly opened streams.
+    subscriber_priority: u8,
+    /// No new objects are sent while false.  Subgroups skipped while paused aren't resumed, so
+    /// forwarding picks up again at the next group.
+    forward: bool,
+
+    /// How long the subscription lasts after SUBSCRIBE_OK or the last SUBSCRIBE_UPDATE, if it expires.
+    expires: Option<Duration>,
+    /// When the subscription expires, unless it's refreshed first.
+    expires_at: Option<Instant>,
+
+    /// The number of data streams opened, reported in PUBLISH_DONE.
+    stream_count: u64,
+
     closed: Result<(), ServeError>,
 }
 
 impl SubscribedState {
+    fn new(info: &SubscribeInfo) -> Self {
+        Self {
+            largest_location: None,
+            start_location: None,
+            end_group_id: None,
+            subscriber_priority: info.subscriber_priority,
+            forward: info.forward,
+            expires: None,
+            expires_at: None,
+            stream_count: 0,
+            closed: Ok(()),
+        }
+    }
+
+    /// Apply a SUBSCRIBE_UPDATE.  The range can only be narrowed, so a start before the current
+    /// one or an end after the current one is ignored.
+    fn update(&mut self, msg: &message::SubscribeUpdate) {
+        self.start_location = Some(match self.start_location {
+            Some(start) => start.max(msg.start_location),
+            None => msg.start_location,
+        });
+
+        // The end group is sent plus one, with 0 meaning open-ended.
+        if let Some(end) = msg.end_group_id.checked_sub(1) {
+            self.end_group_id = Some(match self.end_group_id {
+                Some(current) => current.min(end),
+                None => end,
+            });
+        }
+
+        self.subscriber_priority = msg.subscriber_priority;
+        self.forward = msg.forward;
+
+        // Any SUBSCRIBE_UPDATE refreshes the subscription.
+        self.expires_at = self.expires.map(|expires| Instant::now() + expires);
+    }
+
+    /// True if the group is past the end of the subscription.
+    fn is_after_end(&self, group_id: u64) -> bool {
+        self.end_group_id.is_some_and(|end| group_id > end)
+    }
+
+    /// True if the object is before the start of the subscription.
+    fn is_before_start(&self, location: Location) -> bool {
+        self.start_location.is_some_and(|start| location < start)
+    }
+
     fn update_largest_location(&mut self, group_id: u64, object_id: u64) -> Result<(), ServeError> {
         if let Some(current_largest_location) = self.largest_location {
             let update_largest_location = Location::new(group_id, object_id);
@@ -33,15 +106,6 @@ impl SubscribedState {
     }
 }
 
-impl Default for SubscribedState {
-    fn default() -> Self {
-        Self {
-            largest_location: None,
-            closed: Ok(()),
-        }
-    }
-}
-
 pub struct Subscribed {
     /// The sessions Publisher manager, used to send control messages,
     /// create new QUIC streams, and send datagrams
@@ -50,6 +114,9 @@ pub struct Subscribed {
     /// The tracknamespace and trackname for the subscription.
     pub info: SubscribeInfo,
 
+    /// The alias used in SUBSCRIBE_OK and the stream headers, shared with a PUBLISH of the same track.
+    track_alias: TrackAlias,
+
     state: State<SubscribedState>,
 
     /// Tracks if SubscribeOk has been sent yet or not. Used to send
@@ -66,18 +133,44 @@ impl Subscribed {
         msg: message::Subscribe,
         mlog: Option<Arc<Mutex<mlog::MlogWriter>>>,
     ) -> (Self, SubscribedRecv) {
-        let (send, recv) = State::default().split();
         let info = SubscribeInfo::new_from_subscribe(&msg);
+        let track_alias = publisher.track_alias(&info.track_namespace, &info.track_name);
+        let (send, recv) = State::new(SubscribedState::new(&info)).split();
         let send = Self {
             publisher,
+            track_alias,
             state: send,
-            info,
+            info: info.clone(),
             ok: false,
             mlog,
         };
 
         // Prevents updates after being closed
-        let recv = SubscribedRecv { state: recv };
+        let recv = SubscribedRecv { state: recv, info };
+
+        (send, recv)
+    }
+
+    /// Create the subscription for a PUBLISH that the peer accepted.  PUBLISH_OK already established it,
+    /// so no SUBSCRIBE_OK is sent.
+    pub(super) fn new_published(
+        publisher: Publisher,
+        info: SubscribeInfo,
+        track_alias: TrackAlias,
+        mlog: Option<Arc<Mutex<mlog::MlogWriter>>>,
+    ) -> (Self, SubscribedRecv) {
+        let (send, recv) = State::new(SubscribedState::new(&info)).split();
+        let send = Self {
+            publisher,
+            track_alias,
+            state: send,
+            info: info.clone(),
+            ok: true,
+            mlog,
+        };
+
+        // Prevents updates after being closed
+        let recv = SubscribedRecv { state: recv, info };
 
         (send, recv)
     }
@@ -94,34 +187,135 @@ impl Subscribed {
     async fn serve_inner(&mut self, track: serve::TrackReader) -> Result<(), SessionError> {
         // Update largest location before sending SubscribeOk
         let largest_location = track.largest_location();
-        self.state
-            .lock_mut()
-            .ok_or(ServeError::Cancel)?
-            .largest_location = largest_location;
-
-        // Send SubscribeOk using send_message_and_wait to ensure it is sent at least to the QUIC stack before
-        // we start serving the track.  If a subscriber gets the stream before SubscribeOk
-        // then they won't recognize the track_alias in the stream header.
-        self.publisher
-            .send_message_and_wait(message::SubscribeOk {
-                id: self.info.id,
-                track_alias: self.info.id, // use subscription id as track alias
-                expires: 0,                // TODO SLG
-                group_order: message::GroupOrder::Descending, // TODO: resolve correct value from publisher / subscriber prefs
-                content_exists: largest_location.is_some(),
-                largest_location,
-                params: Default::default(),
-            })
-            .await;
+        let start_location = self.start_location(largest_location)?;
+
+        // Groups before the largest one are only available from the cache.
+        let cached = match (start_location, largest_location) {
+            (Some(start), Some(largest)) if start.group_id < largest.group_id => {
+                match track.mode().await? {
+                    TrackReaderMode::Subgroups(subgroups) => {
+                        // The start has already been evicted from the cache.
+                        if subgroups
+                            .oldest_cached()
+                            .is_none_or(|oldest| oldest > start.group_id)
+                        {
+                            return Err(ServeError::InvalidRange.into());
+                        }
+
+                        let end = match self.info.end_group_id {
+                            Some(end) => end.min(largest.group_id - 1),
+                            None => largest.group_id - 1,
+                        };
+                        subgroups.cached(start.group_id, end)
+                    }
+                    _ => return Err(ServeError::InvalidRange.into()),
+                }
+            }
+            _ => Vec::new(),
+        };
+
+        {
+            let mut state = self.state.lock_mut().ok_or(ServeError::Cancel)?;
+            state.largest_location = largest_location;
+            // Keep any narrowing already applied by a SUBSCRIBE_UPDATE.
+            state.start_location = state.start_location.max(start_location);
+            state.end_group_id = state.end_group_id.or(self.info.end_group_id);
+        }
+
+        let group_order = self.info.group_order.resolve(track.group_order);
 
-        self.ok = true; // So we send SubscribeDone on drop
+        // Subscriptions established by an accepted PUBLISH have nothing more to acknowledge.
+        if !self.ok {
+            // The lifetime starts with SUBSCRIBE_OK.
+            if let Some(mut state) = self.state.lock_mut() {
+                state.expires = track.expires;
+                state.expires_at = track.expires.map(|expires| Instant::now() + expires);
+            }
+
+            // Send SubscribeOk using send_message_and_wait to ensure it is sent at least to the QUIC stack before
+            // we start serving the track.  If a subscriber gets the stream before SubscribeOk
+            // then they won't recognize the track_alias in the stream header.
+            self.publisher
+                .send_message_and_wait(message::SubscribeOk {
+                    id: self.info.id,
+                    track_alias: self.track_alias.id(),
+                    expires: track
+                        .expires
+                        .map(|expires| expires.as_millis() as u64)
+                        .unwrap_or(0),
+                    group_order,
+                    content_exists: largest_location.is_some(),
+                    largest_location,
+                    params: Default::default(),
+                })
+                .await;
+
+            self.ok = true; // So we send SubscribeDone on drop
+        }
+
+        // End the subscription with PUBLISH_DONE once it expires.
+        let expired = Self::expired(self.state.clone());
 
         // Serve based on track mode
-        match track.mode().await? {
-            // TODO cancel track/datagrams on closed
-            TrackReaderMode::Stream(_stream) => panic!("deprecated"),
-            TrackReaderMode::Subgroups(subgroups) => self.serve_subgroups(subgroups).await,
-            TrackReaderMode::Datagrams(datagrams) => self.serve_datagrams(datagrams).await,
+        let serve = async {
+            match track.mode().await? {
+                // TODO cancel track/datagrams on closed
+                TrackReaderMode::Stream(_stream) => panic!("deprecated"),
+                TrackReaderMode::Subgroups(subgroups) => {
+                    self.serve_subgroups(subgroups, cached, group_order).await
+                }
+                TrackReaderMode::Datagrams(datagrams) => self.serve_datagrams(datagrams).await,
+            }
+        };
+
+        tokio::select! {
+            res = serve => res,
+            _ = expired => Err(ServeError::Expired.into()),
+        }
+    }
+
+    /// Wait until the subscription expires, unless a SUBSCRIBE_UPDATE refreshes it first.
+    async fn expired(state: State<SubscribedState>) {
+        loop {
+            let (expires_at, changed) = {
+                let state = state.lock();
+                (state.expires_at, state.modified())
+            };
+
+            match (expires_at, changed) {
+                (Some(expires_at), Some(changed)) => tokio::select! {
+                    _ = tokio::time::sleep_until(expires_at) => return,
+                    _ = changed => {},
+                },
+                (Some(expires_at), None) => return tokio::time::sleep_until(expires_at).await,
+                (None, Some(changed)) => changed.await,
+                (None, None) => return std::future::pending().await,
+            }
+        }
+    }
+
+    /// Resolve the first location to deliver from the subscription filter, given the largest location
+    /// published so far.  None means every object is delivered.
+    fn start_location(
+        &self,
+        largest_location: Option<Location>,
+    ) -> Result<Option<Location>, ServeError> {
+        match self.info.filter_type {
+            FilterType::NextGroupStart => {
+                Ok(largest_location.map(|largest| Location::new(largest.group_id + 1, 0)))
+            }
+            FilterType::LargestObject => Ok(largest_location
+                .map(|largest| Location::new(largest.group_id, largest.object_id + 1))),
+            FilterType::AbsoluteStart => Ok(Some(
+                self.info.start_location.ok_or(ServeError::InvalidRange)?,
+            )),
+            FilterType::AbsoluteRange => {
+                let start = self.info.start_location.ok_or(ServeError::InvalidRange)?;
+                match self.info.end_group_id {
+                    Some(end) if end >= start.group_id => Ok(Some(start)),
+                    _ => Err(ServeError::InvalidRange),
+                }
+            }
         }
     }
 
@@ -168,19 +362,20 @@ impl Drop for Subscribed {
             .err()
             .cloned()
             .unwrap_or(ServeError::Done);
+        let stream_count = state.stream_count;
         drop(state); // Important to avoid a deadlock
 
         if self.ok {
             self.publisher.send_message(message::PublishDone {
                 id: self.info.id,
-                status_code: err.code(),
-                stream_count: 0, // TODO SLG
+                status_code: err.publish_done_status().code(),
+                stream_count,
                 reason: ReasonPhrase(err.to_string()),
             });
         } else {
             self.publisher.send_message(message::SubscribeError {
                 id: self.info.id,
-                error_code: err.code(),
+                error_code: err.subscribe_error_code().code(),
                 reason_phrase: ReasonPhrase(err.to_string()),
             });
         };
@@ -191,32 +386,66 @@ impl Subscribed {
     async fn serve_subgroups(
         &mut self,
         mut subgroups: serve::SubgroupsReader,
+        mut cached: Vec<serve::SubgroupReader>,
+        group_order: message::GroupOrder,
     ) -> Result<(), SessionError> {
-        let mut tasks = FuturesUnordered::new();
+        let mut tasks: FuturesUnordered<BoxFuture<'static, ()>> = FuturesUnordered::new();
         let mut done: Option<Result<(), ServeError>> = None;
 
+        // Groups published before the subscription started are served from the cache all at once, with the
+        // stream priority keeping them in the resolved group order, while new groups are served as they arrive.
+        sort_cached(&mut cached, group_order);
+
+        // New groups are the newest, so they go ahead of the cached ones when descending and after them otherwise.
+        let live_position = match group_order {
+            message::GroupOrder::Descending => 0,
+            _ => cached.len(),
+        };
+
+        let (forward, subscriber_priority) = {
+            let state = self.state.lock();
+            (state.forward, state.subscriber_priority)
+        };
+
+        // Like new groups, cached groups aren't sent while forwarding is off, nor picked up once it's back on.
+        if !forward {
+            cached.clear();
+        }
+
+        for (position, subgroup) in cached.into_iter().enumerate() {
+            tasks.push(
+                self.serve_subgroup_task(subgroup, subscriber_priority, position)
+                    .boxed(),
+            );
+        }
+
         loop {
             tokio::select! {
                 res = subgroups.next(), if done.is_none() => match res {
                     Ok(Some(subgroup)) => {
-                        let header = data::SubgroupHeader {
-                            header_type: data::StreamHeaderType::SubgroupIdExt,  // SubGroupId = Yes, Extensions = Yes, ContainsEndOfGroup = No
-                            track_alias: self.info.id, // use subscription id as track_alias
-                            group_id: subgroup.group_id,
-                            subgroup_id: Some(subgroup.subgroup_id),
-                            publisher_priority: subgroup.priority,
+                        let (after_end, skip, subscriber_priority) = {
+                            let state = self.state.lock();
+                            let skip = !state.forward
+                                || state.start_location.is_some_and(|start| subgroup.group_id < start.group_id);
+                            (state.is_after_end(subgroup.group_id), skip, state.subscriber_priority)
                         };
 
-                        let publisher = self.publisher.clone();
-                        let state = self.state.clone();
-                        let info = subgroup.info.clone();
-                        let mlog = self.mlog.clone();
-
-                        tasks.push(async move {
-                            if let Err(err) = Self::serve_subgroup(header, subgroup, publisher, state, mlog).await {
-                                log::warn!("failed to serve subgroup: {:?}, error: {}", info, err);
+                        if after_end {
+                            // Past the end of the (possibly narrowed) range, so the subscription is complete,
+                            // which PUBLISH_DONE reports as SUBSCRIPTION_ENDED rather than TRACK_ENDED.
+                            if let Some(mut state) = self.state.lock_mut() {
+                                state.closed = Err(ServeError::Cancel);
                             }
-                        });
+                            done = Some(Ok(()));
+                            continue;
+                        }
+
+                        if skip {
+                            log::debug!("[PUBLISHER] serve_subgroups: skipping subgroup {:?}", subgroup.info);
+                            continue;
+                        }
+
+                        tasks.push(self.serve_subgroup_task(subgroup, subscriber_priority, live_position).boxed());
                     },
                     Ok(None) => done = Some(Ok(())),
                     Err(err) => done = Some(Err(err)),
@@ -228,9 +457,47 @@ impl Subscribed {
         }
     }
 
+    fn serve_subgroup_task(
+        &self,
+        subgroup: serve::SubgroupReader,
+        subscriber_priority: u8,
+        position: usize,
+    ) -> impl Future<Output = ()> {
+        let header = data::SubgroupHeader {
+            header_type: data::StreamHeaderType::SubgroupIdExt, // SubGroupId = Yes, Extensions = Yes, ContainsEndOfGroup = No
+            track_alias: self.track_alias.id(),
+            group_id: subgroup.group_id,
+            subgroup_id: Some(subgroup.subgroup_id),
+            publisher_priority: subgroup.priority,
+        };
+
+        let publisher = self.publisher.clone();
+        let state = self.state.clone();
+        let info = subgroup.info.clone();
+        let mlog = self.mlog.clone();
+
+        async move {
+            if let Err(err) = Self::serve_subgroup(
+                header,
+                subgroup,
+                subscriber_priority,
+                position,
+                publisher,
+                state,
+                mlog,
+            )
+            .await
+            {
+                log::warn!("failed to serve subgroup: {:?}, error: {}", info, err);
+            }
+        }
+    }
+
     async fn serve_subgroup(
         header: data::SubgroupHeader,
         mut subgroup_reader: serve::SubgroupReader,
+        subscriber_priority: u8,
+        position: usize,
         mut publisher: Publisher,
         state: State<SubscribedState>,
         mlog: Option<Arc<Mutex<mlog::MlogWriter>>>,
@@ -242,13 +509,33 @@ impl Subscribed {
             subgroup_reader.priority
         );
 
+        // Forwarding may have been paused, or the range narrowed, since the subgroup was queued.
+        {
+            let state = state.lock();
+            if !state.forward || state.is_after_end(subgroup_reader.group_id) {
+                log::debug!(
+                    "[PUBLISHER] serve_subgroup: not forwarding group_id={}, subgroup_id={:?}",
+                    subgroup_reader.group_id,
+                    subgroup_reader.subgroup_id
+                );
+                return Ok(());
+            }
+        }
+
         let mut send_stream = publisher.open_uni().await?;
         log::trace!("[PUBLISHER] serve_subgroup: opened unidirectional stream");
 
-        // TODO figure out u32 vs u64 priority
-        send_stream.set_priority(subgroup_reader.priority as i32);
+        if let Some(mut state) = state.lock_mut() {
+            state.stream_count += 1;
+        }
+
+        send_stream.set_priority(stream_priority(
+            subscriber_priority,
+            subgroup_reader.priority,
+            position,
+        ));
 
-        let mut writer = Writer::new(send_stream);
+        let mut writer = Writer::new(send_stream, publisher.version());
 
         log::debug!(
             "[PUBLISHER] serve_subgroup: sending header - track_alias={}, group_id={}, subgroup_id={:?}, priority={}, header_type={:?}",
@@ -272,10 +559,26 @@ impl Subscribed {
         }
 
         let mut object_count = 0;
+        let mut object_ids = data::ObjectIdDelta::default();
         while let Some(mut subgroup_object_reader) = subgroup_reader.next().await? {
+            {
+                let state = state.lock();
+
+                // Forwarding was paused, or the range was narrowed to end before this group.
+                if !state.forward || state.is_after_end(subgroup_reader.group_id) {
+                    break;
+                }
+
+                let location =
+                    Location::new(subgroup_reader.group_id, subgroup_object_reader.object_id);
+                if state.is_before_start(location) {
+                    continue;
+                }
+            }
+
             let subgroup_object = data::SubgroupObjectExt {
-                object_id_delta: 0, // before delta logic, used to be subgroup_object_reader.object_id,
-                extension_headers: KeyValuePairs::new(), // TODO SLG - empty for now
+                object_id_delta: object_ids.encode(subgroup_object_reader.object_id)?,
+                extension_headers: subgroup_object_reader.extension_headers.clone(),
                 payload_length: subgroup_object_reader.size,
                 status: if subgroup_object_reader.size == 0 {
                     // Only set status if payload length is zero
@@ -362,15 +665,36 @@ impl Subscribed {
 
         let mut datagram_count = 0;
         while let Some(datagram) = datagrams.read().await? {
+            {
+                let state = self.state.lock();
+                if state.is_after_end(datagram.group_id) {
+                    break;
+                }
+
+                let location = Location::new(datagram.group_id, datagram.object_id);
+                if !state.forward || state.is_before_start(location) {
+                    continue;
+                }
+            }
+
+            // Status objects, such as the end of the group or track, are sent without a payload.
+            let is_status = datagram.status != data::ObjectStatus::NormalObject;
+            let has_extensions = !datagram.extension_headers.0.is_empty();
+
             let encoded_datagram = data::Datagram {
-                datagram_type: data::DatagramType::ObjectIdPayload, // TODO SLG
-                track_alias: self.info.id, // use subscription id as track_alias
+                datagram_type: match (is_status, has_extensions) {
+                    (false, false) => data::DatagramType::ObjectIdPayload,
+                    (false, true) => data::DatagramType::ObjectIdPayloadExt,
+                    (true, false) => data::DatagramType::ObjectIdStatus,
+                    (true, true) => data::DatagramType::ObjectIdStatusExt,
+                },
+                track_alias: self.track_alias.id(),
                 group_id: datagram.group_id,
                 object_id: Some(datagram.object_id),
                 publisher_priority: datagram.priority,
-                extension_headers: None,
-                status: None,
-                payload: Some(datagram.payload),
+                extension_headers: has_extensions.then_some(datagram.extension_headers),
+                status: is_status.then_some(datagram.status),
+                payload: (!is_status).then_some(datagram.payload),
             };
 
             let payload_len = encoded_datagram
@@ -379,7 +703,7 @@ impl Subscribed {
                 .map(|p| p.len())
                 .unwrap_or(0);
             let mut buffer = bytes::BytesMut::with_capacity(payload_len + 100);
-            encoded_datagram.encode(&mut buffer)?;
+            encoded_datagram.encode_versioned(&mut buffer, self.publisher.version())?;
 
             log::debug!(
                 "[PUBLISHER] serve_datagrams: sending datagram #{} - group_id={}, object_id={}, priority={}, payload_len={}, total_encoded_len={}",
@@ -413,11 +737,54 @@ impl Subscribed {
     }
 }
 
+/// Sort the cached subgroups in the resolved group order, keeping subgroups within a group ascending.
+fn sort_cached(cached: &mut [serve::SubgroupReader], group_order: message::GroupOrder) {
+    cached.sort_by(|a, b| {
+        let groups = match group_order {
+            message::GroupOrder::Descending => b.group_id.cmp(&a.group_id),
+            _ => a.group_id.cmp(&b.group_id),
+        };
+        groups.then(a.subgroup_id.cmp(&b.subgroup_id))
+    });
+}
+
+/// The most streams sent at once that keep their relative order, such as cached groups.
+const MAX_STREAM_POSITION: usize = 0x7fff;
+
+/// The QUIC stream priority, ordering first by subscriber priority, then by publisher priority, and then by
+/// position among the streams sent at once, such as the cached groups in the resolved group order.
+/// Smaller MoQ priorities are sent first, while QUIC sends larger values first, so it's inverted.
+///
+/// NOTE: This used to be the publisher priority as is, which sent the *largest* publisher priority first and
+/// ignored the subscriber priority. Publishers relying on that order need to flip their priorities.
+pub(super) fn stream_priority(
+    subscriber_priority: u8,
+    publisher_priority: u8,
+    position: usize,
+) -> i32 {
+    let priority = ((subscriber_priority as i32) << 8) | publisher_priority as i32;
+    -((priority << 15) | position.min(MAX_STREAM_POSITION) as i32)
+}
+
 pub(super) struct SubscribedRecv {
     state: State<SubscribedState>,
+
+    /// The tracknamespace and trackname for the subscription, used to resolve joining fetches.
+    pub info: SubscribeInfo,
 }
 
 impl SubscribedRecv {
+    pub fn recv_update(&mut self, msg: &message::SubscribeUpdate) -> Result<(), ServeError> {
+        let state = self.state.lock();
+        state.closed.clone()?;
+
+        if let Some(mut state) = state.into_mut() {
+            state.update(msg);
+        }
+
+        Ok(())
+    }
+
     pub fn recv_unsubscribe(&mut self) -> Result<(), ServeError> {
         let state = self.state.lock();
         state.closed.clone()?;
@@ -429,3 +796,73 @@ impl SubscribedRecv {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stream_priority_order() {
+        // A smaller publisher priority is sent first.
+        assert!(stream_priority(0, 1, 0) > stream_priority(0, 2, 0));
+
+        // The subscriber priority takes precedence over the publisher priority.
+        assert!(stream_priority(1, 255, 0) > stream_priority(2, 0, 0));
+
+        // The position only orders streams with the same priorities.
+        assert!(stream_priority(0, 0, 1) > stream_priority(0, 0, 2));
+        assert!(stream_priority(0, 0, MAX_STREAM_POSITION) > stream_priority(0, 1, 0));
+        assert_eq!(
+            stream_priority(0, 0, usize::MAX),
+            stream_priority(0, 0, MAX_STREAM_POSITION)
+        );
+
+        // The extremes still order correctly.
+        assert!(stream_priority(0, 0, 0) > stream_priority(255, 255, usize::MAX));
+        assert_eq!(stream_priority(0, 0, 0), 0);
+    }
+
+    fn cached_subgroups(ids: &[(u64, u64)]) -> Vec<serve::SubgroupReader> {
+        let track = Arc::new(serve::Track::new(
+            crate::coding::TrackNamespace::from_utf8_path("test"),
+            "video".to_string(),
+        ));
+
+        ids.iter()
+            .map(|&(group_id, subgroup_id)| {
+                let (_, reader) = serve::SubgroupInfo {
+                    track: track.clone(),
+                    group_id,
+                    subgroup_id,
+                    priority: 0,
+                }
+                .produce();
+                reader
+            })
+            .collect()
+    }
+
+    fn ids(cached: &[serve::SubgroupReader]) -> Vec<(u64, u64)> {
+        cached
+            .iter()
+            .map(|subgroup| (subgroup.group_id, subgroup.subgroup_id))
+            .collect()
+    }
+
+    #[test]
+    fn cached_descending() {
+        let mut cached = cached_subgroups(&[(0, 0), (0, 1), (1, 0), (2, 1), (2, 0)]);
+        sort_cached(&mut cached, message::GroupOrder::Descending);
+
+        // Newest group first, with subgroups still ascending.
+        assert_eq!(ids(&cached), vec![(2, 0), (2, 1), (1, 0), (0, 0), (0, 1)]);
+    }
+
+    #[test]
+    fn cached_ascending() {
+        let mut cached = cached_subgroups(&[(2, 0), (0, 1), (1, 0), (0, 0)]);
+        sort_cached(&mut cached, message::GroupOrder::Ascending);
+
+        assert_eq!(ids(&cached), vec![(0, 0), (0, 1), (1, 0), (2, 0)]);
+    }
+}
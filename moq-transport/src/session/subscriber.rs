@@ -5,16 +5,25 @@ use std::{
 };
 
 use crate::{
-    coding::{Decode, TrackNamespace},
+    coding::{AuthToken, DecodeVersioned, KeyValuePairs, Location, TrackNamespace},
     data,
-    message::{self, FilterType, GroupOrder, Message},
+    message::{self, FetchType, FilterType, GroupOrder, Message},
     mlog,
     serve::{self, ServeError},
+    setup,
 };
 
-use crate::watch::Queue;
+use crate::watch::{Queue, State};
+use futures::{future::BoxFuture, stream::FuturesUnordered, FutureExt, StreamExt};
 
-use super::{Announced, AnnouncedRecv, Reader, Session, SessionError, Subscribe, SubscribeRecv};
+use super::{
+    Announced, AnnouncedRecv, Fetch, FetchInfo, FetchRecv, FetchedObject, Published, Reader,
+    RequestToken, Session, SessionError, Subscribe, SubscribeNamespace, SubscribeNamespaceRecv,
+    SubscribeRecv, TrackStatus, TrackStatusRecv, TrackStatusRequest,
+};
+
+/// How long a data stream waits for the SUBSCRIBE_OK assigning its track alias.
+const UNKNOWN_ALIAS_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(1);
 
 // TODO remove Clone.
 #[derive(Clone)]
@@ -25,11 +34,27 @@ pub struct Subscriber {
     /// Queue of announced namespaces we have received from the Publisher, waiting to be processed.
     announced_queue: Queue<Announced>,
 
+    /// Queue of publishes we have received from the Publisher, waiting to be accepted or rejected.
+    published_queue: Queue<Published>,
+
+    /// The currently active outbound namespace subscriptions, keyed by request id.
+    subscribe_namespaces: Arc<Mutex<HashMap<u64, (TrackNamespace, SubscribeNamespaceRecv)>>>,
+
     /// The currently active outbound subscribes, keyed by request id.
     subscribes: Arc<Mutex<HashMap<u64, SubscribeRecv>>>,
 
+    /// The currently active outbound fetches, keyed by request id.
+    fetches: Arc<Mutex<HashMap<u64, FetchRecv>>>,
+
+    /// The outbound track status requests waiting for a response, keyed by request id.
+    track_statuses: Arc<Mutex<HashMap<u64, TrackStatusRecv>>>,
+
     /// Map of track alias to subscription id for quick lookup when receiving streams/datagrams.
-    subscribe_alias_map: Arc<Mutex<HashMap<u64, u64>>>,
+    /// Watched by streams that arrive before the SUBSCRIBE_OK assigning their alias.
+    subscribe_alias_map: State<HashMap<u64, u64>>,
+
+    /// Subscriptions to watch until every reader of the track is dropped, processed by the session run_unused task.
+    unused_queue: Queue<(u64, BoxFuture<'static, bool>)>,
 
     /// The queue we will write any outbound control messages we want to send, the session run_send task
     /// will process the queue and send the message on the control stream.
@@ -42,6 +67,12 @@ pub struct Subscriber {
     /// increment by 2 for each request (odd numbers).
     next_requestid: Arc<atomic::AtomicU64>,
 
+    /// The AUTHORIZATION_TOKEN attached to our requests, shared with the Publisher.
+    auth: RequestToken,
+
+    /// The version negotiated in SETUP, which picks the wire format of data streams and datagrams.
+    version: setup::Version,
+
     /// Optional mlog writer for logging transport events
     mlog: Option<Arc<Mutex<mlog::MlogWriter>>>,
 }
@@ -50,15 +81,24 @@ impl Subscriber {
     pub(super) fn new(
         outgoing: Queue<Message>,
         next_requestid: Arc<atomic::AtomicU64>,
+        auth: RequestToken,
+        version: setup::Version,
         mlog: Option<Arc<Mutex<mlog::MlogWriter>>>,
     ) -> Self {
         Self {
             announced: Default::default(),
             announced_queue: Default::default(),
+            published_queue: Default::default(),
+            subscribe_namespaces: Default::default(),
             subscribes: Default::default(),
+            fetches: Default::default(),
+            track_statuses: Default::default(),
             subscribe_alias_map: Default::default(),
+            unused_queue: Default::default(),
             outgoing,
             next_requestid,
+            auth,
+            version,
             mlog,
         }
     }
@@ -80,14 +120,62 @@ impl Subscriber {
         self.announced_queue.pop().await
     }
 
+    /// Wait for the next track pushed by the publisher with a PUBLISH, if any.
+    /// Use [Published::accept] to receive it, or drop it to reject.
+    pub async fn published(&mut self) -> Option<Published> {
+        self.published_queue.pop().await
+    }
+
+    /// Subscribe to a namespace prefix, waiting until the publisher accepts it.  Namespaces announced under the
+    /// prefix are then returned by [SubscribeNamespace::announced] instead of [Self::announced].
+    /// Namespaces the publisher announced before the subscription were already returned by [Self::announced].
+    pub async fn subscribe_namespace(
+        &mut self,
+        namespace_prefix: TrackNamespace,
+    ) -> Result<SubscribeNamespace, ServeError> {
+        let request_id = self.get_next_request_id();
+        let (send, recv) =
+            SubscribeNamespace::new(self.clone(), request_id, namespace_prefix.clone());
+        self.subscribe_namespaces
+            .lock()
+            .unwrap()
+            .insert(request_id, (namespace_prefix, recv));
+
+        send.ok().await?;
+
+        Ok(send)
+    }
+
     /// Get the current next request id to use and increment the value for by 2 for the next request
-    fn get_next_request_id(&self) -> u64 {
+    pub(super) fn get_next_request_id(&self) -> u64 {
         self.next_requestid.fetch_add(2, atomic::Ordering::Relaxed)
     }
 
-    pub fn track_status(&mut self, track_namespace: &TrackNamespace, track_name: &str) {
+    /// Attach an AUTHORIZATION_TOKEN to our future requests, such as SUBSCRIBE and FETCH, or None to stop.
+    /// The token is shared with the [super::Publisher] of the same session.
+    pub fn set_auth_token(&mut self, token: Option<AuthToken>) {
+        self.auth.set(token)
+    }
+
+    /// The parameters for a new request, carrying our AUTHORIZATION_TOKEN if any.
+    pub(super) fn request_params(&self) -> KeyValuePairs {
+        self.auth.params()
+    }
+
+    /// Ask the publisher for the status of a track, waiting for the reply.
+    /// Returns an error with the TRACK_STATUS_ERROR code if the publisher can't serve the track.
+    pub async fn track_status(
+        &mut self,
+        track_namespace: &TrackNamespace,
+        track_name: &str,
+    ) -> Result<TrackStatus, ServeError> {
+        let request_id = self.get_next_request_id();
+        let (send, recv) = TrackStatusRequest::new(self.clone(), request_id);
+
+        // Insert before sending, so the response can't beat us to the map.
+        self.track_statuses.lock().unwrap().insert(request_id, recv);
         self.send_message(message::TrackStatus {
-            id: self.get_next_request_id(),
+            id: request_id,
             track_namespace: track_namespace.clone(),
             track_name: track_name.to_string(),
             subscriber_priority: 127, // default to mid value, see: https://github.com/moq-wg/moq-transport/issues/504
@@ -96,18 +184,221 @@ impl Subscriber {
             filter_type: FilterType::LargestObject,
             start_location: None,
             end_group_id: None,
-            params: Default::default(),
+            params: self.request_params(),
         });
-        // TODO make async and wait for response?
+
+        send.reply().await
     }
 
-    /// Subscribe to a track by creating a new subscribe request to the publisher.  Block until subscription is closed.
+    /// Subscribe to a track by creating a new subscribe request to the publisher.  Block until subscription is closed,
+    /// refreshing it before it expires.
     pub async fn subscribe(&mut self, track: serve::TrackWriter) -> Result<(), ServeError> {
         let request_id = self.get_next_request_id();
-        let (send, recv) = Subscribe::new(self.clone(), request_id, track);
+        let (send, recv) = Subscribe::new(self.clone(), request_id, track, true);
+        self.watch_unused(&recv);
         self.subscribes.lock().unwrap().insert(request_id, recv);
 
-        send.closed().await
+        send.refresh_until_closed().await
+    }
+
+    /// Move the active subscriptions onto the subscriber of a new session, such as after a GOAWAY.
+    /// Each one is re-issued starting at the next group, while this session finishes the current one.
+    /// Existing [Subscribe] handles keep working, and wait on the new subscription once migrated.
+    pub fn migrate(&mut self, to: &Subscriber) {
+        to.auth.set(self.auth.get());
+
+        let mut subscribes = self.subscribes.lock().unwrap();
+        for subscribe in subscribes.values_mut() {
+            if let Some(recv) = subscribe.migrate(to.clone()) {
+                to.watch_unused(&recv);
+                to.subscribes.lock().unwrap().insert(recv.id(), recv);
+            }
+        }
+    }
+
+    /// Subscribe to a track, returning the handle instead of blocking until it's closed.
+    /// [Subscribe::ok] waits for SUBSCRIBE_OK and its details, such as the largest location, while objects keep
+    /// being written to the track until [Subscribe::closed].  The handle unsubscribes on drop.
+    /// If the publisher set an expiry, either [Subscribe::refresh] it or wait for [Subscribe::expired].
+    pub fn subscribe_handle(&mut self, track: serve::TrackWriter) -> Subscribe {
+        self.subscribe_with_forward(track, true)
+    }
+
+    /// Subscribe to a track without forwarding any objects yet.  The subscription is established, so
+    /// the largest location is known, but no streams are opened until [Subscribe::set_forward] turns it on.
+    pub fn subscribe_standby(&mut self, track: serve::TrackWriter) -> Subscribe {
+        self.subscribe_with_forward(track, false)
+    }
+
+    /// Subscribe to a track starting at an absolute location, such as a group still in the publisher's cache,
+    /// and optionally ending after end_group (inclusive).  Returns the handle like [Self::subscribe_handle].
+    /// The publisher rejects the subscription with [ServeError::InvalidRange] if the start is no longer cached.
+    pub fn subscribe_range(
+        &mut self,
+        track: serve::TrackWriter,
+        start: Location,
+        end_group: Option<u64>,
+    ) -> Subscribe {
+        let request_id = self.get_next_request_id();
+        let (send, recv) = Subscribe::new_range(self.clone(), request_id, track, start, end_group);
+        self.watch_unused(&recv);
+        self.subscribes.lock().unwrap().insert(request_id, recv);
+
+        send
+    }
+
+    fn subscribe_with_forward(&mut self, track: serve::TrackWriter, forward: bool) -> Subscribe {
+        let request_id = self.get_next_request_id();
+        let (send, recv) = Subscribe::new(self.clone(), request_id, track, forward);
+        self.watch_unused(&recv);
+        self.subscribes.lock().unwrap().insert(request_id, recv);
+
+        send
+    }
+
+    /// Watch the subscription, so we unsubscribe as soon as every reader of the track is dropped.
+    fn watch_unused(&self, recv: &SubscribeRecv) {
+        let mut queue = self.unused_queue.clone();
+        let _ = queue.push((recv.id(), recv.unused().boxed()));
+    }
+
+    /// Unsubscribe from each watched subscription once every reader of its track is dropped, rather than
+    /// waiting for the next object to notice, so the publisher stops sending data nobody reads.
+    pub(super) async fn run_unused(mut self) -> Result<(), SessionError> {
+        let mut tasks = FuturesUnordered::new();
+
+        loop {
+            tokio::select! {
+                Some((id, unused)) = self.unused_queue.pop() => {
+                    let mut this = self.clone();
+                    tasks.push(async move {
+                        if unused.await {
+                            this.unsubscribe_unused(id);
+                        }
+                    });
+                },
+                _ = tasks.next(), if !tasks.is_empty() => {},
+                else => return Ok(()),
+            }
+        }
+    }
+
+    fn unsubscribe_unused(&mut self, id: u64) {
+        if self.subscribes.lock().unwrap().contains_key(&id) {
+            log::debug!(
+                "[SUBSCRIBER] unsubscribing id={}: every reader of the track was dropped",
+                id
+            );
+            self.send_message(message::Unsubscribe { id });
+        }
+    }
+
+    /// Subscribe to a track, and use a joining fetch to fill in the objects before the subscription starts, so
+    /// playback can begin at a group boundary.  With FetchType::RelativeJoining, joining_start is the number of
+    /// groups before the current one; with FetchType::AbsoluteJoining, it's the first group_id to fetch.
+    /// Fetched objects are written to the track ahead of the live ones, skipping any duplicates.
+    /// Block until subscription is closed, refreshing it before it expires.
+    pub async fn subscribe_joining(
+        &mut self,
+        track: serve::TrackWriter,
+        fetch_type: FetchType,
+        joining_start: u64,
+    ) -> Result<(), ServeError> {
+        if fetch_type == FetchType::Standalone {
+            return Err(ServeError::NotSupported(
+                "a standalone fetch can't join a subscription".to_string(),
+            ));
+        }
+
+        let track_namespace = track.namespace.clone();
+        let track_name = track.name.clone();
+
+        let request_id = self.get_next_request_id();
+        let (send, mut recv) = Subscribe::new(self.clone(), request_id, track, true);
+        recv.join()?;
+        self.watch_unused(&recv);
+        self.subscribes.lock().unwrap().insert(request_id, recv);
+
+        let fetch_id = self.get_next_request_id();
+        let mut info = FetchInfo::new_joining(
+            fetch_id,
+            track_namespace,
+            track_name,
+            fetch_type,
+            message::JoiningFetch {
+                joining_request_id: request_id,
+                joining_start,
+            },
+        );
+        info.params = self.request_params();
+        let (fetch, fetch_recv) = Fetch::new(self.clone(), info);
+        self.fetches.lock().unwrap().insert(fetch_id, fetch_recv);
+        self.send_message(fetch.message());
+
+        tokio::select! {
+            res = send.refresh_until_closed() => return res,
+            res = self.clone().recv_joining(request_id, fetch) => {
+                if let Err(err) = res {
+                    log::warn!("failed joining fetch for subscribe id={}: {}", request_id, err);
+                }
+            }
+        }
+
+        send.refresh_until_closed().await
+    }
+
+    /// Write the objects from a joining fetch to the subscription, then release the live streams.
+    async fn recv_joining(mut self, subscribe_id: u64, mut fetch: Fetch) -> Result<(), ServeError> {
+        let res = self.recv_joining_objects(subscribe_id, &mut fetch).await;
+
+        // Always release the live streams, even if the fetch failed.
+        if let Some(subscribe) = self.subscribes.lock().unwrap().get_mut(&subscribe_id) {
+            subscribe.joined()?;
+        }
+
+        res
+    }
+
+    async fn recv_joining_objects(
+        &mut self,
+        subscribe_id: u64,
+        fetch: &mut Fetch,
+    ) -> Result<(), ServeError> {
+        while let Some(object) = fetch.next().await? {
+            match self.subscribes.lock().unwrap().get_mut(&subscribe_id) {
+                Some(subscribe) => subscribe.fetched(object)?,
+                None => return Err(ServeError::Cancel),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Fetch a range of objects from a track, from start up to end (exclusive). An end object_id of 0 requests the
+    /// entire end group.  Objects are read from the returned Fetch, which cancels the request if dropped early.
+    pub fn fetch(
+        &mut self,
+        track_namespace: TrackNamespace,
+        track_name: &str,
+        start: Location,
+        end: Location,
+    ) -> Fetch {
+        let request_id = self.get_next_request_id();
+        let mut info = FetchInfo::new_standalone(
+            request_id,
+            track_namespace,
+            track_name.to_string(),
+            start,
+            end,
+        );
+        info.params = self.request_params();
+        let (send, recv) = Fetch::new(self.clone(), info);
+
+        // Insert before sending, so the response can't beat us to the map.
+        self.fetches.lock().unwrap().insert(request_id, recv);
+        self.send_message(send.message());
+
+        send
     }
 
     /// Send a message to the publisher via the control stream.
@@ -121,6 +412,15 @@ impl Subscriber {
             }
             // TODO SLG - there is no longer a namespace in the error, need to map via request id
             message::Subscriber::PublishNamespaceError(_msg) => todo!(), //self.drop_announce(&msg.track_namespace),
+            message::Subscriber::Unsubscribe(msg) => {
+                if let Some(subscribe) = self.remove_subscribe(msg.id) {
+                    subscribe.unsubscribe();
+                }
+            }
+            message::Subscriber::FetchCancel(msg) => self.drop_fetch(msg.id),
+            message::Subscriber::UnsubscribeNamespace(msg) => {
+                self.drop_subscribe_namespace(&msg.track_namespace_prefix)
+            }
             _ => {}
         }
 
@@ -128,27 +428,23 @@ impl Subscriber {
         let _ = self.outgoing.push(msg.into());
     }
 
-    fn not_implemented_yet(&self) -> Result<(), SessionError> {
-        Err(SessionError::Serve(ServeError::Internal(
-            "Not implemented yet".to_string(),
-        )))
-    }
-
     /// Receive a message from the publisher via the control stream.
     pub(super) fn recv_message(&mut self, msg: message::Publisher) -> Result<(), SessionError> {
         let res = match &msg {
             message::Publisher::PublishNamespace(msg) => self.recv_publish_namespace(msg),
             message::Publisher::PublishNamespaceDone(msg) => self.recv_publish_namespace_done(msg),
-            message::Publisher::Publish(_msg) => self.not_implemented_yet(), // TODO
+            message::Publisher::Publish(msg) => self.recv_publish(msg),
             message::Publisher::PublishDone(msg) => self.recv_publish_done(msg),
             message::Publisher::SubscribeOk(msg) => self.recv_subscribe_ok(msg),
             message::Publisher::SubscribeError(msg) => self.recv_subscribe_error(msg),
             message::Publisher::TrackStatusOk(msg) => self.recv_track_status_ok(msg),
-            message::Publisher::TrackStatusError(_msg) => self.not_implemented_yet(), // TODO
-            message::Publisher::FetchOk(_msg) => self.not_implemented_yet(),          // TODO
-            message::Publisher::FetchError(_msg) => self.not_implemented_yet(),       // TODO
-            message::Publisher::SubscribeNamespaceOk(_msg) => self.not_implemented_yet(), // TODO
-            message::Publisher::SubscribeNamespaceError(_msg) => self.not_implemented_yet(), // TODO
+            message::Publisher::TrackStatusError(msg) => self.recv_track_status_error(msg),
+            message::Publisher::FetchOk(msg) => self.recv_fetch_ok(msg),
+            message::Publisher::FetchError(msg) => self.recv_fetch_error(msg),
+            message::Publisher::SubscribeNamespaceOk(msg) => self.recv_subscribe_namespace_ok(msg),
+            message::Publisher::SubscribeNamespaceError(msg) => {
+                self.recv_subscribe_namespace_error(msg)
+            }
         };
 
         if let Err(SessionError::Serve(err)) = res {
@@ -172,17 +468,109 @@ impl Subscriber {
             hash_map::Entry::Vacant(entry) => entry,
         };
 
-        // Create the announced namespace and insert it into our map of active announces, and the announced queue.
+        // Create the announced namespace and insert it into our map of active announces.
         let (announced, recv) = Announced::new(self.clone(), msg.id, msg.track_namespace.clone());
-        if let Err(announced) = self.announced_queue.push(announced) {
-            announced.close(ServeError::Cancel)?;
-            return Ok(());
+
+        // Route to the most specific namespace subscription that matches, otherwise to the announced queue.
+        let unrouted = {
+            let mut subscribe_namespaces = self.subscribe_namespaces.lock().unwrap();
+            let subscribe_namespace = subscribe_namespaces
+                .values_mut()
+                .filter(|(prefix, _)| msg.track_namespace.has_prefix(prefix))
+                .max_by_key(|(prefix, _)| prefix.fields.len());
+
+            match subscribe_namespace {
+                Some((_, subscribe_namespace)) => subscribe_namespace.recv_announced(announced),
+                None => Some(announced),
+            }
+        };
+
+        if let Some(announced) = unrouted {
+            if let Err(announced) = self.announced_queue.push(announced) {
+                announced.close(ServeError::Cancel)?;
+                return Ok(());
+            }
         }
         entry.insert(recv);
 
         Ok(())
     }
 
+    /// Handle the reception of a Publish message from the publisher.
+    fn recv_publish(&mut self, msg: &message::Publish) -> Result<(), SessionError> {
+        let duplicate = {
+            let subscribes = self.subscribes.lock().unwrap();
+            self.check_alias(
+                &subscribes,
+                msg.track_alias,
+                &msg.track_namespace,
+                &msg.track_name,
+            )?;
+            subscribes.contains_key(&msg.id)
+        };
+
+        let published = Published::new(self.clone(), msg.clone());
+        if duplicate {
+            published.close(ServeError::Duplicate)?;
+            return Ok(());
+        }
+
+        // Rejected on drop if the application isn't listening.
+        if let Err(published) = self.published_queue.push(published) {
+            published.close(ServeError::Cancel)?;
+        }
+
+        Ok(())
+    }
+
+    /// Register the subscription for an accepted publish, so its streams and datagrams are routed to it.
+    pub(super) fn add_published(
+        &mut self,
+        id: u64,
+        track_alias: u64,
+        recv: SubscribeRecv,
+    ) -> Result<(), ServeError> {
+        let mut subscribes = self.subscribes.lock().unwrap();
+        if subscribes.contains_key(&id) {
+            return Err(ServeError::Duplicate);
+        }
+
+        // The alias was checked on PUBLISH, but may have been taken by a SUBSCRIBE_OK since.
+        let (namespace, name) = (&recv.info().track_namespace, &recv.info().track_name);
+        if self
+            .check_alias(&subscribes, track_alias, namespace, name)
+            .is_err()
+        {
+            return Err(ServeError::Duplicate);
+        }
+
+        if let Some(mut aliases) = self.subscribe_alias_map.lock_mut() {
+            aliases.entry(track_alias).or_insert(id);
+        }
+        self.watch_unused(&recv);
+        subscribes.insert(id, recv);
+
+        Ok(())
+    }
+
+    /// Check that a track alias used by the publisher isn't already in use by a different track.
+    /// The same track may be both subscribed and published with one alias.
+    fn check_alias(
+        &self,
+        subscribes: &HashMap<u64, SubscribeRecv>,
+        track_alias: u64,
+        namespace: &TrackNamespace,
+        name: &str,
+    ) -> Result<(), SessionError> {
+        let aliases = self.subscribe_alias_map.lock();
+        match aliases.get(&track_alias).and_then(|id| subscribes.get(id)) {
+            Some(existing) if !existing.is_track(namespace, name) => {
+                Err(SessionError::DuplicateTrackAlias(track_alias))
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// Handle the reception of a PublishNamespaceDone message from the publisher.
     fn recv_publish_namespace_done(
         &mut self,
@@ -195,17 +583,57 @@ impl Subscriber {
         Ok(())
     }
 
+    /// Handle the reception of a SubscribeNamespaceOk message from the publisher.
+    fn recv_subscribe_namespace_ok(
+        &mut self,
+        msg: &message::SubscribeNamespaceOk,
+    ) -> Result<(), SessionError> {
+        if let Some((_, subscribe_namespace)) =
+            self.subscribe_namespaces.lock().unwrap().get_mut(&msg.id)
+        {
+            subscribe_namespace.recv_ok()?;
+        }
+
+        Ok(())
+    }
+
+    /// Handle the reception of a SubscribeNamespaceError message from the publisher.
+    fn recv_subscribe_namespace_error(
+        &mut self,
+        msg: &message::SubscribeNamespaceError,
+    ) -> Result<(), SessionError> {
+        if let Some((_, subscribe_namespace)) =
+            self.subscribe_namespaces.lock().unwrap().remove(&msg.id)
+        {
+            subscribe_namespace.recv_error(ServeError::from_subscribe_namespace_error(
+                msg.error_code.into(),
+                &msg.reason_phrase.0,
+            ))?;
+        }
+
+        Ok(())
+    }
+
     /// Handle the reception of a SubscribeOk message from the publisher.
     fn recv_subscribe_ok(&mut self, msg: &message::SubscribeOk) -> Result<(), SessionError> {
-        if let Some(subscribe) = self.subscribes.lock().unwrap().get_mut(&msg.id) {
-            // Map track alias to subscription id for quick lookup when receiving streams/datagrams
-            self.subscribe_alias_map
-                .lock()
-                .unwrap()
-                .insert(msg.track_alias, msg.id);
+        let mut subscribes = self.subscribes.lock().unwrap();
+        if let Some(subscribe) = subscribes.get(&msg.id) {
+            let (namespace, name) = (
+                &subscribe.info().track_namespace,
+                &subscribe.info().track_name,
+            );
+            self.check_alias(&subscribes, msg.track_alias, namespace, name)?;
+        }
+
+        if let Some(subscribe) = subscribes.get_mut(&msg.id) {
+            // Map track alias to subscription id for quick lookup when receiving streams/datagrams.
+            // A track shared with a PUBLISH keeps routing to the existing subscription.
+            if let Some(mut aliases) = self.subscribe_alias_map.lock_mut() {
+                aliases.entry(msg.track_alias).or_insert(msg.id);
+            }
 
             // Notify the subscribe of the successful subscription
-            subscribe.ok(msg.track_alias)?;
+            subscribe.ok(msg)?;
         }
 
         Ok(())
@@ -213,13 +641,21 @@ impl Subscriber {
 
     /// Remove a subscribe from our map of active subscribes, and the alias map if present.
     fn remove_subscribe(&mut self, id: u64) -> Option<SubscribeRecv> {
-        if let Some(subscribe) = self.subscribes.lock().unwrap().remove(&id) {
-            // Remove from alias map if present
+        let mut subscribes = self.subscribes.lock().unwrap();
+        if let Some(subscribe) = subscribes.remove(&id) {
+            // Remove from alias map if present, handing the alias to another subscription sharing it.
             if let Some(track_alias) = subscribe.track_alias() {
-                self.subscribe_alias_map
-                    .lock()
-                    .unwrap()
-                    .remove(&track_alias);
+                if let Some(mut aliases) = self.subscribe_alias_map.lock_mut() {
+                    if aliases.get(&track_alias) == Some(&id) {
+                        aliases.remove(&track_alias);
+                        if let Some(other) = subscribes
+                            .values()
+                            .find(|other| other.track_alias() == Some(track_alias))
+                        {
+                            aliases.insert(track_alias, other.id());
+                        }
+                    }
+                }
             };
             Some(subscribe)
         } else {
@@ -230,7 +666,10 @@ impl Subscriber {
     /// Handle the reception of a SubscribeError message from the publisher.
     fn recv_subscribe_error(&mut self, msg: &message::SubscribeError) -> Result<(), SessionError> {
         if let Some(subscribe) = self.remove_subscribe(msg.id) {
-            subscribe.error(ServeError::Closed(msg.error_code))?;
+            subscribe.error(ServeError::from_subscribe_error(
+                msg.error_code.into(),
+                &msg.reason_phrase.0,
+            ))?;
         }
 
         Ok(())
@@ -239,20 +678,80 @@ impl Subscriber {
     /// Handle the reception of a PublishDone message from the publisher.
     fn recv_publish_done(&mut self, msg: &message::PublishDone) -> Result<(), SessionError> {
         if let Some(subscribe) = self.remove_subscribe(msg.id) {
-            subscribe.error(ServeError::Closed(msg.status_code))?;
+            subscribe.done(msg)?;
         }
 
         Ok(())
     }
 
     /// Handle the reception of a TrackStatusOk message from the publisher.
-    fn recv_track_status_ok(&mut self, _msg: &message::TrackStatusOk) -> Result<(), SessionError> {
-        // TODO: Expose this somehow?
-        // TODO: Also add a way to send a Track Status Request in the first place
+    fn recv_track_status_ok(&mut self, msg: &message::TrackStatusOk) -> Result<(), SessionError> {
+        if let Some(track_status) = self.track_statuses.lock().unwrap().remove(&msg.id) {
+            // Nobody is waiting for the reply if the request was abandoned.
+            match track_status.ok(msg) {
+                Ok(()) | Err(ServeError::Cancel) => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
 
         Ok(())
     }
 
+    /// Handle the reception of a TrackStatusError message from the publisher.
+    fn recv_track_status_error(
+        &mut self,
+        msg: &message::TrackStatusError,
+    ) -> Result<(), SessionError> {
+        if let Some(track_status) = self.track_statuses.lock().unwrap().remove(&msg.id) {
+            let err = ServeError::from_subscribe_error(msg.error_code.into(), &msg.reason_phrase.0);
+            match track_status.error(err) {
+                Ok(()) | Err(ServeError::Cancel) => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Remove a track status request from our map, once it's answered or abandoned.
+    pub(super) fn drop_track_status(&mut self, id: u64) {
+        self.track_statuses.lock().unwrap().remove(&id);
+    }
+
+    /// Handle the reception of a FetchOk message from the publisher.
+    fn recv_fetch_ok(&mut self, msg: &message::FetchOk) -> Result<(), SessionError> {
+        if let Some(fetch) = self.fetches.lock().unwrap().get_mut(&msg.id) {
+            fetch.ok(msg)?;
+        }
+
+        Ok(())
+    }
+
+    /// Handle the reception of a FetchError message from the publisher.
+    fn recv_fetch_error(&mut self, msg: &message::FetchError) -> Result<(), SessionError> {
+        if let Some(fetch) = self.fetches.lock().unwrap().remove(&msg.id) {
+            fetch.error(ServeError::from_fetch_error(
+                msg.error_code.into(),
+                &msg.reason_phrase.0,
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    /// Remove a fetch from our map of active fetches.
+    pub(super) fn drop_fetch(&mut self, id: u64) {
+        self.fetches.lock().unwrap().remove(&id);
+    }
+
+    /// Remove a namespace subscription from our map of active namespace subscriptions.
+    fn drop_subscribe_namespace(&mut self, namespace_prefix: &TrackNamespace) {
+        self.subscribe_namespaces
+            .lock()
+            .unwrap()
+            .retain(|_, (prefix, _)| prefix != namespace_prefix);
+    }
+
     /// Remove an announced namespace from our map of active announces.
     fn drop_publish_namespace(&mut self, namespace: &TrackNamespace) {
         self.announced.lock().unwrap().remove(namespace);
@@ -260,11 +759,30 @@ impl Subscriber {
 
     /// Get a subscribe id by track alias.
     fn get_subscribe_id_by_alias(&mut self, track_alias: u64) -> Option<u64> {
-        self.subscribe_alias_map
-            .lock()
-            .unwrap()
-            .get(&track_alias)
-            .cloned()
+        self.subscribe_alias_map.lock().get(&track_alias).cloned()
+    }
+
+    /// Wait for SUBSCRIBE_OK to assign a track alias, since a data stream can overtake it on the control
+    /// stream.  Gives up after UNKNOWN_ALIAS_TIMEOUT, leaving the stream to be rejected.
+    async fn wait_for_alias(&self, track_alias: u64) {
+        let assigned = async {
+            loop {
+                {
+                    let aliases = self.subscribe_alias_map.lock();
+                    if aliases.contains_key(&track_alias) {
+                        return;
+                    }
+
+                    match aliases.modified() {
+                        Some(notify) => notify,
+                        None => return,
+                    }
+                }
+                .await;
+            }
+        };
+
+        let _ = tokio::time::timeout(UNKNOWN_ALIAS_TIMEOUT, assigned).await;
     }
 
     /// Handle reception of a new stream from the QUIC session.
@@ -273,7 +791,7 @@ impl Subscriber {
         stream: web_transport::RecvStream,
     ) -> Result<(), SessionError> {
         log::trace!("[SUBSCRIBER] recv_stream: new stream received, decoding header");
-        let mut reader = Reader::new(stream);
+        let mut reader = Reader::new(stream, self.version);
 
         // Decode the stream header
         let stream_header: data::StreamHeader = reader.decode().await?;
@@ -294,23 +812,48 @@ impl Subscriber {
             }
         }
 
-        // No fetch support yet, so panic if fetch_header for now (via unwrap below)
+        if let Some(fetch_header) = stream_header.fetch_header {
+            return self.recv_fetch_stream(reader, fetch_header).await;
+        }
+
         let track_alias = stream_header.subgroup_header.as_ref().unwrap().track_alias;
         log::trace!(
             "[SUBSCRIBER] recv_stream: stream for subscription track_alias={}",
             track_alias
         );
+        self.wait_for_alias(track_alias).await;
+
+        // Stop reading the stream once we've unsubscribed, since nobody will read the objects.
+        let unsubscribed = self.get_subscribe_id_by_alias(track_alias).and_then(|id| {
+            self.subscribes
+                .lock()
+                .unwrap()
+                .get(&id)
+                .map(|subscribe| subscribe.unsubscribed())
+        });
 
         let mlog = self.mlog.clone();
-        let res = self.recv_stream_inner(reader, stream_header, mlog).await;
+        let res = match unsubscribed {
+            Some(unsubscribed) => tokio::select! {
+                res = self.recv_stream_inner(reader, stream_header, mlog) => res,
+                _ = unsubscribed => {
+                    log::debug!(
+                        "[SUBSCRIBER] recv_stream: unsubscribed, stopping stream for track_alias={}",
+                        track_alias
+                    );
+                    Ok(())
+                }
+            },
+            None => self.recv_stream_inner(reader, stream_header, mlog).await,
+        };
         if let Err(SessionError::Serve(err)) = &res {
             log::warn!(
                 "[SUBSCRIBER] recv_stream: stream processing error for track_alias={}: {:?}",
                 track_alias,
                 err
             );
-            // The writer is closed, so we should teriminate.
-            // TODO it would be nice to do this immediately when the Writer is closed.
+            // The writer is closed, so we should terminate.  The session unsubscribes as soon as every
+            // reader is dropped, but an error on a stream that was already in flight can still get here.
             if let Some(subscribe_id) = self.get_subscribe_id_by_alias(track_alias) {
                 if let Some(subscribe) = self.remove_subscribe(subscribe_id) {
                     subscribe.error(err.clone())?;
@@ -334,10 +877,28 @@ impl Subscriber {
             track_alias
         );
 
+        // Hold live subgroups back until a joining fetch has filled in the head of the track.
+        loop {
+            let joining = match self.get_subscribe_id_by_alias(track_alias) {
+                Some(subscribe_id) => self
+                    .subscribes
+                    .lock()
+                    .unwrap()
+                    .get(&subscribe_id)
+                    .and_then(|subscribe| subscribe.joining()),
+                None => None,
+            };
+
+            match joining {
+                Some(changed) => changed.await,
+                None => break,
+            }
+        }
+
         // This is super silly, but I couldn't figure out a way to avoid the mutex guard across awaits.
         enum Writer {
-            //Fetch(serve::FetchWriter),
-            Subgroup(serve::SubgroupWriter),
+            // The writer, and the first object_id not already delivered by a joining fetch.
+            Subgroup(serve::SubgroupWriter, u64),
         }
 
         let writer = {
@@ -345,19 +906,35 @@ impl Subscriber {
             if let Some(subscribe_id) = self.get_subscribe_id_by_alias(track_alias) {
                 // Look up the subscribe by id
                 let mut subscribes = self.subscribes.lock().unwrap();
-                let subscribe = subscribes.get_mut(&subscribe_id).ok_or_else(|| {
+                if !subscribes.contains_key(&subscribe_id) {
                     log::error!(
                         "[SUBSCRIBER] recv_stream_inner: subscribe_id={} not found, track_alias={}",
                         subscribe_id,
                         track_alias
                     );
-                    ServeError::NotFound
-                })?;
+                    return Err(ServeError::NotFound.into());
+                }
 
                 // Create the appropriate writer based on the stream header type
                 if stream_header.header_type.is_subgroup() {
                     log::trace!("[SUBSCRIBER] recv_stream_inner: creating subgroup writer");
-                    Writer::Subgroup(subscribe.subgroup(stream_header.subgroup_header.unwrap())?)
+                    match Self::create_shared_subgroup(
+                        &mut subscribes,
+                        subscribe_id,
+                        track_alias,
+                        stream_header.subgroup_header.unwrap(),
+                    )? {
+                        Some((writer, first_object_id)) => {
+                            Writer::Subgroup(writer, first_object_id)
+                        }
+                        None => {
+                            log::debug!(
+                                "[SUBSCRIBER] recv_stream_inner: every subscription already has this subgroup, track_alias={}",
+                                track_alias
+                            );
+                            return Ok(());
+                        }
+                    }
                 } else {
                     log::error!(
                         "[SUBSCRIBER] recv_stream_inner: stream header_type={} not supported",
@@ -379,11 +956,16 @@ impl Subscriber {
 
         // Handle the stream based on the writer type
         match writer {
-            //Writer::Fetch(fetch) => Self::recv_fetch(fetch, reader).await?,
-            Writer::Subgroup(subgroup_writer) => {
+            Writer::Subgroup(subgroup_writer, first_object_id) => {
                 log::trace!("[SUBSCRIBER] recv_stream_inner: receiving subgroup data");
-                Self::recv_subgroup(stream_header.header_type, subgroup_writer, reader, mlog)
-                    .await?
+                Self::recv_subgroup(
+                    stream_header.header_type,
+                    subgroup_writer,
+                    first_object_id,
+                    reader,
+                    mlog,
+                )
+                .await?
             }
         };
 
@@ -394,10 +976,46 @@ impl Subscriber {
         Ok(())
     }
 
+    /// Create the subgroup for a stream on the subscription its track alias routes to.
+    /// A track that's both subscribed and published shares one alias, and the publisher sends a copy of each
+    /// subgroup per request, so a copy the first subscription already has goes to the next one sharing the alias.
+    /// Returns None if every subscription sharing the alias already has the subgroup.
+    fn create_shared_subgroup(
+        subscribes: &mut HashMap<u64, SubscribeRecv>,
+        subscribe_id: u64,
+        track_alias: u64,
+        header: data::SubgroupHeader,
+    ) -> Result<Option<(serve::SubgroupWriter, u64)>, ServeError> {
+        let mut shared: Vec<u64> = subscribes
+            .iter()
+            .filter(|(id, subscribe)| {
+                **id != subscribe_id && subscribe.track_alias() == Some(track_alias)
+            })
+            .map(|(id, _)| *id)
+            .collect();
+        shared.sort();
+
+        for id in std::iter::once(subscribe_id).chain(shared) {
+            let subscribe = match subscribes.get_mut(&id) {
+                Some(subscribe) => subscribe,
+                None => continue,
+            };
+
+            match subscribe.subgroup(header.clone()) {
+                Ok(writer) => return Ok(Some(writer)),
+                Err(ServeError::Duplicate) => continue,
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(None)
+    }
+
     /// If new stream is a Subgroup stream, handle reception of subgroup objects and payloads.
     async fn recv_subgroup(
         stream_header_type: data::StreamHeaderType,
         mut subgroup_writer: serve::SubgroupWriter,
+        first_object_id: u64,
         mut reader: Reader,
         mlog: Option<Arc<Mutex<mlog::MlogWriter>>>,
     ) -> Result<(), SessionError> {
@@ -409,7 +1027,7 @@ impl Subscriber {
         );
 
         let mut object_count = 0;
-        let mut current_object_id = 0u64;
+        let mut object_ids = data::ObjectIdDelta::default();
         while !reader.done().await? {
             log::trace!(
                 "[SUBSCRIBER] recv_subgroup: reading object #{} (has_ext_headers={})",
@@ -456,22 +1074,21 @@ impl Subscriber {
                     }
                 };
 
-            // Calculate absolute object_id from delta
-            current_object_id += object_id_delta;
+            let current_object_id = object_ids.decode(object_id_delta)?;
 
             // Log subgroup object parsed/received
             if let Some(ref mlog) = mlog {
                 if let Ok(mut mlog_guard) = mlog.lock() {
                     let time = mlog_guard.elapsed_ms();
                     let stream_id = 0; // TODO: Placeholder, need actual QUIC stream ID
-                    let event = if let Some(obj_ext) = decoded_object {
+                    let event = if let Some(obj_ext) = &decoded_object {
                         mlog::subgroup_object_ext_parsed(
                             time,
                             stream_id,
                             subgroup_writer.info.group_id,
                             subgroup_writer.info.subgroup_id,
                             current_object_id,
-                            &obj_ext,
+                            obj_ext,
                         )
                     } else {
                         // For non-extension objects, create a temporary SubgroupObject for logging
@@ -493,9 +1110,42 @@ impl Subscriber {
                 }
             }
 
-            // TODO SLG - object_id_delta, extension headers and object status are being ignored and not passed on
+            // Skip objects that a joining fetch already delivered.
+            if current_object_id < first_object_id {
+                log::trace!(
+                    "[SUBSCRIBER] recv_subgroup: skipping object_id={} already received by joining fetch",
+                    current_object_id
+                );
+                while remaining_bytes > 0 {
+                    let data = reader
+                        .read_chunk(remaining_bytes)
+                        .await?
+                        .ok_or(SessionError::WrongSize)?;
+                    remaining_bytes -= data.len();
+                }
+                continue;
+            }
+
+            let extension_headers = decoded_object
+                .map(|object| object.extension_headers)
+                .unwrap_or_default();
 
-            let mut object_writer = subgroup_writer.create(remaining_bytes)?;
+            // Status objects have no payload; forward them so readers see the end of the group or track.
+            if let Some(status) =
+                status.filter(|status| *status != data::ObjectStatus::NormalObject)
+            {
+                log::debug!(
+                    "[SUBSCRIBER] recv_subgroup: object #{} has status={:?}",
+                    object_count + 1,
+                    status
+                );
+                subgroup_writer.write_status_at(current_object_id, status, extension_headers)?;
+                object_count += 1;
+                continue;
+            }
+
+            let mut object_writer =
+                subgroup_writer.create_at(current_object_id, remaining_bytes, extension_headers)?;
             log::trace!(
                 "[SUBSCRIBER] recv_subgroup: reading payload for object #{} ({} bytes)",
                 object_count + 1,
@@ -545,10 +1195,113 @@ impl Subscriber {
         Ok(())
     }
 
+    /// Handle reception of a fetch stream, forwarding each object to the matching Fetch.
+    async fn recv_fetch_stream(
+        &mut self,
+        reader: Reader,
+        fetch_header: data::FetchHeader,
+    ) -> Result<(), SessionError> {
+        let request_id = fetch_header.request_id;
+        log::trace!(
+            "[SUBSCRIBER] recv_fetch_stream: stream for fetch request_id={}",
+            request_id
+        );
+
+        let res = self.recv_fetch_objects(reader, request_id).await;
+        match &res {
+            Ok(()) => {
+                if let Some(fetch) = self.fetches.lock().unwrap().get_mut(&request_id) {
+                    fetch.fin()?;
+                }
+            }
+            Err(err) => {
+                log::warn!(
+                    "[SUBSCRIBER] recv_fetch_stream: stream processing error for request_id={}: {:?}",
+                    request_id,
+                    err
+                );
+                if let Some(fetch) = self.fetches.lock().unwrap().remove(&request_id) {
+                    fetch.error(err.clone().into())?;
+                }
+            }
+        }
+
+        res
+    }
+
+    /// Continue handling the reception of a fetch stream, reading objects until FIN.
+    async fn recv_fetch_objects(
+        &mut self,
+        mut reader: Reader,
+        request_id: u64,
+    ) -> Result<(), SessionError> {
+        let mut object_count = 0;
+        while !reader.done().await? {
+            // Stop reading while the application is behind, so the publisher is flow controlled.
+            loop {
+                let full = match self.fetches.lock().unwrap().get(&request_id) {
+                    Some(fetch) => fetch.full()?,
+                    None => return Err(ServeError::Cancel.into()),
+                };
+
+                match full {
+                    Some(read) => read.await,
+                    None => break,
+                }
+            }
+
+            let object = reader.decode::<data::FetchObject>().await?;
+            log::debug!(
+                "[SUBSCRIBER] recv_fetch_objects: object #{} - group_id={}, subgroup_id={}, object_id={}, payload_length={}, status={:?}",
+                object_count + 1,
+                object.group_id,
+                object.subgroup_id,
+                object.object_id,
+                object.payload_length,
+                object.status
+            );
+
+            let mut payload = bytes::BytesMut::with_capacity(object.payload_length);
+            while payload.len() < object.payload_length {
+                let data = reader
+                    .read_chunk(object.payload_length - payload.len())
+                    .await?
+                    .ok_or(SessionError::WrongSize)?;
+                payload.extend_from_slice(&data);
+            }
+
+            let object = FetchedObject {
+                group_id: object.group_id,
+                subgroup_id: object.subgroup_id,
+                object_id: object.object_id,
+                priority: object.publisher_priority,
+                extension_headers: object.extension_headers,
+                status: object.status.unwrap_or(data::ObjectStatus::NormalObject),
+                payload: payload.freeze(),
+            };
+
+            // The fetch may have been cancelled in the meantime.
+            match self.fetches.lock().unwrap().get_mut(&request_id) {
+                Some(fetch) => fetch.object(object)?,
+                None => return Err(ServeError::Cancel.into()),
+            }
+
+            object_count += 1;
+        }
+
+        log::info!(
+            "[SUBSCRIBER] recv_fetch_objects: completed fetch request_id={} ({} objects received)",
+            request_id,
+            object_count
+        );
+
+        Ok(())
+    }
+
     /// Handle reception of a datagram from the QUIC session.
     pub fn recv_datagram(&mut self, datagram: bytes::Bytes) -> Result<(), SessionError> {
         let mut cursor = io::Cursor::new(datagram);
-        let datagram = data::Datagram::decode(&mut cursor)?;
+        let datagram = data::Datagram::decode_versioned(&mut cursor, self.version)?;
 
         // Look up the subscribe id for this track alias
         if let Some(subscribe_id) = self.get_subscribe_id_by_alias(datagram.track_alias) {
@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::coding::TrackNamespace;
+
+type TrackKey = (TrackNamespace, String);
+
+#[derive(Default)]
+struct TrackAliasesState {
+    next: u64,
+
+    /// The alias of each track being served, with the number of subscriptions sharing it.
+    tracks: HashMap<TrackKey, (u64, usize)>,
+}
+
+/// Allocates the track aliases we send in SUBSCRIBE_OK and PUBLISH, independently of request IDs.
+/// A track served to the peer by both a SUBSCRIBE and a PUBLISH shares one alias.  Aliases aren't reused
+/// once released, so late streams for an old subscription can't be mistaken for a new one.
+#[derive(Clone, Default)]
+pub(super) struct TrackAliases {
+    state: Arc<Mutex<TrackAliasesState>>,
+}
+
+impl TrackAliases {
+    /// The alias for the track, allocating one unless the track is already being served.
+    pub fn acquire(&self, namespace: &TrackNamespace, name: &str) -> TrackAlias {
+        let key = (namespace.clone(), name.to_string());
+
+        let mut state = self.state.lock().unwrap();
+        let next = state.next;
+        let (id, refs) = state.tracks.entry(key.clone()).or_insert((next, 0));
+        *refs += 1;
+
+        let id = *id;
+        if id == next {
+            state.next += 1;
+        }
+
+        TrackAlias {
+            id,
+            key,
+            aliases: self.clone(),
+        }
+    }
+}
+
+/// A track alias held by a subscription, released once every subscription sharing it is dropped.
+pub(super) struct TrackAlias {
+    id: u64,
+    key: TrackKey,
+    aliases: TrackAliases,
+}
+
+impl TrackAlias {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+}
+
+impl Clone for TrackAlias {
+    fn clone(&self) -> Self {
+        let mut state = self.aliases.state.lock().unwrap();
+        if let Some((_, refs)) = state.tracks.get_mut(&self.key) {
+            *refs += 1;
+        }
+
+        Self {
+            id: self.id,
+            key: self.key.clone(),
+            aliases: self.aliases.clone(),
+        }
+    }
+}
+
+impl Drop for TrackAlias {
+    fn drop(&mut self) {
+        let mut state = self.aliases.state.lock().unwrap();
+        if let Some((_, refs)) = state.tracks.get_mut(&self.key) {
+            *refs -= 1;
+            if *refs == 0 {
+                state.tracks.remove(&self.key);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn acquire_release() {
+        let aliases = TrackAliases::default();
+        let namespace = TrackNamespace::from_utf8_path("live");
+
+        let video = aliases.acquire(&namespace, "video");
+        let audio = aliases.acquire(&namespace, "audio");
+        assert_eq!(video.id(), 0);
+        assert_eq!(audio.id(), 1);
+
+        // The same track shares its alias, until every holder is dropped.
+        let shared = aliases.acquire(&namespace, "video");
+        assert_eq!(shared.id(), 0);
+        let cloned = shared.clone();
+        drop(video);
+        drop(shared);
+        assert_eq!(aliases.acquire(&namespace, "video").id(), 0);
+
+        // Released aliases aren't reused.
+        drop(cloned);
+        assert_eq!(aliases.acquire(&namespace, "video").id(), 2);
+    }
+}
@@ -0,0 +1,138 @@
+use crate::coding::{KeyValuePairs, Location};
+use crate::message::{self, GroupOrder};
+use crate::serve::ServeError;
+use crate::watch::State;
+
+use super::Subscriber;
+
+/// The status of a track, as reported by the publisher in TRACK_STATUS_OK.
+/// A track that can't be served is reported as an error instead, such as [ServeError::NotFound].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TrackStatus {
+    /// Whether the track has started.
+    pub status: TrackStatusCode,
+
+    /// The time in milliseconds after which a subscription to the track would expire, or 0 if it never does.
+    pub expires: u64,
+
+    /// The order the publisher would deliver groups in.
+    pub group_order: GroupOrder,
+
+    /// The largest object published so far, or None if the track has no content yet.
+    pub largest_location: Option<Location>,
+
+    pub params: KeyValuePairs,
+}
+
+/// Whether a track has started, which draft-14 signals with content_exists in place of the status code of
+/// earlier drafts.  A track that doesn't exist is reported as an error instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrackStatusCode {
+    /// Objects have been published, up to [TrackStatus::largest_location].
+    InProgress,
+
+    /// The track exists, but nothing has been published yet.
+    NotYetBegun,
+}
+
+impl From<&message::TrackStatusOk> for TrackStatus {
+    fn from(msg: &message::TrackStatusOk) -> Self {
+        Self {
+            status: match msg.content_exists {
+                true => TrackStatusCode::InProgress,
+                false => TrackStatusCode::NotYetBegun,
+            },
+            expires: msg.expires,
+            group_order: msg.group_order,
+            largest_location: msg.largest_location.filter(|_| msg.content_exists),
+            params: msg.params.clone(),
+        }
+    }
+}
+
+struct TrackStatusState {
+    ok: Option<TrackStatus>,
+    closed: Result<(), ServeError>,
+}
+
+impl Default for TrackStatusState {
+    fn default() -> Self {
+        Self {
+            ok: None,
+            closed: Ok(()),
+        }
+    }
+}
+
+// Held by Subscriber::track_status while waiting for a response
+pub(super) struct TrackStatusRequest {
+    state: State<TrackStatusState>,
+    subscriber: Subscriber,
+    id: u64,
+}
+
+impl TrackStatusRequest {
+    pub fn new(subscriber: Subscriber, id: u64) -> (Self, TrackStatusRecv) {
+        let (send, recv) = State::default().split();
+
+        let send = Self {
+            state: send,
+            subscriber,
+            id,
+        };
+        let recv = TrackStatusRecv { state: recv };
+
+        (send, recv)
+    }
+
+    /// Wait until a TRACK_STATUS_OK or TRACK_STATUS_ERROR is received.
+    pub async fn reply(&self) -> Result<TrackStatus, ServeError> {
+        loop {
+            {
+                let state = self.state.lock();
+                if let Some(ok) = &state.ok {
+                    return Ok(ok.clone());
+                }
+                state.closed.clone()?;
+
+                match state.modified() {
+                    Some(notified) => notified,
+                    None => return Err(ServeError::Cancel),
+                }
+            }
+            .await;
+        }
+    }
+}
+
+impl Drop for TrackStatusRequest {
+    fn drop(&mut self) {
+        self.subscriber.drop_track_status(self.id);
+    }
+}
+
+pub(super) struct TrackStatusRecv {
+    state: State<TrackStatusState>,
+}
+
+impl TrackStatusRecv {
+    pub fn ok(self, msg: &message::TrackStatusOk) -> Result<(), ServeError> {
+        let state = self.state.lock();
+        state.closed.clone()?;
+
+        let mut state = state.into_mut().ok_or(ServeError::Cancel)?;
+        state.ok = Some(msg.into());
+
+        Ok(())
+    }
+
+    pub fn error(self, err: ServeError) -> Result<(), ServeError> {
+        let state = self.state.lock();
+        state.closed.clone()?;
+
+        let mut state = state.into_mut().ok_or(ServeError::Cancel)?;
+        state.closed = Err(err);
+
+        Ok(())
+    }
+}
@@ -1,7 +1,7 @@
 use super::{Publisher, SessionError};
 use crate::coding::ReasonPhrase;
 use crate::message;
-use crate::serve;
+use crate::serve::{self, ServeError};
 
 pub struct TrackStatusRequested {
     publisher: Publisher,
@@ -16,15 +16,12 @@ impl TrackStatusRequested {
         }
     }
 
-    pub fn respond_error(
-        &mut self,
-        error_code: u64,
-        error_message: &str,
-    ) -> Result<(), SessionError> {
+    /// Reject the request, using the SUBSCRIBE_ERROR code for the error.
+    pub fn respond_error(mut self, err: ServeError) -> Result<(), SessionError> {
         let status_error = message::TrackStatusError {
             id: self.request_msg.id,
-            error_code,
-            reason_phrase: ReasonPhrase(error_message.to_string()),
+            error_code: err.subscribe_error_code().code(),
+            reason_phrase: ReasonPhrase(err.to_string()),
         };
         self.publisher.send_message(status_error);
         Ok(())
@@ -35,8 +32,11 @@ impl TrackStatusRequested {
         self.publisher.send_message(message::TrackStatusOk {
             id: self.request_msg.id,
             track_alias: self.request_msg.id, // TODO SLG does a track alias make sense in track_status response?  Using track_status request id for now
-            expires: 0,                       // TODO SLG
-            group_order: message::GroupOrder::Ascending, // TODO: resolve correct value from publisher / subscriber prefs
+            expires: track
+                .expires
+                .map(|expires| expires.as_millis() as u64)
+                .unwrap_or(0),
+            group_order: self.request_msg.group_order.resolve(track.group_order),
             content_exists: track.largest_location().is_some(),
             largest_location: track.largest_location(),
             params: Default::default(),
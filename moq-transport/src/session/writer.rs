@@ -1,6 +1,7 @@
 use std::io;
 
-use crate::coding::{Encode, EncodeError};
+use crate::coding::{EncodeError, EncodeVersioned};
+use crate::setup;
 
 use super::SessionError;
 use bytes::Buf;
@@ -8,24 +9,33 @@ use bytes::Buf;
 pub struct Writer {
     stream: web_transport::SendStream,
     buffer: bytes::BytesMut,
+
+    /// The version whose wire format is encoded
+    version: setup::Version,
 }
 
 impl Writer {
-    pub fn new(stream: web_transport::SendStream) -> Self {
+    pub fn new(stream: web_transport::SendStream, version: setup::Version) -> Self {
         Self {
             stream,
             buffer: Default::default(),
+            version,
         }
     }
 
-    pub async fn encode<T: Encode>(&mut self, msg: &T) -> Result<(), SessionError> {
+    /// Switch to the version negotiated in SETUP.
+    pub fn set_version(&mut self, version: setup::Version) {
+        self.version = version;
+    }
+
+    pub async fn encode<T: EncodeVersioned>(&mut self, msg: &T) -> Result<(), SessionError> {
         self.buffer.clear();
         log::trace!(
             "[WRITER] encode: encoding {} to buffer",
             std::any::type_name::<T>()
         );
 
-        msg.encode(&mut self.buffer)?;
+        msg.encode_bytes(&mut self.buffer, self.version)?;
         let encoded_len = self.buffer.len();
         log::debug!(
             "[WRITER] encode: encoded {} ({} bytes), sending to stream",
@@ -85,4 +95,10 @@ impl Writer {
 
         Ok(())
     }
+
+    /// Abruptly terminate the stream with the given error code.
+    pub fn reset(self, code: u32) {
+        log::debug!("[WRITER] reset: resetting stream with code={}", code);
+        self.stream.reset(code);
+    }
 }
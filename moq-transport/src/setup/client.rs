@@ -1,5 +1,8 @@
-use super::Versions;
-use crate::coding::{Decode, DecodeError, Encode, EncodeError, KeyValuePairs};
+use super::{Version, Versions};
+use crate::coding::{
+    decode_length_prefixed, Decode, DecodeError, DecodeVersioned, Encode, EncodeError,
+    EncodeVersioned, KeyValuePairs,
+};
 
 /// Sent by the client to setup the session.
 /// This CLIENT_SETUP message is used by moq-transport draft versions 11 and later.
@@ -23,13 +26,12 @@ impl Decode for Client {
             return Err(DecodeError::InvalidMessage(typ));
         }
 
-        let _len = u16::decode(r)?;
-        // TODO: Check the length of the message.
+        decode_length_prefixed(r, |body| {
+            let versions = Versions::decode(body)?;
+            let params = KeyValuePairs::decode(body)?;
 
-        let versions = Versions::decode(r)?;
-        let params = KeyValuePairs::decode(r)?;
-
-        Ok(Self { versions, params })
+            Ok(Self { versions, params })
+        })
     }
 }
 
@@ -63,6 +65,23 @@ impl Encode for Client {
     }
 }
 
+// SETUP is sent before a version is negotiated, so it's the same in every version.
+impl DecodeVersioned for Client {
+    fn decode_versioned<R: bytes::Buf>(r: &mut R, _version: Version) -> Result<Self, DecodeError> {
+        Self::decode(r)
+    }
+}
+
+impl EncodeVersioned for Client {
+    fn encode_versioned<W: bytes::BufMut>(
+        &self,
+        w: &mut W,
+        _version: Version,
+    ) -> Result<(), EncodeError> {
+        self.encode(w)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,5 +1,8 @@
 use super::Version;
-use crate::coding::{Decode, DecodeError, Encode, EncodeError, KeyValuePairs};
+use crate::coding::{
+    decode_length_prefixed, Decode, DecodeError, DecodeVersioned, Encode, EncodeError,
+    EncodeVersioned, KeyValuePairs,
+};
 
 /// Sent by the server in response to a client setup.
 /// This SERVER_SETUP message is used by moq-transport draft versions 11 and later.
@@ -23,13 +26,12 @@ impl Decode for Server {
             return Err(DecodeError::InvalidMessage(typ));
         }
 
-        let _len = u16::decode(r)?;
-        // TODO: Check the length of the message.
+        decode_length_prefixed(r, |body| {
+            let version = Version::decode(body)?;
+            let params = KeyValuePairs::decode(body)?;
 
-        let version = Version::decode(r)?;
-        let params = KeyValuePairs::decode(r)?;
-
-        Ok(Self { version, params })
+            Ok(Self { version, params })
+        })
     }
 }
 
@@ -62,6 +64,23 @@ impl Encode for Server {
     }
 }
 
+// Like CLIENT_SETUP, this is decoded before the version is known.
+impl DecodeVersioned for Server {
+    fn decode_versioned<R: bytes::Buf>(r: &mut R, _version: Version) -> Result<Self, DecodeError> {
+        Self::decode(r)
+    }
+}
+
+impl EncodeVersioned for Server {
+    fn encode_versioned<W: bytes::BufMut>(
+        &self,
+        w: &mut W,
+        _version: Version,
+    ) -> Result<(), EncodeError> {
+        self.encode(w)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
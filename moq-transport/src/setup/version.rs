@@ -23,6 +23,19 @@ impl Version {
 
     /// https://www.ietf.org/archive/id/draft-ietf-moq-transport-14.html
     pub const DRAFT_14: Version = Version(0xff00000e);
+
+    /// https://www.ietf.org/archive/id/draft-ietf-moq-transport-15.html
+    pub const DRAFT_15: Version = Version(0xff00000f);
+
+    /// The versions we have a wire format for, which are offered and accepted by default.
+    pub const SUPPORTED: &'static [Version] = &[Version::DRAFT_14, Version::DRAFT_15];
+
+    /// The newest supported version, used for the SETUP exchange before a version is negotiated.
+    pub const LATEST: Version = Version::DRAFT_15;
+
+    pub fn is_supported(&self) -> bool {
+        Self::SUPPORTED.contains(self)
+    }
 }
 
 impl From<u32> for Version {
@@ -149,4 +162,13 @@ mod tests {
         let decoded = Versions::decode(&mut buf).unwrap();
         assert_eq!(decoded, versions);
     }
+
+    #[test]
+    fn supported() {
+        assert!(Version::LATEST.is_supported());
+        assert!(Version::DRAFT_14.is_supported());
+        assert!(Version::DRAFT_15.is_supported());
+        assert!(!Version::DRAFT_13.is_supported());
+        assert!(!Version(1).is_supported());
+    }
 }
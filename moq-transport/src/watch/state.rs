@@ -88,6 +88,34 @@ impl<T> State<T> {
         }
     }
 
+    /// Returns true once the other half has been dropped.
+    pub fn is_dropped(&self) -> bool {
+        self.lock().modified().is_none()
+    }
+
+    /// Returns a future that resolves once the other half, or every clone of this half, is dropped.
+    /// The future doesn't keep this half alive.
+    pub fn dropped(&self) -> impl Future<Output = ()> {
+        let state = self.downgrade();
+
+        async move {
+            loop {
+                let notify = match state.upgrade() {
+                    Some(state) => {
+                        let notify = state.lock().modified();
+                        notify
+                    }
+                    None => return,
+                };
+
+                match notify {
+                    Some(notify) => notify.await,
+                    None => return,
+                }
+            }
+        }
+    }
+
     pub fn split(self) -> (Self, Self) {
         let state = self.state.clone();
         (
@@ -252,3 +280,39 @@ impl<T> Drop for StateDrop<T> {
         state.notify();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn dropped_by_other_half() {
+        let (state, other) = State::new(0).split();
+        let mut dropped = Box::pin(state.dropped());
+        assert!(!state.is_dropped());
+        assert!(futures::poll!(&mut dropped).is_pending());
+
+        // Clones of the other half keep it alive.
+        let clone = other.clone();
+        drop(other);
+        assert!(futures::poll!(&mut dropped).is_pending());
+
+        drop(clone);
+        assert!(state.is_dropped());
+        assert!(futures::poll!(&mut dropped).is_ready());
+    }
+
+    #[tokio::test]
+    async fn dropped_by_every_clone() {
+        let (state, _other) = State::new(0).split();
+        let clone = state.clone();
+
+        // The future doesn't keep this half alive.
+        let mut dropped = Box::pin(state.dropped());
+        drop(state);
+        assert!(futures::poll!(&mut dropped).is_pending());
+
+        drop(clone);
+        assert!(futures::poll!(&mut dropped).is_ready());
+    }
+}
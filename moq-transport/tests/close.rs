@@ -0,0 +1,86 @@
+mod common;
+
+use moq_transport::coding::KeyValuePairs;
+use moq_transport::message::{self, FilterType, GroupOrder};
+use moq_transport::session::{Session, SessionConfig, SessionError, TerminationCode};
+
+#[tokio::test]
+async fn peer_close_carries_code_and_reason() {
+    let (client, server) = common::connect().await;
+
+    server
+        .handle
+        .close(TerminationCode::GoawayTimeout, "took too long");
+
+    let err = common::timeout(client.run).await.unwrap().unwrap_err();
+    match err {
+        SessionError::Closed(code, reason) => {
+            assert_eq!(code, TerminationCode::GoawayTimeout);
+            assert_eq!(reason, "took too long");
+        }
+        err => panic!("unexpected error: {:?}", err),
+    }
+}
+
+#[tokio::test]
+async fn peer_close_without_reason() {
+    let (client, server) = common::connect().await;
+
+    server.handle.close(TerminationCode::NoError, "");
+
+    let err = common::timeout(client.run).await.unwrap().unwrap_err();
+    assert!(
+        matches!(
+            err,
+            SessionError::Closed(TerminationCode::NoError, ref reason) if reason.is_empty()
+        ),
+        "{:?}",
+        err
+    );
+}
+
+#[tokio::test]
+async fn reused_request_id_closes_with_invalid_request_id() {
+    let mut endpoints = common::Endpoints::new();
+    let (client_conn, server_conn) = endpoints.connect_quic().await;
+
+    let (mut client, server) = common::timeout(async {
+        tokio::join!(
+            common::RawControl::connect(client_conn, 100),
+            Session::accept_with_config(server_conn, SessionConfig::default()),
+        )
+    })
+    .await;
+    let (session, _, _) = server.expect("server SETUP failed");
+    let server = tokio::spawn(session.run());
+
+    let subscribe = message::Subscribe {
+        id: 0,
+        track_namespace: common::namespace(),
+        track_name: "video".to_string(),
+        subscriber_priority: 0,
+        group_order: GroupOrder::Publisher,
+        forward: true,
+        filter_type: FilterType::LargestObject,
+        start_location: None,
+        end_group_id: None,
+        params: KeyValuePairs::new(),
+    };
+    client.send(subscribe.clone()).await;
+    client.send(subscribe).await;
+
+    // The server closes the session, and the client sees INVALID_REQUEST_ID.
+    let err = common::timeout(server).await.unwrap().unwrap_err();
+    assert_eq!(err.code(), 0x4, "{:?}", err);
+
+    let err = SessionError::from(common::timeout(client.session.closed()).await);
+    assert!(
+        matches!(
+            err,
+            SessionError::Closed(TerminationCode::InvalidRequestId, _)
+        ),
+        "{:?}",
+        err
+    );
+    assert_eq!(err.code(), 0x4);
+}
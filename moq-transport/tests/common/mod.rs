@@ -0,0 +1,394 @@
+//! Runs a client and a server session against each other over QUIC on localhost.
+#![allow(dead_code)]
+
+use std::future::Future;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use bytes::{Buf, Bytes, BytesMut};
+use moq_native_ietf::{quic, tls};
+use moq_transport::coding::{Decode, DecodeError, Encode, KeyValuePairs, TrackNamespace};
+use moq_transport::message::Message;
+use moq_transport::serve::{
+    Subgroup, SubgroupReader, SubgroupsReader, SubgroupsWriter, Track, TrackReader,
+    TrackReaderMode, TrackWriter,
+};
+use moq_transport::session::{
+    Publisher, Session, SessionConfig, SessionError, SessionHandle, Subscribe, Subscriber,
+};
+use moq_transport::setup;
+use tokio::task::JoinHandle;
+
+/// One end of a running session.
+pub struct Peer {
+    pub publisher: Publisher,
+    pub subscriber: Subscriber,
+    pub handle: SessionHandle,
+    pub run: JoinHandle<Result<(), SessionError>>,
+
+    /// The version negotiated in SETUP.
+    pub version: setup::Version,
+
+    // Keep the endpoint around for the lifetime of the session.
+    _quic: quic::Client,
+}
+
+/// Connect a client to a server with the default config.
+pub async fn connect() -> (Peer, Peer) {
+    connect_with_config(SessionConfig::default(), SessionConfig::default()).await
+}
+
+/// Connect a client to a server, returning the (client, server) ends once SETUP is done.
+pub async fn connect_with_config(client: SessionConfig, server: SessionConfig) -> (Peer, Peer) {
+    Endpoints::new().connect(client, server).await
+}
+
+/// A client and server endpoint, which can connect more than once, such as to migrate after a GOAWAY.
+pub struct Endpoints {
+    client: quic::Client,
+    server: quic::Server,
+    url: url::Url,
+}
+
+impl Endpoints {
+    pub fn new() -> Self {
+        let cert = cert_path("localhost.crt");
+        let key = cert_path("localhost.key");
+
+        let server = endpoint(tls::Args {
+            cert: vec![cert.clone()],
+            key: vec![key],
+            root: vec![cert.clone()],
+            disable_verify: true,
+        });
+        let client = endpoint(tls::Args {
+            cert: Vec::new(),
+            key: Vec::new(),
+            root: vec![cert],
+            disable_verify: true,
+        });
+
+        let server = server.server.expect("no server config");
+        let addr = server.local_addr().unwrap();
+        let url = url::Url::parse(&format!("moqt://127.0.0.1:{}", addr.port())).unwrap();
+
+        Self {
+            client: client.client,
+            server,
+            url,
+        }
+    }
+
+    /// Open a QUIC connection, returning the (client, server) ends before any SETUP.
+    pub async fn connect_quic(&mut self) -> (web_transport::Session, web_transport::Session) {
+        let (connected, accepted) =
+            timeout(async { tokio::join!(self.client.connect(&self.url), self.server.accept()) })
+                .await;
+        let (client, _) = connected.expect("failed to connect");
+        let (server, _) = accepted.expect("failed to accept");
+
+        (client, server)
+    }
+
+    /// Connect a client session to a server session, returning the (client, server) ends once SETUP is done.
+    pub async fn connect(&mut self, client: SessionConfig, server: SessionConfig) -> (Peer, Peer) {
+        let (client_conn, server_conn) = self.connect_quic().await;
+
+        let (client_session, server_session) = timeout(async {
+            tokio::join!(
+                Session::connect_with_config(client_conn, client),
+                Session::accept_with_config(server_conn, server),
+            )
+        })
+        .await;
+
+        let (client_session, client_publisher, client_subscriber) =
+            client_session.expect("client SETUP failed");
+        let (server_session, server_publisher, server_subscriber) =
+            server_session.expect("server SETUP failed");
+
+        let client = self.peer(client_session, client_publisher, client_subscriber);
+        let server = self.peer(
+            server_session,
+            server_publisher.expect("no server publisher"),
+            server_subscriber.expect("no server subscriber"),
+        );
+
+        (client, server)
+    }
+
+    /// Run a session, keeping the endpoints around for its lifetime.
+    pub fn peer(&self, session: Session, publisher: Publisher, subscriber: Subscriber) -> Peer {
+        Peer {
+            publisher,
+            subscriber,
+            handle: session.handle(),
+            version: session.version(),
+            run: tokio::spawn(session.run()),
+            _quic: self.client.clone(),
+        }
+    }
+}
+
+fn endpoint(tls: tls::Args) -> quic::Endpoint {
+    let config = quic::Args {
+        bind: "127.0.0.1:0".parse().unwrap(),
+        qlog_dir: None,
+        tls,
+    }
+    .load()
+    .expect("failed to load QUIC config");
+
+    quic::Endpoint::new(config).expect("failed to create QUIC endpoint")
+}
+
+fn cert_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("cert")
+        .join(name)
+}
+
+/// Fail the test instead of hanging if the future doesn't complete in time.
+pub async fn timeout<F: Future>(f: F) -> F::Output {
+    tokio::time::timeout(Duration::from_secs(5), f)
+        .await
+        .expect("timed out")
+}
+
+pub fn namespace() -> TrackNamespace {
+    TrackNamespace::from_utf8_path("test")
+}
+
+/// A track in the test namespace.
+pub fn track(name: &str) -> Track {
+    Track::new(namespace(), name.to_string())
+}
+
+/// Write a group with one subgroup holding the given payloads.
+pub fn write_group(subgroups: &mut SubgroupsWriter, group_id: u64, payloads: &[&str]) {
+    let mut subgroup = subgroups
+        .create(Subgroup {
+            group_id,
+            subgroup_id: 0,
+            priority: 0,
+        })
+        .unwrap();
+
+    for payload in payloads {
+        subgroup.write(Bytes::from(payload.to_string())).unwrap();
+    }
+}
+
+/// Produce a track that caches groups, writing the given groups to it up front.
+pub fn cached_track(name: &str, groups: &[&[&str]]) -> (SubgroupsWriter, TrackReader) {
+    let (writer, reader) = track(name).with_cached_groups(groups.len()).produce();
+    let mut subgroups = writer.subgroups().unwrap();
+    for (group_id, payloads) in groups.iter().enumerate() {
+        write_group(&mut subgroups, group_id as u64, payloads);
+    }
+
+    (subgroups, reader)
+}
+
+/// Serve every SUBSCRIBE and FETCH received by the publisher from the given track.
+pub fn serve(publisher: &Publisher, track: TrackReader) {
+    let mut subscribed = publisher.clone();
+    let reader = track.clone();
+    tokio::spawn(async move {
+        while let Some(subscribed) = subscribed.subscribed().await {
+            let reader = reader.clone();
+            tokio::spawn(async move { subscribed.serve(reader).await });
+        }
+    });
+
+    let mut fetched = publisher.clone();
+    tokio::spawn(async move {
+        while let Some(fetched) = fetched.fetched().await {
+            let reader = track.clone();
+            tokio::spawn(async move { fetched.serve(reader).await });
+        }
+    });
+}
+
+/// Forward a track like a relay: subscribe to it upstream, and serve it to every subscriber downstream
+/// once the upstream subscription is accepted.  The upstream subscription lasts as long as the returned handle.
+pub async fn relay(upstream: &mut Subscriber, downstream: &Publisher, name: &str) -> Subscribe {
+    let (writer, reader) = track(name).produce();
+    let subscribe = upstream.subscribe_handle(writer);
+    timeout(subscribe.ok()).await.unwrap();
+    serve(downstream, reader);
+    subscribe
+}
+
+/// Produce a track to subscribe to, returning the writer to hand to the subscriber.
+pub fn subscriber_track(name: &str) -> (TrackWriter, TrackReader) {
+    track(name).produce()
+}
+
+/// Produce a track to subscribe to that keeps every group, so groups delivered out of order can be inspected.
+pub fn caching_subscriber_track(name: &str) -> (TrackWriter, TrackReader) {
+    track(name).with_cached_groups(8).produce()
+}
+
+/// Wait for the track to start receiving subgroups.
+pub async fn subgroups(track: &TrackReader) -> SubgroupsReader {
+    match timeout(track.mode()).await.unwrap() {
+        TrackReaderMode::Subgroups(subgroups) => subgroups,
+        _ => panic!("expected a subgroups track"),
+    }
+}
+
+/// Wait for a subgroup in the given group to become the latest one.
+pub async fn wait_for_group(subgroups: &mut SubgroupsReader, group_id: u64) -> SubgroupReader {
+    loop {
+        let subgroup = timeout(subgroups.next())
+            .await
+            .unwrap()
+            .expect("track ended");
+        if subgroup.group_id == group_id {
+            return subgroup;
+        }
+    }
+}
+
+/// Read the next count payloads from a subgroup.
+pub async fn read_payloads(subgroup: &mut SubgroupReader, count: usize) -> Vec<String> {
+    let mut payloads = Vec::new();
+    for _ in 0..count {
+        let payload = timeout(subgroup.read_next())
+            .await
+            .unwrap()
+            .expect("subgroup ended");
+        payloads.push(String::from_utf8(payload.to_vec()).unwrap());
+    }
+    payloads
+}
+
+/// Read the IDs of the next count objects from a subgroup, including status objects.
+pub async fn read_object_ids(subgroup: &mut SubgroupReader, count: usize) -> Vec<u64> {
+    let mut object_ids = Vec::new();
+    for _ in 0..count {
+        let object = timeout(subgroup.next())
+            .await
+            .unwrap()
+            .expect("subgroup ended");
+        object_ids.push(object.object_id);
+    }
+    object_ids
+}
+
+/// Wait for a group to be delivered to a caching track, in any order.
+pub async fn wait_for_cached_group(subgroups: &SubgroupsReader, group_id: u64) -> SubgroupReader {
+    timeout(async {
+        loop {
+            if let Some(subgroup) = subgroups.cached(group_id, group_id).pop() {
+                return subgroup;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+}
+
+/// The control stream of a peer that's driven by hand, to see exactly which messages the other side sends.
+/// It only speaks draft-14, which is the format of the plain [Encode] and [Decode] impls.
+pub struct RawControl {
+    pub session: web_transport::Session,
+    send: web_transport::SendStream,
+    recv: web_transport::RecvStream,
+    buffer: BytesMut,
+}
+
+impl RawControl {
+    /// Act as the server, answering CLIENT_SETUP with the given MAX_REQUEST_ID.
+    pub async fn accept(mut session: web_transport::Session, max_request_id: u64) -> Self {
+        let (send, recv) = timeout(session.accept_bi()).await.unwrap();
+        let mut control = Self {
+            session,
+            send,
+            recv,
+            buffer: BytesMut::new(),
+        };
+
+        let _client: setup::Client = control.decode().await;
+        control
+            .encode(&setup::Server {
+                version: setup::Version::DRAFT_14,
+                params: Self::params(max_request_id),
+            })
+            .await;
+
+        control
+    }
+
+    /// Act as the client, sending CLIENT_SETUP with the given MAX_REQUEST_ID.
+    pub async fn connect(mut session: web_transport::Session, max_request_id: u64) -> Self {
+        let (send, recv) = timeout(session.open_bi()).await.unwrap();
+        let mut control = Self {
+            session,
+            send,
+            recv,
+            buffer: BytesMut::new(),
+        };
+
+        control
+            .encode(&setup::Client {
+                versions: [setup::Version::DRAFT_14].into(),
+                params: Self::params(max_request_id),
+            })
+            .await;
+        let _server: setup::Server = control.decode().await;
+
+        control
+    }
+
+    fn params(max_request_id: u64) -> KeyValuePairs {
+        let mut params = KeyValuePairs::new();
+        params.set_intvalue(setup::ParameterType::MaxRequestId.into(), max_request_id);
+        params
+    }
+
+    pub async fn send<M: Into<Message>>(&mut self, msg: M) {
+        self.encode(&msg.into()).await;
+    }
+
+    /// Wait for the next control message.
+    pub async fn recv(&mut self) -> Message {
+        self.decode().await
+    }
+
+    /// Wait for the next control message, or None if nothing arrives in time.
+    pub async fn try_recv(&mut self, wait: Duration) -> Option<Message> {
+        tokio::time::timeout(wait, self.decode()).await.ok()
+    }
+
+    async fn encode<T: Encode>(&mut self, msg: &T) {
+        let mut buf = Vec::new();
+        msg.encode(&mut buf).unwrap();
+        self.send.write(&buf).await.unwrap();
+    }
+
+    async fn decode<T: Decode>(&mut self) -> T {
+        loop {
+            let mut cursor = std::io::Cursor::new(&self.buffer[..]);
+            match T::decode(&mut cursor) {
+                Ok(msg) => {
+                    let consumed = cursor.position() as usize;
+                    self.buffer.advance(consumed);
+                    return msg;
+                }
+                Err(DecodeError::More(_)) => {}
+                Err(err) => panic!("failed to decode: {:?}", err),
+            }
+
+            let chunk = self
+                .recv
+                .read_chunk(usize::MAX)
+                .await
+                .unwrap()
+                .expect("control stream ended");
+            self.buffer.extend_from_slice(&chunk);
+        }
+    }
+}
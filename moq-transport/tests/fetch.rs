@@ -0,0 +1,154 @@
+mod common;
+
+use moq_transport::coding::Location;
+use moq_transport::session::{Fetch, FetchedObject, MAX_QUEUED_FETCH_OBJECTS};
+
+/// Read every object until the publisher finishes the fetch stream.
+async fn read_all(fetch: &mut Fetch) -> Vec<FetchedObject> {
+    let mut objects = Vec::new();
+    while let Some(object) = common::timeout(fetch.next()).await.unwrap() {
+        objects.push(object);
+    }
+    objects
+}
+
+fn summarize(objects: &[FetchedObject]) -> Vec<(u64, u64, String)> {
+    objects
+        .iter()
+        .map(|o| {
+            let payload = String::from_utf8(o.payload.to_vec()).unwrap();
+            (o.group_id, o.object_id, payload)
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn fetch_serves_cached_groups() {
+    let (mut client, server) = common::connect().await;
+
+    let (_writer, track) =
+        common::cached_track("video", &[&["a0", "a1"], &["b0", "b1"], &["c0", "c1"]]);
+    common::serve(&server.publisher, track);
+
+    // An end object_id of 0 requests the whole end group.
+    let mut fetch = client.subscriber.fetch(
+        common::namespace(),
+        "video",
+        Location::new(1, 0),
+        Location::new(2, 0),
+    );
+    let objects = read_all(&mut fetch).await;
+
+    assert_eq!(
+        summarize(&objects),
+        vec![
+            (1, 0, "b0".to_string()),
+            (1, 1, "b1".to_string()),
+            (2, 0, "c0".to_string()),
+            (2, 1, "c1".to_string()),
+        ]
+    );
+    assert_eq!(fetch.end_location(), Some(Location::new(2, 1)));
+}
+
+#[tokio::test]
+async fn fetch_respects_object_range() {
+    let (mut client, server) = common::connect().await;
+
+    let (_writer, track) = common::cached_track("video", &[&["a0", "a1"], &["b0", "b1"]]);
+    common::serve(&server.publisher, track);
+
+    // The end location is exclusive.
+    let mut fetch = client.subscriber.fetch(
+        common::namespace(),
+        "video",
+        Location::new(0, 1),
+        Location::new(1, 1),
+    );
+    let objects = read_all(&mut fetch).await;
+
+    assert_eq!(
+        summarize(&objects),
+        vec![(0, 1, "a1".to_string()), (1, 0, "b0".to_string())]
+    );
+}
+
+#[tokio::test]
+async fn fetch_without_cache_is_rejected() {
+    let (mut client, server) = common::connect().await;
+
+    let (writer, track) = common::track("video").produce();
+    let mut subgroups = writer.subgroups().unwrap();
+    common::write_group(&mut subgroups, 0, &["a0"]);
+    common::serve(&server.publisher, track);
+
+    let mut fetch = client.subscriber.fetch(
+        common::namespace(),
+        "video",
+        Location::new(0, 0),
+        Location::new(0, 0),
+    );
+
+    assert!(common::timeout(fetch.next()).await.is_err());
+}
+
+#[tokio::test]
+async fn fetch_of_evicted_groups_is_rejected() {
+    let (mut client, server) = common::connect().await;
+
+    let (writer, track) = common::track("video").with_cached_groups(2).produce();
+    let mut subgroups = writer.subgroups().unwrap();
+    for group_id in 0..4 {
+        common::write_group(&mut subgroups, group_id, &["x"]);
+    }
+    common::serve(&server.publisher, track);
+
+    // Only groups 2 and 3 are still cached.
+    let mut fetch = client.subscriber.fetch(
+        common::namespace(),
+        "video",
+        Location::new(0, 0),
+        Location::new(1, 0),
+    );
+    assert!(common::timeout(fetch.next()).await.is_err());
+
+    let mut fetch = client.subscriber.fetch(
+        common::namespace(),
+        "video",
+        Location::new(0, 0),
+        Location::new(3, 0),
+    );
+    let objects = read_all(&mut fetch).await;
+    assert_eq!(
+        objects.iter().map(|o| o.group_id).collect::<Vec<_>>(),
+        vec![2, 3]
+    );
+}
+
+#[tokio::test]
+async fn fetch_larger_than_queue_is_flow_controlled() {
+    let (mut client, server) = common::connect().await;
+
+    let count = MAX_QUEUED_FETCH_OBJECTS * 3;
+    let payloads: Vec<String> = (0..count).map(|i| i.to_string()).collect();
+    let payloads: Vec<&str> = payloads.iter().map(String::as_str).collect();
+    let (_writer, track) = common::cached_track("video", &[&payloads]);
+    common::serve(&server.publisher, track);
+
+    let mut fetch = client.subscriber.fetch(
+        common::namespace(),
+        "video",
+        Location::new(0, 0),
+        Location::new(0, 0),
+    );
+
+    // Fall behind so the queue fills up, then make sure nothing was lost or reordered.
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    let objects = read_all(&mut fetch).await;
+
+    let expected: Vec<u64> = (0..count as u64).collect();
+    assert_eq!(
+        objects.iter().map(|o| o.object_id).collect::<Vec<_>>(),
+        expected
+    );
+}
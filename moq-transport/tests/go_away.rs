@@ -0,0 +1,85 @@
+mod common;
+
+use std::time::Duration;
+
+use moq_transport::serve::Tracks;
+use moq_transport::session::{Session, SessionConfig, TerminationCode};
+
+#[tokio::test]
+async fn go_away_migrates_subscriptions_and_announces() {
+    let mut endpoints = common::Endpoints::new();
+    let (mut client, mut server) = endpoints
+        .connect(SessionConfig::default(), SessionConfig::default())
+        .await;
+
+    // The server publishes a track, which the client subscribes to.
+    let (writer, track) = common::track("video").produce();
+    let mut writer = writer.subgroups().unwrap();
+    common::serve(&server.publisher, track.clone());
+
+    let (subscriber_track, reader) = common::caching_subscriber_track("video");
+    let subscribe = client.subscriber.subscribe_handle(subscriber_track);
+    common::timeout(subscribe.ok()).await.unwrap();
+
+    common::write_group(&mut writer, 0, &["a0"]);
+    let subgroups = common::subgroups(&reader).await;
+    common::wait_for_cached_group(&subgroups, 0).await;
+
+    // The client announces a namespace to the server.
+    let (_tracks, _, tracks) = Tracks::new(common::namespace()).produce();
+    let mut publisher = client.publisher.clone();
+    tokio::spawn(async move { publisher.announce(tracks).await });
+    let mut announced = common::timeout(server.subscriber.announced())
+        .await
+        .unwrap();
+    announced.ok().unwrap();
+
+    // The server asks the client to reconnect, which it observes with the new URI.
+    server.handle.go_away("");
+    let uri = common::timeout(client.handle.go_away_received()).await;
+    assert_eq!(uri.as_deref(), Some(""));
+
+    let (client_conn, server_conn) = endpoints.connect_quic().await;
+    let (migrated, accepted) = common::timeout(async {
+        tokio::join!(
+            Session::migrate(
+                client_conn,
+                &mut client.publisher,
+                &mut client.subscriber,
+                SessionConfig::default(),
+            ),
+            Session::accept_with_config(server_conn, SessionConfig::default()),
+        )
+    })
+    .await;
+    let (session, publisher, subscriber) = migrated.unwrap();
+    let _client = endpoints.peer(session, publisher, subscriber);
+    let (session, publisher, subscriber) = accepted.unwrap();
+    let mut new_server = endpoints.peer(session, publisher.unwrap(), subscriber.unwrap());
+
+    // The announce and the subscription are re-issued on the new session.
+    let mut announced = common::timeout(new_server.subscriber.announced())
+        .await
+        .unwrap();
+    assert_eq!(announced.namespace, common::namespace());
+    announced.ok().unwrap();
+
+    common::serve(&new_server.publisher, track);
+
+    // Once the old session is gone, new groups keep reaching the original track.
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    server.handle.close(TerminationCode::NoError, "migrated");
+
+    let delivered = common::timeout(async {
+        for group_id in 1.. {
+            common::write_group(&mut writer, group_id, &["x"]);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            if let Some((latest, _)) = subgroups.latest().filter(|(latest, _)| *latest > 0) {
+                return latest;
+            }
+        }
+        unreachable!()
+    })
+    .await;
+    assert!(delivered >= 1);
+}
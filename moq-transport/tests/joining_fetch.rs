@@ -0,0 +1,117 @@
+mod common;
+
+use std::time::Duration;
+
+use bytes::Bytes;
+use moq_transport::coding::KeyValuePairs;
+use moq_transport::message::FetchType;
+use moq_transport::serve::Subgroup;
+
+#[tokio::test]
+async fn relative_joining_fetch_fills_in_previous_groups() {
+    let (client, server) = common::connect().await;
+
+    let (mut writer, track) = common::cached_track(
+        "video",
+        &[&["a0", "a1"], &["b0", "b1"], &["c0", "c1"], &["d0", "d1"]],
+    );
+    common::serve(&server.publisher, track);
+
+    // Keep the received groups around so we can check them after the fact.
+    let (track, reader) = common::track("video").with_cached_groups(8).produce();
+    let mut subscriber = client.subscriber.clone();
+    tokio::spawn(async move {
+        subscriber
+            .subscribe_joining(track, FetchType::RelativeJoining, 1)
+            .await
+    });
+
+    let mut subgroups = common::subgroups(&reader).await;
+    let mut current = common::wait_for_group(&mut subgroups, 3).await;
+    assert_eq!(common::read_payloads(&mut current, 2).await, ["d0", "d1"]);
+
+    // Give the live subscription a moment to start before publishing the next group.
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    common::write_group(&mut writer, 4, &["e0"]);
+
+    let mut live = common::wait_for_group(&mut subgroups, 4).await;
+    assert_eq!(common::read_payloads(&mut live, 1).await, ["e0"]);
+
+    // Only the one group before the current one was fetched.
+    let groups: Vec<u64> = subgroups
+        .cached(0, u64::MAX)
+        .iter()
+        .map(|subgroup| subgroup.group_id)
+        .collect();
+    assert_eq!(groups, [2, 3, 4]);
+
+    let mut previous = subgroups.cached(2, 2).remove(0);
+    assert_eq!(common::read_payloads(&mut previous, 2).await, ["c0", "c1"]);
+}
+
+#[tokio::test]
+async fn absolute_joining_fetch_starts_at_group() {
+    let (client, server) = common::connect().await;
+
+    let (_writer, track) = common::cached_track("video", &[&["a0"], &["b0"], &["c0"], &["d0"]]);
+    common::serve(&server.publisher, track);
+
+    let (track, reader) = common::track("video").with_cached_groups(8).produce();
+    let mut subscriber = client.subscriber.clone();
+    tokio::spawn(async move {
+        subscriber
+            .subscribe_joining(track, FetchType::AbsoluteJoining, 1)
+            .await
+    });
+
+    let mut subgroups = common::subgroups(&reader).await;
+    common::wait_for_group(&mut subgroups, 3).await;
+
+    let groups: Vec<u64> = subgroups
+        .cached(0, u64::MAX)
+        .iter()
+        .map(|subgroup| subgroup.group_id)
+        .collect();
+    assert_eq!(groups, [1, 2, 3]);
+}
+
+#[tokio::test]
+async fn joining_fetch_keeps_sparse_object_ids() {
+    let (client, server) = common::connect().await;
+
+    let (writer, track) = common::track("video").with_cached_groups(2).produce();
+    let mut writer = writer.subgroups().unwrap();
+    let mut current = Vec::new();
+    for group_id in 0..2 {
+        let mut subgroup = writer
+            .create(Subgroup {
+                group_id,
+                subgroup_id: 0,
+                priority: 0,
+            })
+            .unwrap();
+        subgroup
+            .write_at(0, Bytes::from("x"), KeyValuePairs::new())
+            .unwrap();
+        subgroup
+            .write_at(5, Bytes::from("y"), KeyValuePairs::new())
+            .unwrap();
+        current.push(subgroup);
+    }
+    common::serve(&server.publisher, track);
+
+    let (track, reader) = common::track("video").with_cached_groups(8).produce();
+    let mut subscriber = client.subscriber.clone();
+    tokio::spawn(async move {
+        subscriber
+            .subscribe_joining(track, FetchType::RelativeJoining, 1)
+            .await
+    });
+
+    // Fetched objects keep the publisher's IDs rather than being renumbered.
+    let subgroups = common::subgroups(&reader).await;
+    for group_id in 0..2 {
+        let mut subgroup = common::wait_for_cached_group(&subgroups, group_id).await;
+        assert_eq!(common::read_object_ids(&mut subgroup, 2).await, [0, 5]);
+    }
+}
@@ -0,0 +1,229 @@
+mod common;
+
+use std::time::Duration;
+
+use bytes::Bytes;
+use moq_transport::coding::KeyValuePairs;
+use moq_transport::data::ObjectStatus;
+use moq_transport::serve::{Datagram, Subgroup, TrackReaderMode};
+
+fn extensions(timestamp: u64) -> KeyValuePairs {
+    let mut extensions = KeyValuePairs::new();
+    extensions.set_intvalue(0x2, timestamp);
+    extensions.set_bytesvalue(0x3, b"frame".to_vec());
+    extensions
+}
+
+#[tokio::test]
+async fn subgroup_extension_headers_round_trip() {
+    let (mut client, server) = common::connect().await;
+
+    let (writer, track) = common::track("video").produce();
+    let mut writer = writer.subgroups().unwrap();
+    common::serve(&server.publisher, track);
+
+    let (track, reader) = common::subscriber_track("video");
+    let subscribe = client.subscriber.subscribe_handle(track);
+    common::timeout(subscribe.ok()).await.unwrap();
+
+    let mut subgroup = writer
+        .create(Subgroup {
+            group_id: 0,
+            subgroup_id: 0,
+            priority: 0,
+        })
+        .unwrap();
+    subgroup
+        .write_with_extensions(Bytes::from("a0"), extensions(1000))
+        .unwrap();
+    subgroup.write(Bytes::from("a1")).unwrap();
+
+    let mut subgroups = common::subgroups(&reader).await;
+    let mut subgroup = common::wait_for_group(&mut subgroups, 0).await;
+
+    let mut object = common::timeout(subgroup.next()).await.unwrap().unwrap();
+    assert_eq!(object.extension_headers, extensions(1000));
+    assert_eq!(object.read_all().await.unwrap(), Bytes::from("a0"));
+
+    // Objects without extensions don't pick any up.
+    let object = common::timeout(subgroup.next()).await.unwrap().unwrap();
+    assert_eq!(object.extension_headers, KeyValuePairs::new());
+}
+
+#[tokio::test]
+async fn datagram_extension_headers_round_trip() {
+    let (mut client, server) = common::connect().await;
+
+    let (writer, track) = common::track("audio").produce();
+    let mut writer = writer.datagrams().unwrap();
+    common::serve(&server.publisher, track);
+
+    let (track, reader) = common::subscriber_track("audio");
+    let subscribe = client.subscriber.subscribe_handle(track);
+    common::timeout(subscribe.ok()).await.unwrap();
+
+    // Datagrams may be dropped, so keep sending until one arrives.
+    let mut datagrams = common::timeout(async {
+        for group_id in 0.. {
+            writer
+                .write(Datagram {
+                    group_id,
+                    object_id: 0,
+                    priority: 0,
+                    payload: Bytes::from("x"),
+                    status: ObjectStatus::NormalObject,
+                    extension_headers: extensions(group_id),
+                })
+                .unwrap();
+
+            if let Ok(mode) = tokio::time::timeout(Duration::from_millis(50), reader.mode()).await {
+                match mode.unwrap() {
+                    TrackReaderMode::Datagrams(datagrams) => return datagrams,
+                    _ => panic!("expected a datagrams track"),
+                }
+            }
+        }
+        unreachable!()
+    })
+    .await;
+
+    let datagram = common::timeout(datagrams.read()).await.unwrap().unwrap();
+    assert_eq!(datagram.payload, Bytes::from("x"));
+    assert_eq!(datagram.extension_headers, extensions(datagram.group_id));
+}
+
+#[tokio::test]
+async fn status_objects_end_groups_and_tracks() {
+    let (mut client, server) = common::connect().await;
+
+    let (writer, track) = common::track("video").produce();
+    let mut writer = writer.subgroups().unwrap();
+    common::serve(&server.publisher, track);
+
+    let (track, reader) = common::subscriber_track("video");
+    let subscribe = client.subscriber.subscribe_handle(track);
+    common::timeout(subscribe.ok()).await.unwrap();
+
+    let mut subgroups = None;
+    for (group_id, status) in [(0, ObjectStatus::EndOfGroup), (1, ObjectStatus::EndOfTrack)] {
+        let mut subgroup = writer
+            .create(Subgroup {
+                group_id,
+                subgroup_id: 0,
+                priority: 0,
+            })
+            .unwrap();
+        subgroup.write(Bytes::from("x")).unwrap();
+        subgroup.write_status(status).unwrap();
+
+        // Nothing follows the end of the group or track.
+        assert!(subgroup.write(Bytes::from("y")).is_err());
+
+        // The reader sees the marker explicitly, after the payload.
+        let subgroups = match &mut subgroups {
+            Some(subgroups) => subgroups,
+            None => subgroups.insert(common::subgroups(&reader).await),
+        };
+        let mut subgroup = common::wait_for_group(subgroups, group_id).await;
+
+        let mut object = common::timeout(subgroup.next()).await.unwrap().unwrap();
+        assert_eq!(object.status, ObjectStatus::NormalObject);
+        assert_eq!(object.read_all().await.unwrap(), Bytes::from("x"));
+
+        let object = common::timeout(subgroup.next()).await.unwrap().unwrap();
+        assert_eq!((object.object_id, object.status), (1, status));
+    }
+}
+
+#[tokio::test]
+async fn sparse_object_ids_survive_a_relay() {
+    let mut endpoints = common::Endpoints::new();
+    let (publisher, mut relay_upstream) = endpoints
+        .connect(Default::default(), Default::default())
+        .await;
+    let (mut subscriber, relay_downstream) = endpoints
+        .connect(Default::default(), Default::default())
+        .await;
+
+    let (writer, track) = common::track("video").produce();
+    let mut writer = writer.subgroups().unwrap();
+    common::serve(&publisher.publisher, track);
+
+    let _upstream = common::relay(
+        &mut relay_upstream.subscriber,
+        &relay_downstream.publisher,
+        "video",
+    )
+    .await;
+
+    let (track, reader) = common::subscriber_track("video");
+    let subscribe = subscriber.subscriber.subscribe_handle(track);
+    common::timeout(subscribe.ok()).await.unwrap();
+
+    let mut subgroup = writer
+        .create(Subgroup {
+            group_id: 0,
+            subgroup_id: 0,
+            priority: 0,
+        })
+        .unwrap();
+    subgroup
+        .write_at(0, Bytes::from("a"), KeyValuePairs::new())
+        .unwrap();
+    subgroup
+        .write_at(5, Bytes::from("b"), KeyValuePairs::new())
+        .unwrap();
+    subgroup
+        .write_status_at(9, ObjectStatus::EndOfGroup, KeyValuePairs::new())
+        .unwrap();
+
+    // The relay writes each object with the ID it received, rather than renumbering them.
+    let mut subgroups = common::subgroups(&reader).await;
+    let mut subgroup = common::wait_for_group(&mut subgroups, 0).await;
+    assert_eq!(
+        common::read_object_ids(&mut subgroup, 3).await,
+        vec![0, 5, 9]
+    );
+}
+
+#[tokio::test]
+async fn datagram_status_is_delivered() {
+    let (mut client, server) = common::connect().await;
+
+    let (writer, track) = common::track("audio").produce();
+    let mut writer = writer.datagrams().unwrap();
+    common::serve(&server.publisher, track);
+
+    let (track, reader) = common::subscriber_track("audio");
+    let subscribe = client.subscriber.subscribe_handle(track);
+    common::timeout(subscribe.ok()).await.unwrap();
+
+    // Datagrams may be dropped, so keep ending groups until one arrives.
+    let mut datagrams = common::timeout(async {
+        for group_id in 0.. {
+            writer
+                .write(Datagram {
+                    group_id,
+                    object_id: 0,
+                    priority: 0,
+                    payload: Bytes::new(),
+                    status: ObjectStatus::EndOfGroup,
+                    extension_headers: KeyValuePairs::new(),
+                })
+                .unwrap();
+
+            if let Ok(mode) = tokio::time::timeout(Duration::from_millis(50), reader.mode()).await {
+                match mode.unwrap() {
+                    TrackReaderMode::Datagrams(datagrams) => return datagrams,
+                    _ => panic!("expected a datagrams track"),
+                }
+            }
+        }
+        unreachable!()
+    })
+    .await;
+
+    let datagram = common::timeout(datagrams.read()).await.unwrap().unwrap();
+    assert_eq!(datagram.status, ObjectStatus::EndOfGroup);
+    assert!(datagram.payload.is_empty());
+}
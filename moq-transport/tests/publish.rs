@@ -0,0 +1,114 @@
+mod common;
+
+use moq_transport::serve::ServeError;
+use moq_transport::session::SessionError;
+
+#[tokio::test]
+async fn accepted_publish_delivers_objects() {
+    let (mut client, server) = common::connect().await;
+
+    let (writer, track) = common::track("video").produce();
+    let mut subgroups = writer.subgroups().unwrap();
+    common::write_group(&mut subgroups, 0, &["a0"]);
+
+    let mut publisher = server.publisher.clone();
+    tokio::spawn(async move { publisher.publish(track).await });
+
+    let published = common::timeout(client.subscriber.published())
+        .await
+        .unwrap();
+    assert_eq!(published.track_namespace, common::namespace());
+    assert_eq!(published.track_name, "video");
+
+    let (track, reader) = common::subscriber_track("video");
+    tokio::spawn(published.accept(track));
+
+    // The publish starts at the largest object, so only new groups are pushed.
+    let mut received = common::subgroups(&reader).await;
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    common::write_group(&mut subgroups, 1, &["b0", "b1"]);
+
+    let mut group = common::wait_for_group(&mut received, 1).await;
+    assert_eq!(common::read_payloads(&mut group, 2).await, ["b0", "b1"]);
+}
+
+#[tokio::test]
+async fn rejected_publish_returns_an_error() {
+    let (mut client, server) = common::connect().await;
+
+    let (writer, track) = common::track("video").produce();
+    let _subgroups = writer.subgroups().unwrap();
+
+    let mut publisher = server.publisher.clone();
+    let publish = tokio::spawn(async move { publisher.publish(track).await });
+
+    let published = common::timeout(client.subscriber.published())
+        .await
+        .unwrap();
+    published.close(ServeError::Unauthorized).unwrap();
+
+    let err = common::timeout(publish).await.unwrap().unwrap_err();
+    assert!(matches!(err, SessionError::Serve(ServeError::Unauthorized)));
+}
+
+#[tokio::test]
+async fn dropped_publish_is_uninterested() {
+    let (mut client, server) = common::connect().await;
+
+    let (writer, track) = common::track("video").produce();
+    let _subgroups = writer.subgroups().unwrap();
+
+    let mut publisher = server.publisher.clone();
+    let publish = tokio::spawn(async move { publisher.publish(track).await });
+
+    drop(
+        common::timeout(client.subscriber.published())
+            .await
+            .unwrap(),
+    );
+
+    let err = common::timeout(publish).await.unwrap().unwrap_err();
+    assert!(matches!(err, SessionError::Serve(ServeError::NotFound)));
+}
+
+#[tokio::test]
+async fn subscribed_and_published_track_share_an_alias() {
+    let (mut client, server) = common::connect().await;
+
+    let (writer, track) = common::track("video").produce();
+    let mut subgroups = writer.subgroups().unwrap();
+    common::write_group(&mut subgroups, 0, &["a0"]);
+    common::serve(&server.publisher, track.clone());
+
+    let (subscribed, subscribed_reader) = common::subscriber_track("video");
+    let subscribe = client.subscriber.subscribe_handle(subscribed);
+    let ok = common::timeout(subscribe.ok()).await.unwrap();
+
+    let mut publisher = server.publisher.clone();
+    tokio::spawn(async move { publisher.publish(track).await });
+
+    let published = common::timeout(client.subscriber.published())
+        .await
+        .unwrap();
+    assert_eq!(published.track_alias, ok.track_alias);
+
+    let (pushed, pushed_reader) = common::subscriber_track("video");
+    tokio::spawn(published.accept(pushed));
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+    // The publisher sends a copy of each group per request, and each subscription gets one of them.
+    common::write_group(&mut subgroups, 1, &["b0"]);
+    for reader in [&subscribed_reader, &pushed_reader] {
+        let mut received = common::subgroups(reader).await;
+        let mut group = common::wait_for_group(&mut received, 1).await;
+        assert_eq!(common::read_payloads(&mut group, 1).await, ["b0"]);
+    }
+
+    // Neither subscription was torn down by the other's copy.
+    common::write_group(&mut subgroups, 2, &["c0"]);
+    for reader in [&subscribed_reader, &pushed_reader] {
+        let mut received = common::subgroups(reader).await;
+        let mut group = common::wait_for_group(&mut received, 2).await;
+        assert_eq!(common::read_payloads(&mut group, 1).await, ["c0"]);
+    }
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use std::time::Duration;
+
+use moq_transport::message::{self, Message};
+use moq_transport::session::{Session, SessionConfig};
+
+/// Connect a client session to a server that's driven by hand, which lets the client use request IDs below the
+/// given MAX_REQUEST_ID.
+async fn connect_raw(max_request_id: u64) -> (common::Peer, common::RawControl) {
+    let mut endpoints = common::Endpoints::new();
+    let (client_conn, server_conn) = endpoints.connect_quic().await;
+
+    let (client, server) = common::timeout(async {
+        tokio::join!(
+            Session::connect_with_config(client_conn, SessionConfig::default()),
+            common::RawControl::accept(server_conn, max_request_id),
+        )
+    })
+    .await;
+
+    let (session, publisher, subscriber) = client.expect("client SETUP failed");
+    (endpoints.peer(session, publisher, subscriber), server)
+}
+
+/// Wait for the next SUBSCRIBE, returning its request ID and skipping any REQUESTS_BLOCKED.
+async fn recv_subscribe(server: &mut common::RawControl) -> u64 {
+    loop {
+        match common::timeout(server.recv()).await {
+            Message::Subscribe(msg) => return msg.id,
+            Message::RequestsBlocked(_) => continue,
+            msg => panic!("unexpected message: {:?}", msg),
+        }
+    }
+}
+
+#[tokio::test]
+async fn requests_beyond_the_limit_wait_for_more_ids() {
+    // The client may only use request IDs 0 and 2 until the server grants more.
+    let (mut client, mut server) = connect_raw(4).await;
+
+    let mut subscribes = Vec::new();
+    for i in 0..3 {
+        let (track, reader) = common::subscriber_track(&format!("video{}", i));
+        subscribes.push((client.subscriber.subscribe_handle(track), reader));
+    }
+
+    assert_eq!(recv_subscribe(&mut server).await, 0);
+    assert_eq!(recv_subscribe(&mut server).await, 2);
+
+    // The third request is held back, and the client says it's blocked.
+    match common::timeout(server.recv()).await {
+        Message::RequestsBlocked(msg) => assert_eq!(msg.max_request_id, 4),
+        msg => panic!("expected REQUESTS_BLOCKED, got {:?}", msg),
+    }
+    let held = server.try_recv(Duration::from_millis(200)).await;
+    assert!(held.is_none(), "sent beyond the limit: {:?}", held);
+
+    // Granting more IDs releases it.
+    server.send(message::MaxRequestId { request_id: 6 }).await;
+    assert_eq!(recv_subscribe(&mut server).await, 4);
+}
+
+#[tokio::test]
+async fn cancelled_requests_are_never_sent() {
+    let (mut client, mut server) = connect_raw(4).await;
+
+    let mut subscribes = Vec::new();
+    for i in 0..2 {
+        let (track, reader) = common::subscriber_track(&format!("video{}", i));
+        subscribes.push((client.subscriber.subscribe_handle(track), reader));
+    }
+    assert_eq!(recv_subscribe(&mut server).await, 0);
+    assert_eq!(recv_subscribe(&mut server).await, 2);
+
+    // Give up on a subscription before the server lets the client send it.
+    let (track, _reader) = common::subscriber_track("abandoned");
+    let subscribe = client.subscriber.subscribe_handle(track);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    drop(subscribe);
+
+    // Neither the SUBSCRIBE nor its UNSUBSCRIBE is sent once more IDs are granted.
+    server.send(message::MaxRequestId { request_id: 8 }).await;
+    loop {
+        match server.try_recv(Duration::from_millis(200)).await {
+            Some(Message::RequestsBlocked(_)) => continue,
+            Some(msg) => panic!("unexpected message: {:?}", msg),
+            None => break,
+        }
+    }
+
+    // The next request reuses the abandoned ID, so the server still sees IDs in sequence.
+    let (track, _reader) = common::subscriber_track("video2");
+    let _subscribe = client.subscriber.subscribe_handle(track);
+    assert_eq!(recv_subscribe(&mut server).await, 4);
+    assert!(!client.run.is_finished());
+}
@@ -0,0 +1,326 @@
+mod common;
+
+use std::time::Duration;
+
+use moq_transport::coding::Location;
+use moq_transport::message::{GroupOrder, PublishDoneStatus};
+use moq_transport::serve::{ServeError, TrackReaderMode};
+use moq_transport::session::SessionError;
+
+#[tokio::test]
+async fn subscribe_range_serves_cached_groups() {
+    let (mut client, server) = common::connect().await;
+
+    let (mut writer, track) =
+        common::cached_track("video", &[&["a0", "a1"], &["b0", "b1"], &["c0", "c1"]]);
+    common::serve(&server.publisher, track);
+
+    let (track, reader) = common::caching_subscriber_track("video");
+    let subscribe = client
+        .subscriber
+        .subscribe_range(track, Location::new(1, 1), None);
+
+    let ok = common::timeout(subscribe.ok()).await.unwrap();
+    assert_eq!(ok.largest_location, Some(Location::new(2, 1)));
+
+    let subgroups = common::subgroups(&reader).await;
+
+    // Objects before the start are skipped, even within the start group.
+    let mut group = common::wait_for_cached_group(&subgroups, 1).await;
+    assert_eq!(common::read_payloads(&mut group, 1).await, vec!["b1"]);
+
+    let mut group = common::wait_for_cached_group(&subgroups, 2).await;
+    assert_eq!(common::read_payloads(&mut group, 2).await, vec!["c0", "c1"]);
+
+    // New groups keep arriving after the cached ones.
+    common::write_group(&mut writer, 3, &["d0"]);
+    let mut group = common::wait_for_cached_group(&subgroups, 3).await;
+    assert_eq!(common::read_payloads(&mut group, 1).await, vec!["d0"]);
+
+    assert!(subgroups.cached(0, 0).is_empty());
+}
+
+#[tokio::test]
+async fn subscribe_range_before_cache_is_rejected() {
+    let (mut client, server) = common::connect().await;
+
+    let (writer, track) = common::track("video").with_cached_groups(2).produce();
+    let mut subgroups = writer.subgroups().unwrap();
+    for group_id in 0..4 {
+        common::write_group(&mut subgroups, group_id, &["x"]);
+    }
+    common::serve(&server.publisher, track);
+
+    // Only groups 2 and 3 are still cached.
+    let (track, _reader) = common::caching_subscriber_track("video");
+    let subscribe = client
+        .subscriber
+        .subscribe_range(track, Location::new(0, 0), None);
+
+    let err = common::timeout(subscribe.ok()).await.unwrap_err();
+    assert!(matches!(err, ServeError::InvalidRange), "{:?}", err);
+}
+
+#[tokio::test]
+async fn subscribe_range_ends_after_end_group() {
+    let (mut client, server) = common::connect().await;
+
+    let (mut writer, track) = common::cached_track("video", &[&["a0"], &["b0"]]);
+    common::serve(&server.publisher, track);
+
+    let (track, reader) = common::caching_subscriber_track("video");
+    let subscribe = client
+        .subscriber
+        .subscribe_range(track, Location::new(0, 0), Some(2));
+    common::timeout(subscribe.ok()).await.unwrap();
+
+    common::write_group(&mut writer, 2, &["c0"]);
+
+    let subgroups = common::subgroups(&reader).await;
+    for (group_id, payload) in [(0, "a0"), (1, "b0"), (2, "c0")] {
+        let mut group = common::wait_for_cached_group(&subgroups, group_id).await;
+        assert_eq!(common::read_payloads(&mut group, 1).await, vec![payload]);
+    }
+
+    // The group after the end completes the subscription instead of being delivered.
+    common::write_group(&mut writer, 3, &["d0"]);
+    let _ = common::timeout(subscribe.closed()).await;
+    let done = subscribe.done().expect("no PUBLISH_DONE");
+    assert_eq!(done.status, PublishDoneStatus::SubscriptionEnded);
+    assert!(subgroups.cached(3, 3).is_empty());
+}
+
+#[tokio::test]
+async fn cached_groups_follow_publisher_group_order() {
+    for group_order in [GroupOrder::Ascending, GroupOrder::Descending] {
+        let (mut client, server) = common::connect().await;
+
+        let (writer, track) = common::track("video")
+            .with_group_order(group_order)
+            .with_cached_groups(4)
+            .produce();
+        let mut subgroups = writer.subgroups().unwrap();
+        for (group_id, payload) in ["a0", "b0", "c0", "d0"].iter().enumerate() {
+            common::write_group(&mut subgroups, group_id as u64, &[payload]);
+        }
+        common::serve(&server.publisher, track);
+
+        let (track, reader) = common::caching_subscriber_track("video");
+        let subscribe = client
+            .subscriber
+            .subscribe_range(track, Location::new(0, 0), None);
+
+        let ok = common::timeout(subscribe.ok()).await.unwrap();
+        assert_eq!(ok.group_order, group_order);
+
+        // Every cached group is delivered, not just the first one in the order.
+        let subgroups = common::subgroups(&reader).await;
+        for (group_id, payload) in [(0, "a0"), (1, "b0"), (2, "c0"), (3, "d0")] {
+            let mut group = common::wait_for_cached_group(&subgroups, group_id).await;
+            assert_eq!(common::read_payloads(&mut group, 1).await, vec![payload]);
+        }
+    }
+}
+
+#[tokio::test]
+async fn forward_pauses_and_resumes_delivery() {
+    let (mut client, server) = common::connect().await;
+
+    let (writer, track) = common::track("video").produce();
+    let mut writer = writer.subgroups().unwrap();
+    common::write_group(&mut writer, 0, &["a0"]);
+    common::serve(&server.publisher, track);
+
+    // The subscription is established on standby, but nothing is sent.
+    let (track, reader) = common::caching_subscriber_track("video");
+    let mut subscribe = client.subscriber.subscribe_standby(track);
+    let ok = common::timeout(subscribe.ok()).await.unwrap();
+    assert_eq!(ok.largest_location, Some(Location::new(0, 0)));
+
+    common::write_group(&mut writer, 1, &["b0"]);
+    let idle = tokio::time::timeout(Duration::from_millis(200), reader.mode()).await;
+    assert!(idle.is_err(), "delivered while on standby");
+
+    // Once forwarding is on, new groups are delivered, while those skipped on standby aren't resumed.
+    subscribe.set_forward(true).unwrap();
+    let subgroups = common::timeout(async {
+        for group_id in 2.. {
+            common::write_group(&mut writer, group_id, &["x"]);
+            if let Ok(mode) = tokio::time::timeout(Duration::from_millis(50), reader.mode()).await {
+                return mode.unwrap();
+            }
+        }
+        unreachable!()
+    })
+    .await;
+    let subgroups = match subgroups {
+        TrackReaderMode::Subgroups(subgroups) => subgroups,
+        _ => panic!("expected a subgroups track"),
+    };
+    assert!(subgroups.cached(0, 1).is_empty());
+
+    // Pausing again stops new groups from being sent.
+    subscribe.set_forward(false).unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let (paused, _) = subgroups.latest().unwrap();
+    common::write_group(&mut writer, paused + 1, &["y"]);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(subgroups.cached(paused + 1, paused + 1).is_empty());
+}
+
+#[tokio::test]
+async fn subscribe_handle_reports_ok_and_done() {
+    let (mut client, server) = common::connect().await;
+
+    let (writer, track) = common::track("video")
+        .with_group_order(GroupOrder::Descending)
+        .produce();
+    let mut writer = writer.subgroups().unwrap();
+    common::write_group(&mut writer, 0, &["a0", "a1"]);
+    common::serve(&server.publisher, track);
+
+    let (track, reader) = common::subscriber_track("video");
+    let subscribe = client.subscriber.subscribe_handle(track);
+
+    let ok = common::timeout(subscribe.ok()).await.unwrap();
+    assert_eq!(ok.group_order, GroupOrder::Descending);
+    assert_eq!(ok.largest_location, Some(Location::new(0, 1)));
+    assert!(ok.content_exists());
+    assert_eq!(ok.expires, 0);
+    assert!(subscribe.done().is_none());
+
+    // Objects keep streaming into the track after SUBSCRIBE_OK.
+    let mut subgroups = common::subgroups(&reader).await;
+    for (group_id, payload) in [(1, "b0"), (2, "c0")] {
+        common::write_group(&mut writer, group_id, &[payload]);
+        let mut group = common::wait_for_group(&mut subgroups, group_id).await;
+        assert_eq!(common::read_payloads(&mut group, 1).await, vec![payload]);
+    }
+
+    // Ending the track completes the subscription with PUBLISH_DONE.
+    writer.close(ServeError::Done).unwrap();
+    let _ = common::timeout(subscribe.closed()).await;
+    let done = subscribe.done().expect("no PUBLISH_DONE");
+    assert_eq!(done.status, PublishDoneStatus::TrackEnded);
+    // One stream per group, including group 0, which was the latest when subscribing.
+    assert_eq!(done.stream_count, 3);
+}
+
+#[tokio::test]
+async fn dropping_every_reader_unsubscribes() {
+    let (mut client, mut server) = common::connect().await;
+
+    let (writer, track) = common::track("video").produce();
+    let mut writer = writer.subgroups().unwrap();
+
+    // Serve the subscription ourselves to see when the publisher stops serving it.
+    let served = tokio::spawn(async move {
+        let subscribed = server.publisher.subscribed().await.unwrap();
+        subscribed.serve(track).await
+    });
+
+    let (track, reader) = common::subscriber_track("video");
+    let subscribe = client.subscriber.subscribe_handle(track);
+    common::timeout(subscribe.ok()).await.unwrap();
+
+    common::write_group(&mut writer, 0, &["a0"]);
+    let mut subgroups = common::subgroups(&reader).await;
+    common::wait_for_group(&mut subgroups, 0).await;
+
+    // A remaining reader keeps the subscription alive.
+    let clone = reader.clone();
+    drop(reader);
+    drop(subgroups);
+    let alive = tokio::time::timeout(Duration::from_millis(200), subscribe.closed()).await;
+    assert!(alive.is_err(), "unsubscribed while a reader remains");
+
+    // Once the last one is gone, UNSUBSCRIBE is sent without waiting for another object.
+    drop(clone);
+    common::timeout(subscribe.closed()).await.unwrap();
+
+    // The publisher stops serving it.
+    let served = common::timeout(served).await.unwrap();
+    assert!(
+        matches!(served, Err(SessionError::Serve(ServeError::Cancel))),
+        "{:?}",
+        served
+    );
+}
+
+#[tokio::test]
+async fn unrefreshed_subscription_expires() {
+    let (mut client, server) = common::connect().await;
+
+    let (writer, track) = common::track("video")
+        .with_expires(Duration::from_millis(200))
+        .produce();
+    let _writer = writer.subgroups().unwrap();
+    common::serve(&server.publisher, track);
+
+    let (track, _reader) = common::subscriber_track("video");
+    let subscribe = client.subscriber.subscribe_handle(track);
+    let ok = common::timeout(subscribe.ok()).await.unwrap();
+    assert_eq!(ok.expires, 200);
+
+    // Without a SUBSCRIBE_UPDATE, the publisher ends the subscription once it expires.
+    common::timeout(subscribe.expired()).await;
+    let err = common::timeout(subscribe.closed()).await.unwrap_err();
+    assert!(matches!(err, ServeError::Expired), "{:?}", err);
+    let done = subscribe.done().expect("no PUBLISH_DONE");
+    assert_eq!(done.status, PublishDoneStatus::Expired);
+}
+
+#[tokio::test]
+async fn refreshed_subscription_stays_open() {
+    let (mut client, server) = common::connect().await;
+
+    let (writer, track) = common::track("video")
+        .with_expires(Duration::from_millis(200))
+        .produce();
+    let mut writer = writer.subgroups().unwrap();
+    common::serve(&server.publisher, track);
+
+    let (track, reader) = common::subscriber_track("video");
+    let subscribe = client.subscriber.subscribe_handle(track);
+    common::timeout(subscribe.ok()).await.unwrap();
+
+    // Refreshing outlives several expiry periods.
+    let open =
+        tokio::time::timeout(Duration::from_millis(800), subscribe.refresh_until_closed()).await;
+    assert!(open.is_err(), "closed while refreshing: {:?}", open);
+    assert!(subscribe.done().is_none());
+
+    // And objects are still delivered.
+    common::write_group(&mut writer, 0, &["a0"]);
+    let mut subgroups = common::subgroups(&reader).await;
+    let mut group = common::wait_for_group(&mut subgroups, 0).await;
+    assert_eq!(common::read_payloads(&mut group, 1).await, vec!["a0"]);
+}
+
+#[tokio::test]
+async fn update_start_follows_largest_object() {
+    let (mut client, server) = common::connect().await;
+
+    let (writer, track) = common::track("video").produce();
+    let mut writer = writer.subgroups().unwrap();
+    common::write_group(&mut writer, 0, &["a0", "a1"]);
+    common::serve(&server.publisher, track);
+
+    let (track, _reader) = common::subscriber_track("video");
+    let mut subscribe = client.subscriber.subscribe_handle(track);
+    let ok = common::timeout(subscribe.ok()).await.unwrap();
+    assert_eq!(ok.largest_location, Some(Location::new(0, 1)));
+
+    // The subscription started after the largest object, not at the beginning of the track.
+    let err = subscribe
+        .update(Location::new(0, 0), None, 0, true)
+        .unwrap_err();
+    assert!(matches!(err, ServeError::NotSupported(_)), "{:?}", err);
+
+    // Updates that keep the resolved start are accepted.
+    subscribe.set_forward(false).unwrap();
+    subscribe.refresh().unwrap();
+    subscribe
+        .update(Location::new(0, 2), None, 0, true)
+        .unwrap();
+}
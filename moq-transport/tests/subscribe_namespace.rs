@@ -0,0 +1,90 @@
+mod common;
+
+use moq_transport::coding::TrackNamespace;
+use moq_transport::serve::{ServeError, Tracks, TracksWriter};
+use moq_transport::session::Publisher;
+
+/// Announce a namespace in the background, returning the writer that keeps it alive.
+fn announce(publisher: &Publisher, namespace: &str) -> TracksWriter {
+    let (writer, _, reader) = Tracks::new(TrackNamespace::from_utf8_path(namespace)).produce();
+    let mut publisher = publisher.clone();
+    tokio::spawn(async move { publisher.announce(reader).await });
+    writer
+}
+
+#[tokio::test]
+async fn announces_under_prefix_reach_the_subscription() {
+    let (mut client, mut server) = common::connect().await;
+
+    let prefix = TrackNamespace::from_utf8_path("live");
+    let mut subscription = common::timeout(client.subscriber.subscribe_namespace(prefix.clone()))
+        .await
+        .unwrap();
+
+    // The publisher learns about the prefix, so it can announce what it serves under it.
+    let subscribed = common::timeout(server.publisher.subscribed_namespace())
+        .await
+        .unwrap();
+    assert_eq!(subscribed, prefix);
+    assert!(server
+        .publisher
+        .is_namespace_subscribed(&TrackNamespace::from_utf8_path("live/room")));
+    assert!(!server
+        .publisher
+        .is_namespace_subscribed(&TrackNamespace::from_utf8_path("other")));
+
+    let _other = announce(&server.publisher, "other");
+    let _room = announce(&server.publisher, "live/room");
+
+    let mut announced = common::timeout(subscription.announced())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        announced.info.namespace,
+        TrackNamespace::from_utf8_path("live/room")
+    );
+    announced.ok().unwrap();
+
+    // Namespaces outside the prefix are returned by Subscriber::announced instead.
+    let mut announced = common::timeout(client.subscriber.announced())
+        .await
+        .unwrap();
+    assert_eq!(
+        announced.info.namespace,
+        TrackNamespace::from_utf8_path("other")
+    );
+    announced.ok().unwrap();
+}
+
+#[tokio::test]
+async fn overlapping_prefixes_are_rejected() {
+    let (mut client, _server) = common::connect().await;
+
+    let _live = common::timeout(
+        client
+            .subscriber
+            .subscribe_namespace(TrackNamespace::from_utf8_path("live/room")),
+    )
+    .await
+    .unwrap();
+
+    // Both a longer and a shorter prefix overlap with the existing one.
+    for prefix in ["live/room/camera", "live"] {
+        let res = common::timeout(
+            client
+                .subscriber
+                .subscribe_namespace(TrackNamespace::from_utf8_path(prefix)),
+        )
+        .await;
+        assert!(matches!(res, Err(ServeError::Duplicate)), "{:?}", prefix);
+    }
+
+    let _lobby = common::timeout(
+        client
+            .subscriber
+            .subscribe_namespace(TrackNamespace::from_utf8_path("live/lobby")),
+    )
+    .await
+    .unwrap();
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use std::time::Duration;
+
+use moq_transport::coding::Location;
+use moq_transport::message::GroupOrder;
+use moq_transport::serve::{ServeError, TrackReader};
+use moq_transport::session::{Publisher, TrackStatusCode};
+
+/// Answer the next TRACK_STATUS with the given result.
+fn answer(publisher: &Publisher, result: Result<TrackReader, ServeError>) {
+    let mut publisher = publisher.clone();
+    tokio::spawn(async move {
+        let requested = publisher.track_status_requested().await.unwrap();
+        match result {
+            Ok(track) => requested.respond_ok(&track).unwrap(),
+            Err(err) => requested.respond_error(err).unwrap(),
+        }
+    });
+}
+
+#[tokio::test]
+async fn track_status_reports_the_largest_location() {
+    let (mut client, server) = common::connect().await;
+
+    let (_writer, track) = common::cached_track("video", &[&["a0"], &["b0", "b1"]]);
+    answer(&server.publisher, Ok(track));
+
+    let status = common::timeout(
+        client
+            .subscriber
+            .track_status(&common::namespace(), "video"),
+    )
+    .await
+    .unwrap();
+    assert_eq!(status.status, TrackStatusCode::InProgress);
+    assert_eq!(status.largest_location, Some(Location::new(1, 1)));
+    assert_eq!(status.group_order, GroupOrder::Ascending);
+    assert_eq!(status.expires, 0);
+}
+
+#[tokio::test]
+async fn track_status_of_an_empty_track() {
+    let (mut client, server) = common::connect().await;
+
+    let (writer, track) = common::track("video").produce();
+    let _subgroups = writer.subgroups().unwrap();
+    answer(&server.publisher, Ok(track));
+
+    let status = common::timeout(
+        client
+            .subscriber
+            .track_status(&common::namespace(), "video"),
+    )
+    .await
+    .unwrap();
+    assert_eq!(status.status, TrackStatusCode::NotYetBegun);
+    assert_eq!(status.largest_location, None);
+}
+
+#[tokio::test]
+async fn track_status_error_is_typed() {
+    let (mut client, server) = common::connect().await;
+
+    answer(&server.publisher, Err(ServeError::NotFound));
+
+    let err = common::timeout(
+        client
+            .subscriber
+            .track_status(&common::namespace(), "video"),
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(err, ServeError::NotFound), "{:?}", err);
+}
+
+#[tokio::test]
+async fn abandoned_track_status_keeps_the_session() {
+    let (mut client, server) = common::connect().await;
+
+    // Give up on the first request before it's answered.
+    let (_writer, track) = common::cached_track("video", &[&["a0"]]);
+    let mut subscriber = client.subscriber.clone();
+    let abandoned = tokio::time::timeout(
+        Duration::from_millis(50),
+        subscriber.track_status(&common::namespace(), "video"),
+    )
+    .await;
+    assert!(abandoned.is_err());
+
+    let mut publisher = server.publisher.clone();
+    let requested = common::timeout(publisher.track_status_requested())
+        .await
+        .unwrap();
+    requested.respond_ok(&track).unwrap();
+
+    // The late reply is dropped, and the session carries on.
+    answer(&server.publisher, Ok(track));
+    let status = common::timeout(
+        client
+            .subscriber
+            .track_status(&common::namespace(), "video"),
+    )
+    .await
+    .unwrap();
+    assert_eq!(status.largest_location, Some(Location::new(0, 0)));
+    assert!(!client.run.is_finished());
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use bytes::Bytes;
+use moq_transport::data::DEFAULT_PUBLISHER_PRIORITY;
+use moq_transport::serve::Subgroup;
+use moq_transport::session::SessionConfig;
+use moq_transport::setup::Version;
+
+/// Publish a track from one peer through a relay, and check the subscriber sees the same objects and priorities.
+async fn forward(
+    publisher: &common::Peer,
+    relay_upstream: &mut common::Peer,
+    relay_downstream: &common::Peer,
+    subscriber: &mut common::Peer,
+    name: &str,
+) {
+    let (writer, track) = common::track(name).produce();
+    let mut subgroups = writer.subgroups().unwrap();
+    common::serve(&publisher.publisher, track);
+
+    let _upstream = common::relay(
+        &mut relay_upstream.subscriber,
+        &relay_downstream.publisher,
+        name,
+    )
+    .await;
+
+    let (track, reader) = common::subscriber_track(name);
+    let subscribe = subscriber.subscriber.subscribe_handle(track);
+    common::timeout(subscribe.ok()).await.unwrap();
+
+    // The default priority is left out of draft-15 headers, so both kinds have to survive the trip.
+    for (group_id, priority) in [(0, DEFAULT_PUBLISHER_PRIORITY), (1, 3)] {
+        let mut subgroup = subgroups
+            .create(Subgroup {
+                group_id,
+                subgroup_id: 0,
+                priority,
+            })
+            .unwrap();
+        let payload = format!("{name}{group_id}");
+        subgroup.write(Bytes::from(payload.clone())).unwrap();
+
+        let mut received = common::subgroups(&reader).await;
+        let mut received = common::wait_for_group(&mut received, group_id).await;
+        assert_eq!(received.priority, priority);
+        assert_eq!(common::read_payloads(&mut received, 1).await, vec![payload]);
+    }
+}
+
+#[tokio::test]
+async fn draft_14_and_draft_15_peers_share_a_relay() {
+    let mut endpoints = common::Endpoints::new();
+
+    // The relay accepts both versions, and each peer gets the newest one it offers.
+    let (mut old, mut relay_old) = endpoints
+        .connect(
+            SessionConfig::default().with_versions([Version::DRAFT_14].into()),
+            SessionConfig::default(),
+        )
+        .await;
+    let (mut new, mut relay_new) = endpoints
+        .connect(SessionConfig::default(), SessionConfig::default())
+        .await;
+    assert_eq!(old.version, Version::DRAFT_14);
+    assert_eq!(relay_old.version, Version::DRAFT_14);
+    assert_eq!(new.version, Version::DRAFT_15);
+    assert_eq!(relay_new.version, Version::DRAFT_15);
+
+    // From the draft-14 peer to the draft-15 one, and back the other way.
+    forward(&old, &mut relay_old, &relay_new, &mut new, "old").await;
+    forward(&new, &mut relay_new, &relay_old, &mut old, "new").await;
+}